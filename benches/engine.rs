@@ -0,0 +1,44 @@
+//! CSV parsing and engine benchmarks on synthetic GBM tickers: the per-bar loop with a
+//! `step_batch` strategy and the per-bar FFI path with a `step` strategy.
+//!
+//! Run with `cargo bench --no-default-features --bench engine`; the engine cases embed
+//! Python and need numpy importable from it.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use pyo3::prelude::*;
+use tradekit_rust::bench::{parse_csv, run_engine, strategy, Workload};
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+const HISTORY_SIZE: usize = 20;
+
+fn bench_csv(c: &mut Criterion) {
+    let mut group = c.benchmark_group("csv_parse");
+    for len in SIZES {
+        let workload = Workload::new(1, len, 1).expect("writing benchmark data");
+        group.bench_with_input(BenchmarkId::from_parameter(len), &workload, |b, w| {
+            b.iter(|| parse_csv(black_box(w)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_engine(c: &mut Criterion) {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        for (case, name) in [("bar_loop", "Batch"), ("ffi_strategy", "Step")] {
+            let strategy = strategy(py, name).expect("building the benchmark strategy");
+            let mut group = c.benchmark_group(case);
+            // The per-bar FFI path is slow enough that the largest size only adds run time.
+            for len in &SIZES[..2] {
+                let workload = Workload::new(1, *len, 2).expect("writing benchmark data");
+                group.bench_with_input(BenchmarkId::from_parameter(len), &workload, |b, w| {
+                    b.iter(|| run_engine(py, w, strategy.clone_ref(py), HISTORY_SIZE).unwrap())
+                });
+            }
+            group.finish();
+        }
+    });
+}
+
+criterion_group!(benches, bench_csv, bench_engine);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+//! Indicator kernel benchmarks against straightforward scalar loops.
+//!
+//! Run with `cargo bench --no-default-features --bench indicators`; the default
+//! `extension-module` feature leaves Python symbols unresolved outside an interpreter.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ndarray::Array1;
+use tradekit_rust::indicators::{ewm::ewm, rsi_method::rsi, sma_method::sma, std_method::rolling_std};
+
+// The scalar references are test-only in the library, so include them directly.
+#[path = "../src/indicators/reference.rs"]
+mod reference;
+use reference::{scalar_ema, scalar_rsi, scalar_sma, scalar_std};
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+const WINDOW: usize = 20;
+
+/// Deterministic random walk so runs are comparable.
+fn prices(len: usize) -> Array1<f64> {
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut price = 100.0;
+    Array1::from_iter((0..len).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        price *= 1.0 + ((state >> 11) as f64 / (1u64 << 53) as f64 - 0.5) * 0.02;
+        price
+    }))
+}
+
+fn bench_kernels(c: &mut Criterion) {
+    type Kernel = fn(&Array1<f64>, usize) -> Array1<f64>;
+    let kernels: [(&str, Kernel, Kernel); 4] = [
+        ("sma", sma, scalar_sma),
+        ("ema", ewm, scalar_ema),
+        ("rolling_std", rolling_std, scalar_std),
+        ("rsi", rsi, scalar_rsi),
+    ];
+
+    for (name, chunked, scalar) in kernels {
+        let mut group = c.benchmark_group(name);
+        for len in SIZES {
+            let data = prices(len);
+            group.bench_with_input(BenchmarkId::new("chunked", len), &data, |b, d| b.iter(|| chunked(black_box(d), WINDOW)));
+            group.bench_with_input(BenchmarkId::new("scalar", len), &data, |b, d| b.iter(|| scalar(black_box(d), WINDOW)));
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_kernels);
+criterion_main!(benches);
@@ -0,0 +1,171 @@
+// Attribution of closed-trade PnL to calendar buckets (weekday, month, hour of day) and to
+// holding periods, computed from the trade ledgers in `run` results.
+
+use chrono::{Datelike, NaiveDate, Timelike};
+use chrono_tz::Tz;
+use numpy::PyArray1;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::BTreeMap;
+
+use crate::timestamps;
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+const DEFAULT_DURATION_EDGES: [usize; 6] = [1, 2, 5, 10, 20, 50];
+
+/// One ledger row: an exit of all or part of a position.
+struct LedgerRow {
+    entry_date: String,
+    bars_held: usize,
+    pnl: f64,
+}
+
+/// When a trade was entered, in the exchange's local time.
+struct EntryTime {
+    weekday: usize,
+    month: usize,
+    /// Hour of day; `None` for date-only bars
+    hour: Option<usize>,
+}
+
+impl EntryTime {
+    /// Intraday timestamps (with an offset, or naive ones read in `tz`) are converted to `tz`;
+    /// date-only values are taken as they are.
+    fn parse(s: &str, tz: Tz) -> Option<Self> {
+        if let Some(t) = timestamps::parse_instant(s, tz) {
+            let local = t.with_timezone(&tz);
+            return Some(EntryTime {
+                weekday: local.weekday().num_days_from_monday() as usize,
+                month: local.month0() as usize,
+                hour: Some(local.hour() as usize),
+            });
+        }
+        let date = NaiveDate::parse_from_str(s.get(..10)?, "%Y-%m-%d").ok()?;
+        Some(EntryTime { weekday: date.weekday().num_days_from_monday() as usize, month: date.month0() as usize, hour: None })
+    }
+}
+
+/// Trade count, wins and PnL per bucket, keyed by the bucket's position in its dimension.
+#[derive(Default)]
+struct Table {
+    buckets: BTreeMap<usize, (String, usize, usize, f64)>,
+}
+
+impl Table {
+    fn add(&mut self, order: usize, label: impl FnOnce() -> String, pnl: f64) {
+        let row = self.buckets.entry(order).or_insert_with(|| (label(), 0, 0, 0.0));
+        row.1 += 1;
+        if pnl > 0.0 {
+            row.2 += 1;
+        }
+        row.3 += pnl;
+    }
+
+    fn to_py<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let rows: Vec<&(String, usize, usize, f64)> = self.buckets.values().collect();
+        let out = PyDict::new(py);
+        out.set_item("bucket", rows.iter().map(|r| r.0.as_str()).collect::<Vec<_>>())?;
+        out.set_item("trades", PyArray1::from_vec(py, rows.iter().map(|r| r.1).collect()))?;
+        out.set_item("wins", PyArray1::from_vec(py, rows.iter().map(|r| r.2).collect()))?;
+        out.set_item("win_rate_pct", PyArray1::from_vec(py, rows.iter().map(|r| r.2 as f64 / r.1 as f64 * 100.0).collect()))?;
+        out.set_item("pnl", PyArray1::from_vec(py, rows.iter().map(|r| r.3).collect()))?;
+        out.set_item("avg_pnl", PyArray1::from_vec(py, rows.iter().map(|r| r.3 / r.1 as f64).collect()))?;
+        Ok(out)
+    }
+}
+
+/// Label of holding-period bucket `k` for `edges`: "0", "2-4", "50+" and so on.
+fn duration_label(edges: &[usize], k: usize) -> String {
+    let lo = if k == 0 { 0 } else { edges[k - 1] };
+    match edges.get(k) {
+        None => format!("{}+", lo),
+        Some(&hi) if hi == lo + 1 => lo.to_string(),
+        Some(&hi) => format!("{}-{}", lo, hi - 1),
+    }
+}
+
+fn read_ledger(ledger: &PyAny, rows: &mut Vec<LedgerRow>) -> PyResult<()> {
+    let column = |key: &str| ledger.get_item(key)
+        .map_err(|_| PyValueError::new_err(format!("trade ledger has no '{}'", key)));
+    let entry_dates: Vec<String> = column("entry_date")?.extract()?;
+    let entry_index: Vec<usize> = column("entry_index")?.extract()?;
+    let exit_index: Vec<usize> = column("exit_index")?.extract()?;
+    let pnl: Vec<f64> = column("pnl")?.extract()?;
+    for (k, entry_date) in entry_dates.into_iter().enumerate() {
+        rows.push(LedgerRow { entry_date, bars_held: exit_index[k].saturating_sub(entry_index[k]), pnl: pnl[k] });
+    }
+    Ok(())
+}
+
+/// Ledger rows of a `run` result (every ticker's details), of one ticker's details or of a
+/// single trade ledger.
+fn collect_rows(source: &PyAny) -> PyResult<Vec<LedgerRow>> {
+    let mut rows = Vec::new();
+    if let Ok(details) = source.get_item("details") {
+        for ticker in details.call_method0("keys")?.iter()? {
+            read_ledger(details.get_item(ticker?)?.get_item("trades")?, &mut rows)?;
+        }
+    } else if let Ok(ledger) = source.get_item("trades") {
+        read_ledger(ledger, &mut rows)?;
+    } else {
+        read_ledger(source, &mut rows)?;
+    }
+    Ok(rows)
+}
+
+/// Attributes realized PnL to the weekday, month and (for intraday bars) hour of each trade's
+/// entry and to its holding period in bars. `results` is a dict returned by `run` / `update`,
+/// one ticker's details or a single `trades` ledger; every ledger row (a full or partial exit)
+/// counts as one trade, and a trade wins when its net `pnl` is positive.
+///
+/// Intraday timestamps are read in `timezone` (default "UTC", which also suits the UTC
+/// timestamps of engines with a timezone) before bucketing. `duration_edges` are the
+/// increasing bar counts that start each holding-period bucket after the first (default
+/// [1, 2, 5, 10, 20, 50]). Returns {"weekday", "month", "hour", "duration"} tables, each a
+/// dict of `bucket`, `trades`, `wins`, `win_rate_pct`, `pnl` and `avg_pnl` columns over the
+/// non-empty buckets in natural order; "hour" is empty for daily data.
+#[pyfunction]
+#[pyo3(name = "pnl_attribution")]
+fn py_pnl_attribution(
+    py: Python<'_>,
+    results: &PyAny,
+    duration_edges: Option<Vec<usize>>,
+    timezone: Option<&str>,
+) -> PyResult<PyObject> {
+    let edges = duration_edges.unwrap_or_else(|| DEFAULT_DURATION_EDGES.to_vec());
+    if edges.first() == Some(&0) || edges.windows(2).any(|w| w[0] >= w[1]) {
+        return Err(PyValueError::new_err("duration_edges must be positive and strictly increasing"));
+    }
+    let tz = timezone.map(timestamps::parse_tz).transpose()?.unwrap_or(Tz::UTC);
+
+    let (mut weekday, mut month, mut hour, mut duration) = (Table::default(), Table::default(), Table::default(), Table::default());
+    for row in collect_rows(results)? {
+        let k = edges.partition_point(|&e| e <= row.bars_held);
+        duration.add(k, || duration_label(&edges, k), row.pnl);
+        let Some(entry) = EntryTime::parse(&row.entry_date, tz) else {
+            log::warn!("pnl_attribution: cannot read entry date '{}', left out of the calendar tables", row.entry_date);
+            continue;
+        };
+        weekday.add(entry.weekday, || WEEKDAYS[entry.weekday].to_string(), row.pnl);
+        month.add(entry.month, || MONTHS[entry.month].to_string(), row.pnl);
+        if let Some(h) = entry.hour {
+            hour.add(h, || format!("{:02}:00", h), row.pnl);
+        }
+    }
+
+    let out = PyDict::new(py);
+    out.set_item("weekday", weekday.to_py(py)?)?;
+    out.set_item("month", month.to_py(py)?)?;
+    out.set_item("hour", hour.to_py(py)?)?;
+    out.set_item("duration", duration.to_py(py)?)?;
+    Ok(out.to_object(py))
+}
+
+pub fn register(py: Python<'_>, parent: &PyModule) -> PyResult<()> {
+    let m = PyModule::new(py, "analysis")?;
+    m.add_function(wrap_pyfunction!(py_pnl_attribution, m)?)?;
+    parent.add_submodule(m)?;
+    Ok(())
+}
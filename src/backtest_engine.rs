@@ -1,14 +1,113 @@
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
-use numpy::PyArray1; // Ensure you have "numpy" in your Cargo.toml features
-use std::io::{BufReader, BufRead};
-use std::fs::File;
+use pyo3::types::{PyDict, PyList, PySlice};
+use numpy::{PyArray1, PyReadonlyArray1}; // Ensure you have "numpy" in your Cargo.toml features
 use glob::glob;
 use serde::{Serialize, Deserialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use chrono_tz::Tz;
+use ndarray::Array1;
+
+use crate::patterns;
+use crate::series;
+use crate::timestamps;
+use crate::stats::{
+    drawdowns, histogram, kurtosis, max_drawdown, mean, pct_changes, sharpe_ratio, skewness, std_sample,
+    underwater_curve, TRADING_DAYS_PER_YEAR,
+};
+
+mod fills;
+mod history;
+mod lots;
+mod benchmark;
+mod budget;
+mod builder;
+mod dataset;
+mod debug;
+mod delisting;
+mod errors;
+mod exposure;
+mod optimize;
+mod orders;
+mod overlay;
+mod pairs;
+mod participation;
+mod regimes;
+mod risk_free;
+mod rules;
+mod sampling;
+mod schedule;
+mod search;
+mod sensitivity;
+mod self_check;
+mod signal_price;
+mod sizing;
+mod rotation;
+mod state;
+mod strategy_scope;
+#[cfg(feature = "arrow")]
+mod stream;
+#[cfg(not(feature = "arrow"))]
+mod stream {
+    use pyo3::exceptions::PyValueError;
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+
+    /// Stand-in when built without the `arrow` feature; opening a stream fails.
+    pub(super) enum ResultStream {}
+
+    impl ResultStream {
+        pub fn open(_path: &str) -> PyResult<Self> {
+            Err(PyValueError::new_err("stream_to needs tradekit_rust built with the 'arrow' feature"))
+        }
+        pub fn write(&mut self, _ticker: &str, _detail: &PyDict) -> PyResult<()> { match *self {} }
+        pub fn finish(self) -> PyResult<()> { match self {} }
+    }
+}
+mod symbols;
+mod synthetic;
+mod timing;
+mod trace;
+mod universes;
+mod update;
+mod validation;
+mod walk_forward;
+
+use fills::FillModel;
+use lots::{closed_lots_to_py, ClosedLot};
+use benchmark::Benchmark;
+use budget::{DetailBudget, Kept};
+use delisting::Delisting;
+use errors::{OnError, TickerError};
+pub use budget::LazyDetails;
+pub use builder::BacktestEngineBuilder;
+pub use debug::DebugEngine;
+pub use history::HistoryBuffer;
+use orders::OrderLog;
+use overlay::EquityOverlay;
+use participation::{Participation, VolumeLimit};
+use regimes::Regimes;
+use risk_free::RiskFreeRate;
+use sampling::SharpeSampling;
+use signal_price::SignalPrice;
+use rules::{EntryRules, Overrides, SignalFilters};
+use sizing::{PositionSizer, TradeRecord};
+use state::{EngineState, TickerState};
+use strategy_scope::StrategyScope;
+use stream::ResultStream;
+use symbols::SymbolSpec;
+use schedule::Scheduling;
+use exposure::Exposure;
+use synthetic::Synthetic;
+use timing::TickerTiming;
+use validation::NanPolicy;
+use trace::{BarAction, BarTrace};
 
 const INITIAL_CAPITAL_PER_STOCK: f64 = 10000.0;
-const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+const RETURN_HISTOGRAM_BINS: usize = 50;
+const TOP_DRAWDOWNS: usize = 5;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct StockMetric {
@@ -16,12 +115,189 @@ struct StockMetric {
     final_balance: f64,
     trades: i32,
     wins: i32,
+    gross_wins: i32,
+    net_wins: i32,
+    breakevens: i32,
     roi_pct: f64,
     buy_and_hold_pct: f64,
     alpha_pct: f64,
     max_drawdown_pct: f64,
     sharpe: f64,
     n_periods: usize,
+    time_in_market_pct: f64,
+    avg_exposure_pct: f64,
+    annual_turnover: f64,
+    avg_holding_bars: f64,
+    /// Betas of the strategy's equity and of the ticker's closes; set only with a benchmark.
+    beta: Option<f64>,
+    asset_beta: Option<f64>,
+    /// The ticker was delisted at its last bar
+    #[serde(default)]
+    delisted: bool,
+}
+
+impl StockMetric {
+    fn to_py<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let d = PyDict::new(py);
+        d.set_item("ticker", &self.ticker)?;
+        d.set_item("final_balance", self.final_balance)?;
+        d.set_item("trades", self.trades)?;
+        d.set_item("wins", self.wins)?;
+        d.set_item("gross_wins", self.gross_wins)?;
+        d.set_item("net_wins", self.net_wins)?;
+        d.set_item("breakevens", self.breakevens)?;
+        d.set_item("roi_pct", self.roi_pct)?;
+        d.set_item("buy_and_hold_pct", self.buy_and_hold_pct)?;
+        d.set_item("alpha_pct", self.alpha_pct)?;
+        d.set_item("max_drawdown_pct", self.max_drawdown_pct)?;
+        d.set_item("sharpe", self.sharpe)?;
+        d.set_item("n_periods", self.n_periods)?;
+        d.set_item("time_in_market_pct", self.time_in_market_pct)?;
+        d.set_item("avg_exposure_pct", self.avg_exposure_pct)?;
+        d.set_item("annual_turnover", self.annual_turnover)?;
+        d.set_item("avg_holding_bars", self.avg_holding_bars)?;
+        d.set_item("beta", self.beta)?;
+        d.set_item("asset_beta", self.asset_beta)?;
+        d.set_item("delisted", self.delisted)?;
+        Ok(d)
+    }
+
+    fn from_py(d: &PyDict) -> PyResult<Self> {
+        fn get<'a, T: FromPyObject<'a>>(d: &'a PyDict, key: &str) -> PyResult<T> {
+            d.get_item(key)
+                .ok_or_else(|| PyValueError::new_err(format!("metrics entry has no '{}'", key)))?
+                .extract()
+        }
+        fn get_opt(d: &PyDict, key: &str) -> PyResult<Option<f64>> {
+            Ok(d.get_item(key).map(|v| v.extract()).transpose()?.flatten())
+        }
+        Ok(StockMetric {
+            ticker: get(d, "ticker")?,
+            final_balance: get(d, "final_balance")?,
+            trades: get(d, "trades")?,
+            wins: get(d, "wins")?,
+            gross_wins: get(d, "gross_wins")?,
+            net_wins: get(d, "net_wins")?,
+            breakevens: get(d, "breakevens")?,
+            roi_pct: get(d, "roi_pct")?,
+            buy_and_hold_pct: get(d, "buy_and_hold_pct")?,
+            alpha_pct: get(d, "alpha_pct")?,
+            max_drawdown_pct: get(d, "max_drawdown_pct")?,
+            sharpe: get(d, "sharpe")?,
+            n_periods: get(d, "n_periods")?,
+            time_in_market_pct: get(d, "time_in_market_pct")?,
+            avg_exposure_pct: get(d, "avg_exposure_pct")?,
+            annual_turnover: get(d, "annual_turnover")?,
+            avg_holding_bars: get(d, "avg_holding_bars")?,
+            beta: get_opt(d, "beta")?,
+            asset_beta: get_opt(d, "asset_beta")?,
+            delisted: d.get_item("delisted").map(|v| v.extract()).transpose()?.unwrap_or(false),
+        })
+    }
+}
+
+/// One exit fill: a whole round trip, or one leg of a position scaled out over several sells.
+/// PnL and commission cover the shares sold in this leg at the average entry price; the last
+/// leg has `closes_position` set. Excursions are measured from the entry price using the highs
+/// and lows of the bars after the entry bar, up to and including the exit bar.
+#[derive(Debug, Clone)]
+struct Trade {
+    entry_index: usize,
+    exit_index: usize,
+    entry_date: String,
+    exit_date: String,
+    entry_price: f64,
+    exit_price: f64,
+    shares: f64,
+    closes_position: bool,
+    gross_pnl: f64,
+    pnl: f64,
+    commission: f64,
+    outcome: TradeOutcome,
+    mae_pct: f64,
+    mfe_pct: f64,
+}
+
+/// Which PnL decides whether a trade counts towards `wins`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WinBasis {
+    Gross,
+    Net,
+}
+
+/// A strategy decision for one bar: `side` 1 buys, -1 sells, anything else holds. `fraction`
+/// is the share of the open position a sell closes; buys use the position sizer. A `target`
+/// exposure (from a float signal) is turned into a side and fraction by the engine.
+#[derive(Debug, Clone, Copy)]
+struct Signal {
+    side: i32,
+    fraction: f64,
+    target: Option<f64>,
+}
+
+impl Signal {
+    fn full(side: i32) -> Self {
+        Signal { side, fraction: 1.0, target: None }
+    }
+
+    /// Reads an int signal, a float target exposure in [-1, 1] or a `(signal, fraction)`
+    /// tuple, e.g. `(-1, 0.5)` to sell half.
+    fn from_py(obj: &PyAny) -> PyResult<Self> {
+        if let Ok(side) = obj.extract::<i32>() {
+            return Ok(Signal::full(side));
+        }
+        if let Ok(target) = obj.extract::<f64>() {
+            if !(-1.0..=1.0).contains(&target) {
+                return Err(PyValueError::new_err(format!("float signal must be in [-1, 1], got {}", target)));
+            }
+            return Ok(Signal { side: 0, fraction: 1.0, target: Some(target) });
+        }
+        let (side, fraction): (i32, f64) = obj.extract()?;
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            return Err(PyValueError::new_err(format!("signal fraction must be in (0, 1], got {}", fraction)));
+        }
+        Ok(Signal { side, fraction, target: None })
+    }
+
+    /// The trade that moves `exposure` (invested share of equity) to the target: a buy of
+    /// `fraction` of equity, a sell of `fraction` of the position, or a hold when the two
+    /// differ by less than `threshold`. The engine is long only, so negative targets mean flat.
+    fn rebalance(target: f64, exposure: f64, threshold: f64) -> Self {
+        let target = target.max(0.0);
+        let delta = target - exposure;
+        if delta.abs() < threshold || delta == 0.0 {
+            Signal { side: 0, fraction: 1.0, target: Some(target) }
+        } else if delta > 0.0 {
+            Signal { side: 1, fraction: delta, target: Some(target) }
+        } else {
+            Signal { side: -1, fraction: (-delta / exposure).min(1.0), target: Some(target) }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TradeOutcome {
+    Win,
+    Loss,
+    Breakeven,
+}
+
+impl TradeOutcome {
+    /// A trade whose return on the capital committed is within `breakeven_pct` of zero is breakeven.
+    fn classify(pnl: f64, cost_basis: f64, breakeven_pct: f64) -> Self {
+        let return_pct = if cost_basis > 0.0 { pnl / cost_basis * 100.0 } else { 0.0 };
+        if return_pct.abs() <= breakeven_pct { TradeOutcome::Breakeven }
+        else if return_pct > 0.0 { TradeOutcome::Win }
+        else { TradeOutcome::Loss }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            TradeOutcome::Win => "win",
+            TradeOutcome::Loss => "loss",
+            TradeOutcome::Breakeven => "breakeven",
+        }
+    }
 }
 
 #[pyclass]
@@ -30,310 +306,2200 @@ pub struct BacktestEngine {
     history_size: usize,
     data_folder: String,
     risk_free_rate_annual: f64,
+    commission_rate: f64,
+    breakeven_pct: f64,
+    win_basis: WinBasis,
+    strict: bool,
+    /// Zone of naive intraday timestamps, unless overridden per ticker
+    timezone: Option<Tz>,
+    ticker_timezones: HashMap<String, Tz>,
+    fill_model: Box<dyn FillModel>,
+    position_sizer: Box<dyn PositionSizer>,
+    symbol_specs: HashMap<String, SymbolSpec>,
+    entry_rules: EntryRules,
+    signal_filters: SignalFilters,
+    /// Smallest exposure change a float target signal trades for
+    rebalance_threshold: f64,
+    regimes: Option<Regimes>,
+    benchmark: Option<Benchmark>,
+    equity_overlay: Option<EquityOverlay>,
+    /// Synthetic instruments by name, sorted
+    synthetics: Vec<(String, Synthetic)>,
+    detail_budget: Option<DetailBudget>,
+    scheduling: Scheduling,
+    delistings: HashMap<String, Delisting>,
+    /// Date-indexed rates replacing `risk_free_rate_annual` when set
+    risk_free: Option<RiskFreeRate>,
+    nan_policy: NanPolicy,
+    /// Price of each bar in the history strategies see
+    signal_price: SignalPrice,
+    on_error: OnError,
+    sharpe_sampling: SharpeSampling,
+    strategy_scope: StrategyScope,
+    /// Round every quantity down to whole shares where no lot size is given
+    whole_shares: bool,
+    volume_limit: Option<VolumeLimit>,
+    /// Pass `step` a view of the history ring rather than a copy
+    history_view: bool,
 }
 
 #[pymethods]
 impl BacktestEngine {
+    /// `commission_bps` is charged on the notional of every fill. A closed trade whose return is
+    /// within `breakeven_pct` percent of zero counts as breakeven; otherwise `win_basis`
+    /// ("net" after commissions, or "gross") decides whether it is a win. With `strict=True`,
+    /// a run that matches no data files or skips every file raises instead of returning warnings.
+    ///
+    /// `timezone` (an IANA name such as "Asia/Kolkata") is the exchange zone of naive intraday
+    /// timestamps; `ticker_timezones` overrides it per ticker. When a zone applies, intraday
+    /// timestamps are normalized to UTC so tickers from different exchanges align, and details
+    /// carry each bar's `sessions` date in the exchange zone. Date-only rows are left as they are.
+    ///
+    /// `fill_model` sets the price of entries and exits in `run`, `update` and `run_signals`:
+    /// "close" (default), "next_open", "vwap" (bar typical price) or "worst" (buy at the high,
+    /// sell at the low). Positions are still marked to market at the close.
+    ///
+    /// `symbols` maps tickers to trading constraints, e.g. `{"INFY": {"lot_size": 1,
+    /// "tick_size": 0.05, "min_notional": 500}}`. Fill prices are rounded to the tick against the
+    /// trader, quantities down to whole lots (leftover cash stays uninvested), and orders below
+    /// the minimum value are ignored.
+    ///
+    /// `entry_rules` limits when buy signals may open a position: `min_bars_between_trades`
+    /// after any exit, `reentry_after_loss_bars` after a losing exit and `max_trades_per_day`
+    /// entries per session. Refused buys are listed per ticker in `overridden_signals` with the
+    /// rule that refused them; `signals` keeps what the strategy asked for.
+    ///
+    /// `signal_filters` stabilizes noisy strategies: a buy or sell acts only once the strategy
+    /// has emitted it `confirm_bars` bars in a row, sells need `exit_confirm_bars` in a row
+    /// instead when given (a longer exit confirmation than entry one adds hysteresis), and no
+    /// sell exits before `min_hold_bars` bars in the position. Held-back signals act as holds
+    /// and are listed in `overridden_signals` with the filter's name.
+    ///
+    /// Strategies may also return a float in [-1, 1] as the target share of equity to hold,
+    /// e.g. 0.6 for 60% long. The engine buys, adds to or sells down the position to reach it
+    /// once it differs from the current exposure by at least `rebalance_threshold` (default
+    /// 0.05); the engine is long only, so targets at or below 0 mean flat.
+    ///
+    /// `position_sizer` picks the share of the balance each entry invests; the rest stays in
+    /// cash. "full" (default) invests everything, "fixed_fraction" invests `fraction`, "kelly"
+    /// uses the running win rate and payoff ratio times `scale` (with `fraction` until
+    /// `min_trades` round trips), and "vol_target" / "atr_target" scale to an annualized
+    /// `target_vol` (default 0.15) from the return std or ATR over `window` bars (default 20).
+    /// Parameters go in `sizer_params`.
+    ///
+    /// Everything is checked here rather than on first use: `history_size` must be at least 1,
+    /// `data_folder` an existing directory, `commission_bps` in [0, 10000) and the rates finite.
     #[new]
-    fn new(strategy: PyObject, history_size: usize, data_folder: String, risk_free_rate_annual: Option<f64>) -> Self {
-        BacktestEngine { 
-            strategy, 
-            history_size, 
-            data_folder, 
-            risk_free_rate_annual: risk_free_rate_annual.unwrap_or(0.0),
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        strategy: PyObject,
+        history_size: usize,
+        data_folder: String,
+        risk_free_rate_annual: Option<f64>,
+        commission_bps: Option<f64>,
+        breakeven_pct: Option<f64>,
+        win_basis: Option<String>,
+        strict: Option<bool>,
+        timezone: Option<String>,
+        ticker_timezones: Option<HashMap<String, String>>,
+        fill_model: Option<String>,
+        symbols: Option<HashMap<String, HashMap<String, f64>>>,
+        entry_rules: Option<HashMap<String, usize>>,
+        position_sizer: Option<String>,
+        sizer_params: Option<HashMap<String, f64>>,
+        signal_filters: Option<HashMap<String, usize>>,
+        rebalance_threshold: Option<f64>,
+    ) -> PyResult<Self> {
+        let mut builder = BacktestEngine::builder()
+            .strategy(strategy)
+            .history_size(history_size)
+            .data_folder(data_folder);
+        if let Some(rate) = risk_free_rate_annual {
+            builder = builder.risk_free_rate_annual(rate);
+        }
+        if let Some(bps) = commission_bps {
+            builder = builder.commission_bps(bps);
+        }
+        if let Some(pct) = breakeven_pct {
+            builder = builder.breakeven_pct(pct);
+        }
+        if let Some(basis) = win_basis {
+            builder = builder.win_basis(basis);
+        }
+        if let Some(strict) = strict {
+            builder = builder.strict(strict);
+        }
+        if let Some(tz) = timezone {
+            builder = builder.timezone(tz);
         }
+        if let Some(zones) = ticker_timezones {
+            builder = builder.ticker_timezones(zones);
+        }
+        if let Some(name) = fill_model {
+            builder = builder.fill_model(name);
+        }
+        if let Some(symbols) = symbols {
+            builder = builder.symbols(symbols);
+        }
+        if let Some(rules) = entry_rules {
+            builder = builder.entry_rules(rules);
+        }
+        if position_sizer.is_some() || sizer_params.is_some() {
+            builder = builder.position_sizer(position_sizer.unwrap_or_else(|| "full".to_string()), sizer_params.unwrap_or_default());
+        }
+        if let Some(filters) = signal_filters {
+            builder = builder.signal_filters(filters);
+        }
+        if let Some(threshold) = rebalance_threshold {
+            builder = builder.rebalance_threshold(threshold);
+        }
+        builder.build()
+    }
+
+    /// Attaches regime labels (e.g. "bull", "bear", "high_vol") keyed by date or session date;
+    /// `None` removes them. While set, `strategy.step` also receives `regime=` the label of the
+    /// last bar it can see (or None), and results carry per-bar `regimes` plus `regime_metrics`
+    /// with ROI, Sharpe and win rate per regime for each ticker and the portfolio.
+    fn set_regimes(&mut self, labels: Option<HashMap<String, String>>) {
+        self.regimes = labels.map(Regimes::new);
+    }
+
+    /// Attaches benchmark closes keyed by date or session date; `None` removes them. While set,
+    /// each ticker's metrics carry the full-period `beta` of the strategy's returns and the
+    /// `asset_beta` of the ticker itself against the benchmark, and details carry the strategy's
+    /// `rolling_beta` over `window` bars (default 60), usable directly as a hedge ratio.
+    fn set_benchmark(&mut self, closes: Option<HashMap<String, f64>>, window: Option<usize>) -> PyResult<()> {
+        let window = window.unwrap_or(60);
+        if window < 2 {
+            return Err(PyValueError::new_err("benchmark window must be at least 2 bars"));
+        }
+        self.benchmark = closes.map(|c| Benchmark::new(c, window));
+        Ok(())
+    }
+
+    /// Defines synthetic instruments as weighted sums of tickers in the data folder, e.g.
+    /// `{"SPREAD": {"A": 2.0, "B": -1.0}}`; `None` removes them. `run`, `update` and the
+    /// optimizers price each one bar by bar on the dates all its legs share and trade it as a
+    /// single ticker after the data files (which are still run on their own). A synthetic
+    /// whose price is not positive on some bar is skipped as unreadable.
+    fn set_synthetics(&mut self, definitions: Option<HashMap<String, HashMap<String, f64>>>) -> PyResult<()> {
+        let mut synthetics = definitions.unwrap_or_default()
+            .into_iter()
+            .map(|(name, weights)| Ok((name.clone(), Synthetic::new(&name, weights)?)))
+            .collect::<PyResult<Vec<_>>>()?;
+        synthetics.sort_by(|a, b| a.0.cmp(&b.0));
+        self.synthetics = synthetics;
+        Ok(())
+    }
+
+    /// Trades each ticker's equity curve: the strategy's own signals are followed on a shadow
+    /// curve, and while it is below its `window`-bar moving average new entries invest only
+    /// `scale` (default 0, pausing them) of what the position sizer would. Exits are never
+    /// held back. Paused entries are listed in `overridden_signals` as "equity_overlay", and
+    /// details carry `equity_overlay` with the per-bar shadow `equity` and entry `scale`.
+    /// `window=None` removes the overlay.
+    fn set_equity_overlay(&mut self, window: Option<usize>, scale: Option<f64>) -> PyResult<()> {
+        self.equity_overlay = window.map(|w| EquityOverlay::new(w, scale.unwrap_or(0.0))).transpose()?;
+        Ok(())
+    }
+
+    /// Limits the per-ticker `details` kept by `run`, for universes too large to hold in memory;
+    /// calling it with no arguments removes the limits. Metrics and the portfolio curves always
+    /// cover every ticker.
+    ///
+    /// - `downsample=N` keeps every Nth bar (and the last) of each per-bar series, adds
+    ///   `bar_index` with the kept bar positions and recomputes `returns` between kept bars.
+    /// - `min_metrics` maps metric names to minimums, e.g. `{"sharpe": 0.5}`; tickers below any
+    ///   of them get no details.
+    /// - `spill_dir` pickles each ticker's details to `<spill_dir>/<ticker>.pkl` as soon as it
+    ///   finishes; `details` is then a `LazyDetails` mapping that loads a ticker on access.
+    ///
+    /// Results with downsampled or spilled details cannot be passed to `update`.
+    fn set_detail_budget(
+        &mut self,
+        downsample: Option<usize>,
+        min_metrics: Option<HashMap<String, f64>>,
+        spill_dir: Option<String>,
+    ) -> PyResult<()> {
+        self.detail_budget = if downsample.is_none() && min_metrics.is_none() && spill_dir.is_none() {
+            None
+        } else {
+            Some(DetailBudget::new(downsample, min_metrics, spill_dir)?)
+        };
+        Ok(())
+    }
+
+    /// Chooses how `run`, `update` and the optimizers use threads. "serial" (default) does
+    /// everything on the calling thread. "pipelined" reads and parses data files on
+    /// `n_io_threads` workers (default: cores - 1, at most 4) and computes pattern signals on
+    /// `n_compute_threads` workers (default 1) a few tickers ahead, while the calling thread runs
+    /// the strategy and builds the results; it releases the GIL whenever it waits for a ticker.
+    /// Python strategies gain nothing from more threads than that, since every `step` call
+    /// needs the GIL. Results are identical and in the same order in both modes.
+    fn set_scheduling(&mut self, mode: &str, n_io_threads: Option<usize>, n_compute_threads: Option<usize>) -> PyResult<()> {
+        self.scheduling = Scheduling::parse(mode, n_io_threads, n_compute_threads)?;
+        Ok(())
+    }
+
+    /// Reads delisting dates and recovery values from a CSV file with `ticker`,
+    /// `delisting_date` and optional `recovery` columns; `None` removes them. Bars of a listed
+    /// ticker after its delisting date are dropped, and its last remaining bar sells any open
+    /// position at `recovery` times the close (0 when blank: a total loss), whatever the
+    /// strategy signals. Data that ends before the delisting date is traded as still listed. Affected tickers have `delisted` set in their metrics and a
+    /// `delisting` entry in their details, and `portfolio_summary` counts them.
+    fn set_delistings(&mut self, path: Option<String>) -> PyResult<()> {
+        self.delistings = path.as_deref().map(delisting::load).transpose()?.unwrap_or_default();
+        Ok(())
+    }
+
+    /// Replaces the constant `risk_free_rate_annual` with annual rates over time: a dict of
+    /// date -> rate, a `(dates, rates)` pair of arrays, or the path of a CSV file with a header
+    /// and `date,rate` rows; `None` goes back to the constant. Rates between the given dates
+    /// are interpolated linearly and held flat outside them. Sharpe ratios then subtract the
+    /// average rate over each curve's dates, and unless `accrue_cash=False` uninvested cash in
+    /// single-ticker runs earns the rate in force, compounded over the calendar days between
+    /// bars.
+    fn set_risk_free(&mut self, rates: Option<&PyAny>, accrue_cash: Option<bool>) -> PyResult<()> {
+        self.risk_free = rates.map(|r| RiskFreeRate::from_py(r, accrue_cash.unwrap_or(true))).transpose()?;
+        Ok(())
+    }
+
+    /// Chooses what happens to tickers with unusable numbers: a NaN or infinite price, a
+    /// close that is not positive, or metrics that come out NaN or infinite. "mark" (default)
+    /// leaves the ticker out of the results and lists it in `portfolio_summary["invalid_tickers"]`
+    /// as ticker -> reason; "raise" fails the run with the reason; "off" skips the checks.
+    fn set_nan_policy(&mut self, policy: &str) -> PyResult<()> {
+        self.nan_policy = NanPolicy::parse(policy)?;
+        Ok(())
+    }
+
+    /// Chooses the price of each bar in the history strategies decide from (`step` and
+    /// `step_batch`): "close" (default), "adj_close", "open", "high", "low" or "typical"
+    /// ((high + low + close) / 3). "adj_close" reads an `adj_close` (or "Adj Close") column of
+    /// the data files and falls back to the close without one. Fills keep using the raw prices
+    /// picked by `fill_model`, so `set_signal_price("adj_close")` with "next_open" computes
+    /// signals from adjusted closes and trades at the raw open. Positions are still marked to
+    /// market at the raw close.
+    fn set_signal_price(&mut self, field: &str) -> PyResult<()> {
+        self.signal_price = SignalPrice::parse(field)?;
+        Ok(())
+    }
+
+    /// Chooses what happens when `strategy.step` or `step_batch` raises. "hold" (default)
+    /// treats the bar (or, for `step_batch`, the ticker's bars) as a hold and counts it in
+    /// `strategy_errors`; "raise" ends the run with the exception; "collect" drops the ticker
+    /// and lists it in the result's `errors` as ticker -> {"type", "message", "traceback"},
+    /// returning every ticker that completed. Outside `run`, `update` and `run_universes`
+    /// (e.g. in `walk_forward`) "collect" raises like "raise". Invalid return values always hold.
+    fn set_on_error(&mut self, mode: &str) -> PyResult<()> {
+        self.on_error = OnError::parse(mode)?;
+        Ok(())
+    }
+
+    /// Chooses the returns Sharpe ratios are computed from. "daily" (default) samples intraday
+    /// equity curves at each day's last bar first, so the 252-day annualization applies to
+    /// daily returns; daily data is unaffected. "bar" uses every bar's return. Equity curves
+    /// in the results stay per bar either way.
+    fn set_sharpe_sampling(&mut self, mode: &str) -> PyResult<()> {
+        self.sharpe_sampling = SharpeSampling::parse(mode)?;
+        Ok(())
+    }
+
+    /// Chooses how the strategy object is scoped to tickers. "shared" (default) passes one
+    /// object through every ticker, so state it keeps carries over from one ticker to the
+    /// next. "reset" calls `strategy.reset(ticker)` before each ticker's first bar. "factory"
+    /// treats `strategy` as a callable (e.g. the strategy class) and trades each ticker with a
+    /// fresh `strategy(ticker)`; `patterns` and `step_batch` are then read from that object.
+    ///
+    /// Tickers resumed from a saved state are not reset. Under "factory" no strategy state is
+    /// saved or restored, and runs that trade one strategy across tickers (`walk_forward`,
+    /// `optimize`, `search`, `run_pair`, `run_rotation`) raise.
+    fn set_strategy_scope(&mut self, py: Python<'_>, mode: &str) -> PyResult<()> {
+        let scope = StrategyScope::parse(mode)?;
+        scope.check(py, &self.strategy)?;
+        self.strategy_scope = scope;
+        Ok(())
+    }
+
+    /// With `True`, entries, scale-ins and partial exits trade whole shares only, as a
+    /// `lot_size` of 1 would for every ticker without its own lot size in `symbols`. The cash a
+    /// whole share does not fit into stays uninvested and shows in the details' `cash`, so
+    /// high-priced tickers run partly in cash; an order that cannot buy one share is ignored.
+    /// `False` (default) buys fractional shares with the whole budget.
+    fn set_whole_shares(&mut self, enabled: bool) {
+        self.whole_shares = enabled;
+    }
+
+    /// With `True`, `step` gets a read-only numpy view of the engine's history ring instead of
+    /// a copy, saving an allocation per call. The view changes after the call, so a strategy
+    /// that keeps the history or writes to it must copy it itself. `False` (default) passes a
+    /// fresh writable array every bar.
+    fn set_history_view(&mut self, enabled: bool) {
+        self.history_view = enabled;
+    }
+
+    /// Caps every fill at `max_participation` (in (0, 1]) of its bar's volume, in whole lots;
+    /// `None` removes the cap. `remainder` decides what happens to the rest of a larger order:
+    /// "carry" (default) fills it on the following bars while the strategy holds, until done
+    /// or cancelled by an opposite signal; "partial" drops it; "reject" refuses the whole
+    /// order. Entries that get no shares on their bar are refused either way, and delisting
+    /// exits ignore the cap. Bars without volume absorb nothing.
+    ///
+    /// Cut fills are "PARTIALLY_FILLED" in `orders`, and details carry `participation`: the
+    /// `mean_rate_pct` and `max_rate_pct` of bar volume taken by fills, the number of
+    /// `capped_orders` and `rejected_orders`, `unfilled_shares` dropped or cancelled, and the
+    /// `carried_shares` still pending after the last bar.
+    fn set_volume_limit(&mut self, max_participation: Option<f64>, remainder: Option<&str>) -> PyResult<()> {
+        self.volume_limit = max_participation
+            .map(|p| VolumeLimit::new(p, remainder.unwrap_or("carry")))
+            .transpose()?;
+        Ok(())
+    }
+
+    /// Writes every ticker's `orders` in `results` (from `run` or `update`) to a CSV file with
+    /// `timestamp,symbol,side,qty,price,order_type,status` rows in time order, to diff against
+    /// a broker's fill report. Timestamps are FIX UTCTimestamps (`YYYYMMDD-HH:MM:SS`, naive
+    /// ones read in the engine's timezone) or dates (`YYYYMMDD`) for daily bars; orders are
+    /// MARKET orders either FILLED, PARTIALLY_FILLED under a volume limit, or REJECTED for
+    /// sizing below one lot, the minimum notional or the volume limit. Returns the number of
+    /// rows written.
+    fn export_orders(&self, results: &PyAny, path: &str) -> PyResult<usize> {
+        orders::export(results, path, self.timezone.unwrap_or(Tz::UTC))
     }
 
     /// Run backtest. Returns full details in memory (as dict of numpy arrays) instead of writing files.
-    fn run(&self, py: Python<'_>) -> PyResult<PyObject> {
-        let pattern = format!("{}/*_meso.csv", self.data_folder);
-        let paths: Vec<_> = glob(&pattern)
+    /// Per-bar series in each ticker's details include `realized_pnl` and `unrealized_pnl`, the
+    /// open `position_size` in shares, and the split of equity into `cash` and `invested`
+    /// (market value of the position). `orders` lists every order sent, with its bar `index`,
+    /// `date`, `side`, `quantity`, fill `price` and `status` (see `export_orders`).
+    /// The `history` passed to `step` is a copy of the closes before the bar, or a read-only view
+    /// of them with `set_history_view(True)`.
+    /// A strategy defining `step_batch(history_matrix)` is called once per ticker with every bar's
+    /// history window as rows of a 2D view and returns the whole signal vector, instead of one
+    /// `step` call per bar; its signals cannot depend on the position.
+    /// With `trace=True` each ticker's details also carry a per-bar `trace` of the strategy input,
+    /// the emitted signal, what the engine did with it and the resulting position/value change.
+    /// `timing` maps each ticker to its data load, strategy, engine loop and metrics time in ms
+    /// and its number of strategy calls; `portfolio_summary["profile"]` totals them with the
+    /// strategy's share of the time.
+    /// `portfolio["exposure"]` holds per-bar `gross`, `net`, `long` and `short` exposure as a
+    /// percentage of portfolio equity, summarized in `portfolio_summary` by their averages and
+    /// the extremes of gross and net exposure.
+    ///
+    /// `save_state` writes positions, balances and the last processed date per ticker (plus
+    /// `strategy.get_state()` if defined) to a JSON file. Passing that file as `resume_state`
+    /// continues each ticker from the bar after its last processed date, so only newly appended
+    /// bars are simulated and reported; `strategy.set_state(...)` receives the saved state.
+    /// The same state is returned as JSON text under `state`.
+    ///
+    /// `stream_to` (needs the `arrow` feature) also writes each ticker's per-bar date, close,
+    /// signal, equity, PnL, position size and cash/invested split as soon as it finishes, so
+    /// another process can start on early tickers: to one Arrow IPC stream with a record batch
+    /// per ticker (read it with `pyarrow.ipc.open_stream`), or, when the path is a directory or
+    /// ends in `/`, to one Feather file per ticker that appears only once complete.
+    fn run(
+        &self,
+        py: Python<'_>,
+        trace: Option<bool>,
+        save_state: Option<String>,
+        resume_state: Option<String>,
+        stream_to: Option<String>,
+    ) -> PyResult<PyObject> {
+        let resumed = match &resume_state {
+            Some(path) => {
+                let state = EngineState::load(path)?;
+                self.check_state(&state)?;
+                if self.strategy_scope.saves_state() {
+                    state.restore_strategy(py, &self.strategy)?;
+                }
+                state
+            }
+            None => EngineState::new(self.history_size),
+        };
+
+        let mut stream = stream_to.as_deref().map(ResultStream::open).transpose()?;
+        let sinks = DetailSinks { stream: stream.as_mut(), budget: self.detail_budget.as_ref() };
+        let mut out = self.simulate(py, &self.strategy, &self.data_folder, trace.unwrap_or(false), &resumed, sinks)?;
+        if let Some(stream) = stream {
+            stream.finish()?;
+        }
+        if self.strategy_scope.saves_state() {
+            out.state.capture_strategy(py, &self.strategy)?;
+        }
+        if let Some(path) = &save_state {
+            out.state.save(path)?;
+        }
+        assemble(self, py, out)
+    }
+
+    /// Runs the strategy over several universes of data files: `universes` is a list of data
+    /// folders (named after their last path component) or a dict of name -> folder. Returns
+    /// `universes`, a `run`-style result per universe; `combined`, the metrics, summary,
+    /// portfolio curves and timing of all universes' tickers as one portfolio (warnings and
+    /// timing keys are prefixed with the universe); and `comparison`, a table of each
+    /// universe's headline summary figures. Details are kept in full, without the detail budget.
+    fn run_universes(&self, py: Python<'_>, universes: &PyAny, trace: Option<bool>) -> PyResult<PyObject> {
+        universes::run_universes(self, py, universes, trace.unwrap_or(false))
+    }
+
+    /// Extends `results` (from `run` or a previous `update`) with the bars in `new_data_folder`
+    /// dated after each ticker's last processed bar. Positions and strategy state continue from
+    /// `results["state"]`; per-bar series and the trade ledger are appended, and metrics, returns
+    /// and drawdowns are recomputed over the combined history. Files in `new_data_folder` must
+    /// still contain at least `history_size` bars before the first new one.
+    fn update(
+        &self,
+        py: Python<'_>,
+        results: &PyDict,
+        new_data_folder: String,
+        trace: Option<bool>,
+    ) -> PyResult<PyObject> {
+        update::update(self, py, results, &new_data_folder, trace.unwrap_or(false))
+    }
+
+    /// Fill simulation and metrics for a precomputed signal array, skipping per-bar strategy calls.
+    /// `signals[i]` (1 buy, -1 sell, anything else hold) is acted on at `closes[i]` just like a
+    /// `step` result for bar `i`; `exit_fractions[i]` optionally sells only part of the position.
+    /// On sell bars a fraction must be in (0, 1], with NaN selling everything; other bars ignore
+    /// it. `dates` default to zero-padded bar numbers. `volumes` are required once a volume limit
+    /// is set. Returns the same structure as `run` with `ticker` as the only entry.
+    #[allow(clippy::too_many_arguments)]
+    fn run_signals(
+        &self,
+        py: Python<'_>,
+        ticker: String,
+        closes: PyReadonlyArray1<f64>,
+        signals: Vec<i32>,
+        dates: Option<Vec<String>>,
+        exit_fractions: Option<Vec<f64>>,
+        volumes: Option<PyReadonlyArray1<f64>>,
+    ) -> PyResult<PyObject> {
+        let closes = closes.as_array().to_vec();
+        if closes.is_empty() {
+            return Err(PyValueError::new_err("closes is empty"));
+        }
+        if signals.len() != closes.len() {
+            return Err(PyValueError::new_err(format!(
+                "closes and signals must have the same length, got {} and {}", closes.len(), signals.len()
+            )));
+        }
+        let signals: Vec<Signal> = match exit_fractions {
+            Some(f) if f.len() != closes.len() => {
+                return Err(PyValueError::new_err(format!(
+                    "closes and exit_fractions must have the same length, got {} and {}", closes.len(), f.len()
+                )));
+            }
+            Some(f) => signals.iter().zip(&f).enumerate()
+                .map(|(i, (&side, &fraction))| {
+                    if side != -1 || fraction.is_nan() {
+                        return Ok(Signal::full(side));
+                    }
+                    if !(fraction > 0.0 && fraction <= 1.0) {
+                        return Err(PyValueError::new_err(format!(
+                            "exit_fractions[{}] must be in (0, 1] on a sell, got {}", i, fraction
+                        )));
+                    }
+                    Ok(Signal { side, fraction, target: None })
+                })
+                .collect::<PyResult<_>>()?,
+            None => signals.iter().map(|&side| Signal::full(side)).collect(),
+        };
+        let dates = match dates {
+            Some(d) if d.len() != closes.len() => {
+                return Err(PyValueError::new_err(format!(
+                    "closes and dates must have the same length, got {} and {}", closes.len(), d.len()
+                )));
+            }
+            Some(d) => d,
+            None => (0..closes.len()).map(|i| format!("{:08}", i)).collect(),
+        };
+        let volumes = match volumes {
+            Some(v) if v.len() != closes.len() => {
+                return Err(PyValueError::new_err(format!(
+                    "closes and volumes must have the same length, got {} and {}", closes.len(), v.len()
+                )));
+            }
+            Some(v) => v.as_array().to_vec(),
+            None if self.volume_limit.is_some() => {
+                return Err(PyValueError::new_err("volumes are required when a volume limit is set"));
+            }
+            None => vec![0.0; closes.len()],
+        };
+        let price_data: Vec<Bar> = dates.into_iter()
+            .zip(closes.iter().zip(&volumes))
+            .map(|(date, (&close, &volume))| Bar::new(date, close, close, close, close, volume))
+            .collect();
+
+        let mut out = RunOutput {
+            details: PyDict::new(py),
+            metrics: Vec::new(),
+            equity_curves: Vec::new(),
+            position_curves: Vec::new(),
+            files: FileCounts::default(),
+            warnings: Vec::new(),
+            state: EngineState::new(self.history_size),
+            spilled: None,
+            timings: Vec::new(),
+            invalid: Vec::new(),
+            errors: Vec::new(),
+        };
+        if let Some(reason) = self.nan_policy.screen(&ticker, || validation::check_bars(&price_data))? {
+            out.invalid.push((ticker, reason));
+            return assemble(self, py, out);
+        }
+
+        let st = TickerState::new(INITIAL_CAPITAL_PER_STOCK, INITIAL_CAPITAL_PER_STOCK / closes[0]);
+        let run = self.simulate_ticker(py, &ticker, &price_data, 0, st, false, |i, _, _| Ok(Some(signals[i])))?;
+        if let Some(reason) = self.nan_policy.screen(&ticker, || validation::check_metric(&run.metric))? {
+            out.invalid.push((ticker, reason));
+            return assemble(self, py, out);
+        }
+
+        out.details.set_item(&ticker, run.detail)?;
+        out.state.tickers.insert(ticker, run.state);
+        out.metrics.push(run.metric);
+        out.equity_curves.push(run.equity_curve);
+        out.position_curves.push(run.position_values);
+        assemble(self, py, out)
+    }
+
+    /// Runs the engine on `n_bars` (default 500) seeded GBM bars with strategies whose outcome
+    /// is known and reports accounting violations: no signals must keep the initial capital,
+    /// buying on the first bar must match buy-and-hold exactly, equity must equal cash plus
+    /// position and initial capital plus realized and unrealized PnL on every bar, and
+    /// commissions must never raise the final balance. Returns {"passed": bool, "checks":
+    /// [{"name", "passed", "violation"}]}. Identities are checked with this engine's settings,
+    /// exact baselines with default settings.
+    fn self_check(&self, py: Python<'_>, n_bars: Option<usize>, seed: Option<u64>) -> PyResult<PyObject> {
+        let n_bars = n_bars.unwrap_or(500);
+        if n_bars < 2 {
+            return Err(PyValueError::new_err("self_check needs at least 2 bars"));
+        }
+        self_check::self_check(self, py, n_bars, seed.unwrap_or(42))
+    }
+
+    /// Walk-forward evaluation over the engine's data folder. The dates of all tickers are cut
+    /// into folds of `train_bars` dates followed by `test_bars` dates, moving forward `step`
+    /// dates at a time (default `test_bars`, so test windows don't overlap). With `anchored`
+    /// every fold trains from the first date instead (a rolling-origin evaluation): the first
+    /// fold trains on `train_bars` dates and each refit, `step` dates later, on all dates
+    /// before its test window.
+    ///
+    /// For each fold the strategy's `fit(train)` is called with {ticker: PriceSeries} of the
+    /// training dates, then it trades the test dates, each ticker starting flat with fresh
+    /// capital (history before the test window is visible to `step`). A strategy with
+    /// `get_params()` exports its fitted parameters (a dict, bytes or any object) after each
+    /// fit, and one with `set_params(params)` gets the previous fold's parameters back before
+    /// the next fit, so fitting can warm-start. Passing a result's `params` list as `replay`
+    /// sets each fold's parameters without fitting, reproducing the run.
+    ///
+    /// Returns the `mode` ("rolling" or "anchored"), `folds` (dates, number of `train_bars`,
+    /// `params`, per-ticker `metrics`, `roi_pct` and `sharpe` of the test window), the
+    /// `params` of every fold in order, and a `portfolio` equity curve that compounds the test
+    /// windows one after another (with `step < test_bars` the overlapping dates appear once
+    /// per fold).
+    fn walk_forward(
+        &self,
+        py: Python<'_>,
+        train_bars: usize,
+        test_bars: usize,
+        step: Option<usize>,
+        replay: Option<&PyList>,
+        anchored: Option<bool>,
+    ) -> PyResult<PyObject> {
+        self.strategy_scope.require_instance("walk_forward")?;
+        walk_forward::walk_forward(self, py, train_bars, test_bars, step.unwrap_or(test_bars), anchored.unwrap_or(false), replay)
+    }
+
+    /// Windowed samples of every ticker in the data folder for training models that later
+    /// drive a strategy. Returns a dict with `X` of shape (samples, `window`, features), the
+    /// labels `y`, and each sample's `tickers` and `dates` entry plus the `features` names.
+    ///
+    /// Sample rows line up with `step`: the window of the sample dated at bar `i` holds bars
+    /// `i - window .. i`, and its label compares the close `horizon` bars after bar `i` with the
+    /// close of bar `i`. `features` (default `["close"]`) picks per-bar columns from "close",
+    /// "open", "high", "low", "returns" and "log_returns"; `label` is "return" (default),
+    /// "log_return" or "direction" (1 / -1 beyond +/- `threshold`, else 0).
+    fn make_dataset(
+        &self,
+        py: Python<'_>,
+        window: usize,
+        horizon: usize,
+        features: Option<Vec<String>>,
+        label: Option<String>,
+        threshold: Option<f64>,
+    ) -> PyResult<PyObject> {
+        let features = features.unwrap_or_else(|| vec!["close".to_string()]);
+        dataset::make_dataset(self, py, window, horizon, &features, label.as_deref().unwrap_or("return"), threshold.unwrap_or(0.0))
+    }
+
+    /// Grid search over strategy parameters. `grid` maps parameter names to lists of values;
+    /// every combination builds a strategy with `factory(**params)` and runs it over the data
+    /// folder from scratch. Trials are ranked by the portfolio `metric` ("sharpe" by default,
+    /// "roi_pct", "max_drawdown_pct", "average_sharpe" or "win_rate_pct"), higher first unless
+    /// `maximize=False` (drawdown defaults to lower first).
+    ///
+    /// Returns `params` (one dict per trial), `scores`, `best_params` and `best_score`. With
+    /// exactly two parameters it also returns `heatmap`: `values[i, j]` is the score at
+    /// `rows[i]` of `rows_param` and `cols[j]` of `cols_param`.
+    ///
+    /// With `cv_folds=k` each ticker's bars are split into k purged K-fold blocks (dropping
+    /// `purge` bars before and `embargo` bars after each test block from its training bars),
+    /// and trials rank by their mean test-block score instead of the full-sample one. Metrics
+    /// are then computed per ticker on the fold's bars and averaged. `cross_validation` holds
+    /// the per-fold scores and, for each fold, the trial that did best on its training bars
+    /// and its held-out score.
+    #[allow(clippy::too_many_arguments)]
+    fn optimize(
+        &self,
+        py: Python<'_>,
+        factory: PyObject,
+        grid: &PyDict,
+        metric: Option<String>,
+        maximize: Option<bool>,
+        cv_folds: Option<usize>,
+        purge: Option<usize>,
+        embargo: Option<usize>,
+    ) -> PyResult<PyObject> {
+        let objective = optimize::Objective::parse(metric.as_deref())?;
+        let maximize = maximize.unwrap_or(objective.maximize_by_default());
+        let cv = match cv_folds {
+            Some(k) if k < 2 => return Err(PyValueError::new_err(format!("cv_folds must be at least 2, got {}", k))),
+            Some(k) => Some(optimize::CrossValidation { folds: k, purge: purge.unwrap_or(0), embargo: embargo.unwrap_or(0) }),
+            None => None,
+        };
+        self.strategy_scope.require_instance("optimize")?;
+        optimize::grid_search(self, py, factory, grid, objective, maximize, cv)
+    }
+
+    /// Random or genetic search over a parameter `space` mapping names to a list of choices
+    /// (categorical) or a `(low, high)` range (ints if both ends are ints, floats otherwise).
+    /// Strategies are built with `factory(**params)` and scored like `optimize`.
+    ///
+    /// `method="random"` (default) samples `n_trials` candidates (default 50). `"genetic"`
+    /// evolves a `population` (default 20) for `n_trials / population` generations, keeping the
+    /// two best, breeding the rest by tournament selection and uniform crossover, and mutating
+    /// each gene with probability `mutation_rate` (default 0.1). With `patience`, the search
+    /// stops after that many trials (random) or generations (genetic) without improvement.
+    /// `seed` makes runs reproducible; repeated candidates are not re-run.
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        py: Python<'_>,
+        factory: PyObject,
+        space: &PyDict,
+        method: Option<String>,
+        metric: Option<String>,
+        maximize: Option<bool>,
+        n_trials: Option<usize>,
+        population: Option<usize>,
+        mutation_rate: Option<f64>,
+        patience: Option<usize>,
+        seed: Option<u64>,
+    ) -> PyResult<PyObject> {
+        let objective = optimize::Objective::parse(metric.as_deref())?;
+        let maximize = maximize.unwrap_or(objective.maximize_by_default());
+        let options = search::SearchOptions {
+            n_trials: n_trials.unwrap_or(50),
+            population: population.unwrap_or(20),
+            mutation_rate: mutation_rate.unwrap_or(0.1),
+            patience,
+            seed: seed.unwrap_or(42),
+        };
+        self.strategy_scope.require_instance("search")?;
+        search::search(self, py, factory, space, method.as_deref().unwrap_or("random"), objective, maximize, options)
+    }
+
+    /// Re-prices one strategy run under several transaction costs without calling the
+    /// strategy again. The strategy's signals for every ticker in the data folder are recorded
+    /// once, then replayed through the engine with each of `costs` (in bps, default
+    /// [0, 5, 10, 25]) in place of `commission_bps`, charged on the notional of every fill.
+    /// Strategies that read the position see the positions of the recording run, made at the
+    /// engine's own commission.
+    ///
+    /// Returns the curves of portfolio `total_roi_pct`, `portfolio_sharpe`, `average_sharpe`,
+    /// `max_drawdown_pct` and `total_trades` against `cost_bps`, plus `tickers` with each
+    /// ticker's `roi_pct` and `sharpe` curves.
+    fn run_sensitivity(&mut self, py: Python<'_>, costs: Option<Vec<f64>>) -> PyResult<PyObject> {
+        let costs = costs.unwrap_or_else(|| vec![0.0, 5.0, 10.0, 25.0]);
+        sensitivity::run_sensitivity(self, py, &costs)
+    }
+
+    /// Pair-trading backtest on the spread `ticker_a - hedge_ratio * ticker_b`.
+    /// The strategy sees the spread history and the spread position (-1, 0, 1); a signal of 1
+    /// opens a long spread or closes a short one, -1 opens a short spread or closes a long one.
+    /// Without a fixed `hedge_ratio` the ratio is re-estimated by rolling OLS over `hedge_window`
+    /// bars (defaults to `history_size`); leg sizes are locked in at entry. With a negative ratio
+    /// both legs sit on the same side of the spread. Details carry the per-bar gross, net, long
+    /// and short `exposure` of the legs and metrics their averages.
+    ///
+    /// Both legs pay `commission_bps` on their notional at entry and exit, and round trips are
+    /// judged like single-ticker trades (`breakeven_pct`, `win_basis`). Strategy errors follow
+    /// `on_error`; subscribed patterns and the `regime=` label are those of `ticker_a`.
+    fn run_pair(
+        &self,
+        py: Python<'_>,
+        ticker_a: String,
+        ticker_b: String,
+        hedge_ratio: Option<f64>,
+        hedge_window: Option<usize>,
+    ) -> PyResult<PyObject> {
+        self.strategy_scope.require_instance("run_pair")?;
+        let hedge = match hedge_ratio {
+            Some(h) => pairs::HedgeRatio::Fixed(h),
+            None => pairs::HedgeRatio::RollingOls(hedge_window.unwrap_or(self.history_size).max(2)),
+        };
+        pairs::run_pair(self, py, &ticker_a, &ticker_b, hedge)
+    }
+
+    /// Cross-sectional rotation over all tickers on the union of their dates with shared capital.
+    /// Every `rebalance_every` bars the strategy's `rank(histories)` is called with a dict of
+    /// ticker -> close history and returns either a list of tickers (best first) or a dict of
+    /// ticker -> score; the engine then holds the top `top_n` names in equal weight. Only
+    /// tickers with `history_size` bars so far and data on the bar are offered; missing days
+    /// carry the last close. A delisted ticker is sold at its recovery value on its delisting
+    /// bar and leaves the ranking, listed under `delisted` in the details.
+    /// `capital` defaults to the per-stock capital times the number of tickers. Each rebalance
+    /// pays `commission_bps` on the notional every name trades; `rank` errors follow `on_error`.
+    fn run_rotation(
+        &self,
+        py: Python<'_>,
+        top_n: usize,
+        rebalance_every: Option<usize>,
+        capital: Option<f64>,
+    ) -> PyResult<PyObject> {
+        self.strategy_scope.require_instance("run_rotation")?;
+        if top_n == 0 {
+            return Err(PyValueError::new_err("top_n must be > 0"));
+        }
+        let capital = capital.unwrap_or_else(|| rotation::default_capital(self));
+        rotation::run_rotation(self, py, top_n, rebalance_every.unwrap_or(1).max(1), capital)
+    }
+}
+
+impl BacktestEngine {
+    /// Starts a [`BacktestEngineBuilder`], the Rust counterpart of the Python constructor.
+    pub fn builder() -> BacktestEngineBuilder {
+        BacktestEngineBuilder::default()
+    }
+
+    fn data_pattern(folder: &str) -> String {
+        format!("{}/*_meso.csv", folder)
+    }
+
+    fn data_files(&self) -> Vec<PathBuf> {
+        Self::data_files_in(&self.data_folder)
+    }
+
+    fn data_files_in(folder: &str) -> Vec<PathBuf> {
+        glob(&Self::data_pattern(folder))
             .expect("Failed to read glob pattern")
             .filter_map(Result::ok)
-            .collect();
+            .collect()
+    }
+
+    fn symbol_spec(&self, ticker: &str) -> SymbolSpec {
+        let spec = self.symbol_specs.get(ticker).copied().unwrap_or_default();
+        if self.whole_shares { spec.whole_shares() } else { spec }
+    }
+
+    fn ticker_timezone(&self, ticker: &str) -> Option<Tz> {
+        self.ticker_timezones.get(ticker).copied().or(self.timezone)
+    }
+
+    /// Loads a ticker's bars, normalizing intraday timestamps to UTC when a timezone applies.
+    fn load_bars(&self, path: &str, ticker: &str) -> Result<Vec<Bar>, std::io::Error> {
+        let mut bars = load_ohlcv(path)?;
+        if let Some(tz) = self.ticker_timezone(ticker) {
+            for bar in bars.iter_mut() {
+                if let Some(t) = timestamps::parse_instant(&bar.date, tz) {
+                    bar.date = timestamps::format_utc(&t);
+                    bar.session = timestamps::session_date(&t, tz);
+                }
+            }
+        }
+        if let Some(delisting) = self.delistings.get(ticker) {
+            delisting.truncate(&mut bars);
+        }
+        Ok(bars)
+    }
+
+    /// Annual risk-free rate for excess returns over a curve dated `dates`: the average of the
+    /// risk-free series on those dates when one is set, otherwise the constant rate.
+    fn risk_free_rate(&self, dates: &[String]) -> f64 {
+        match &self.risk_free {
+            Some(series) => series.mean_rate(dates),
+            None => self.risk_free_rate_annual,
+        }
+    }
+
+    /// Sharpe ratio of an equity curve dated `dates`, sampled as `sharpe_sampling` says, in
+    /// excess of the risk-free rate over the sampled dates.
+    fn sharpe(&self, dates: &[String], equity: &[f64]) -> f64 {
+        if self.sharpe_sampling == SharpeSampling::Daily
+            && let Some((days, closes)) = sampling::daily_closes(dates, equity)
+        {
+            return sharpe_ratio(&closes, self.risk_free_rate(&days));
+        }
+        sharpe_ratio(&equity.to_vec(), self.risk_free_rate(dates))
+    }
+
+    fn check_state(&self, state: &EngineState) -> PyResult<()> {
+        if state.history_size != self.history_size {
+            return Err(PyValueError::new_err(format!(
+                "engine state was saved with history_size {}, engine uses {}", state.history_size, self.history_size
+            )));
+        }
+        Ok(())
+    }
+
+    fn subscribed_patterns(py: Python<'_>, strategy: &PyObject) -> Vec<String> {
+        let requested: Vec<String> = strategy
+            .getattr(py, "patterns")
+            .and_then(|p| p.extract(py))
+            .unwrap_or_default();
+
+        requested.into_iter()
+            .filter(|name| {
+                let known = patterns::PATTERN_NAMES.contains(&name.as_str());
+                if !known { log::warn!("Ignoring unknown candlestick pattern subscription: {}", name); }
+                known
+            })
+            .collect()
+    }
+
+    /// Simulates every ticker file in `data_folder` with `strategy`, continuing tickers found in `resumed`.
+    fn simulate<'py>(
+        &self,
+        py: Python<'py>,
+        strategy: &PyObject,
+        data_folder: &str,
+        trace_enabled: bool,
+        resumed: &EngineState,
+        mut sinks: DetailSinks<'_>,
+    ) -> PyResult<RunOutput<'py>> {
+        self.strategy_scope.check(py, strategy)?;
+        let paths = Self::data_files_in(data_folder);
+        let mut next_state = EngineState::new(self.history_size);
+        next_state.tickers = resumed.tickers.clone();
 
         let mut metrics_vec: Vec<StockMetric> = Vec::with_capacity(paths.len());
-        let py_metrics_list = PyList::empty(py);
         
         // This dictionary will hold { "TICKER": { "dates": [], "closes": np.array, ... } }
         let py_details_map = PyDict::new(py); 
 
-        for path in paths {
-            let file_path = path.to_str().unwrap();
-            let ticker = path.file_stem().unwrap().to_str().unwrap().replace("_meso", "");
+        // Strategies subscribe to candlestick patterns through a `patterns` list attribute;
+        // subscribed signals are then passed as a third `step` argument.
+        let subscribed_patterns = Self::subscribed_patterns(py, strategy);
+
+        // Per-ticker equity curves, combined into a date-aligned portfolio curve at the end
+        let mut equity_curves: Vec<(Vec<String>, Vec<f64>)> = Vec::with_capacity(paths.len());
+        let mut position_curves: Vec<Vec<f64>> = Vec::with_capacity(paths.len());
+
+        let mut files = FileCounts::default();
+        let mut timings: Vec<(String, TickerTiming)> = Vec::with_capacity(paths.len());
+        let mut invalid: Vec<(String, String)> = Vec::new();
+        let mut errors: Vec<(String, TickerError)> = Vec::new();
+        let mut spilled = sinks.budget.and_then(|b| b.spill_dir()).map(|dir| LazyDetails::new(dir.clone()));
+
+        // Data files, then synthetic instruments priced from their legs' files
+        let sources: Vec<(String, Option<(&String, &Synthetic)>)> = paths.iter()
+            .map(|path| (path.to_str().unwrap().to_string(), None))
+            .chain(self.synthetics.iter().map(|(name, synthetic)| (format!("synthetic {}", name), Some((name, synthetic)))))
+            .collect();
+        let load = |(file_path, synthetic): &(String, Option<(&String, &Synthetic)>)| {
+            let load_started = Instant::now();
+            let (ticker, loaded) = match synthetic {
+                Some((name, synthetic)) => (name.to_string(), synthetic.bars(self, data_folder)),
+                None => {
+                    let ticker = Path::new(file_path).file_stem().unwrap().to_str().unwrap().replace("_meso", "");
+                    let loaded = self.load_bars(file_path, &ticker);
+                    (ticker, loaded)
+                }
+            };
+            PreparedTicker { file_path: file_path.clone(), ticker, loaded, load_time: load_started.elapsed(), pattern_signals: Vec::new() }
+        };
+        let detect_patterns = |mut prepared: PreparedTicker| {
+            if let Ok(price_data) = &prepared.loaded
+                && price_data.len() > self.history_size + 1
+            {
+                prepared.pattern_signals = Self::pattern_signals(&subscribed_patterns, price_data);
+            }
+            prepared
+        };
+        self.scheduling.run(py, &sources, load, detect_patterns, |ready| {
+            for prepared in ready {
+                let PreparedTicker { file_path, ticker, loaded, load_time, pattern_signals } = prepared?;
+                let mut timing = TickerTiming { load: load_time, ..TickerTiming::default() };
+                let price_data = match loaded {
+                    Ok(p) => p,
+                    Err(e) => {
+                        log::warn!("Skipping {} because of read error: {}", file_path, e);
+                        files.skipped_read_error += 1;
+                        continue;
+                    }
+                };
+
+                if let Some(reason) = self.nan_policy.screen(&ticker, || validation::check_bars(&price_data))? {
+                    log::warn!("Skipping {}: {}", file_path, reason);
+                    invalid.push((ticker, reason));
+                    continue;
+                }
+
+                if price_data.len() <= self.history_size + 1 {
+                    log::info!("Skipping {}: {} bars is not more than history_size + 1", file_path, price_data.len());
+                    files.skipped_too_short += 1;
+                    continue;
+                }
+
+                // --- Simulation State ---
+                let prior = resumed.tickers.get(&ticker);
+                // Resume at the first bar after the saved date; fresh tickers start once history is full.
+                let start = match prior {
+                    Some(p) => {
+                        let first_new = price_data.iter().position(|b| b.date > p.last_date).unwrap_or(price_data.len());
+                        if first_new < self.history_size {
+                            log::warn!(
+                                "{}: only {} bars precede the first new bar, the next {} new bars are skipped to fill history",
+                                ticker, first_new, self.history_size - first_new
+                            );
+                        }
+                        first_new.max(self.history_size)
+                    }
+                    None => self.history_size,
+                };
+                if start >= price_data.len() {
+                    log::info!("No new bars for {} after {}", ticker, prior.map_or("", |p| p.last_date.as_str()));
+                    files.up_to_date += 1;
+                    continue;
+                }
+                let st = match prior {
+                    Some(p) => p.clone(),
+                    None => TickerState::new(INITIAL_CAPITAL_PER_STOCK, INITIAL_CAPITAL_PER_STOCK / price_data[start].close),
+                };
+
+                // Under "collect" an exception from here on ends this ticker only.
+                let simulated = (|| {
+                    // Resumed tickers keep the strategy state they were saved with.
+                    let strategy = match prior {
+                        Some(_) if self.strategy_scope == StrategyScope::Reset => strategy.clone_ref(py),
+                        _ => self.strategy_scope.instance(py, strategy, &ticker)?,
+                    };
+                    let strategy = &strategy;
+                    // A factory's objects may subscribe to other patterns than the factory itself.
+                    let pattern_signals = match self.strategy_scope {
+                        StrategyScope::Factory => {
+                            let own = Self::subscribed_patterns(py, strategy);
+                            if own == subscribed_patterns { pattern_signals } else { Self::pattern_signals(&own, &price_data) }
+                        }
+                        _ => pattern_signals,
+                    };
+                    // Vectorized strategies produce a whole ticker's signals in one `step_batch` call.
+                    let batch_signals = if strategy.as_ref(py).hasattr("step_batch")? {
+                        let call_started = Instant::now();
+                        let signals = self.batch_signals(py, strategy, &ticker, &price_data, start, &pattern_signals)?;
+                        timing.strategy += call_started.elapsed();
+                        timing.ffi_calls += 1;
+                        Some(signals)
+                    } else {
+                        None
+                    };
 
-            let price_data = match load_date_and_prices(file_path) {
-                Ok(p) => p,
-                Err(e) => {
-                    eprintln!("Skipping {} because of read error: {}", file_path, e);
+                    let simulate_started = Instant::now();
+                    let mut step_time = Duration::ZERO;
+                    let mut step_calls = 0;
+                    let run = self.simulate_ticker(py, &ticker, &price_data, start, st, trace_enabled, |i, history, position| {
+                        if let Some(signals) = &batch_signals {
+                            return Ok(signals[i - start]);
+                        }
+                        let call_started = Instant::now();
+                        let signal = self.step_signal(py, strategy, &ticker, &price_data, &pattern_signals, i, history, position);
+                        step_time += call_started.elapsed();
+                        step_calls += 1;
+                        signal
+                    })?;
+                    timing.strategy += step_time;
+                    timing.ffi_calls += step_calls;
+                    timing.metrics = run.metrics_time;
+                    timing.fills = simulate_started.elapsed().saturating_sub(step_time + run.metrics_time);
+                    Ok((run, pattern_signals))
+                })();
+                let (run, pattern_signals) = match simulated {
+                    Ok(simulated) => simulated,
+                    Err(e) if self.on_error == OnError::Collect => {
+                        log::error!("Leaving out {}: {}", ticker, e);
+                        errors.push((ticker, TickerError::new(py, &e)));
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+                files.strategy_errors += run.strategy_errors;
+                if let Some(reason) = self.nan_policy.screen(&ticker, || validation::check_metric(&run.metric))? {
+                    log::warn!("Leaving out {}: {}", ticker, reason);
+                    invalid.push((ticker, reason));
                     continue;
                 }
+
+                if !pattern_signals.is_empty() {
+                    let py_patterns = PyDict::new(py);
+                    for (name, sig) in &pattern_signals {
+                        py_patterns.set_item(name, PyArray1::from_slice(py, &sig.as_slice().unwrap()[start..]))?;
+                    }
+                    run.detail.set_item("patterns", py_patterns)?;
+                }
+                if self.ticker_timezone(&ticker).is_some() {
+                    let sessions: Vec<&str> = price_data[start..].iter().map(|b| b.session.as_str()).collect();
+                    run.detail.set_item("sessions", sessions)?;
+                }
+
+                if let Some(stream) = sinks.stream.as_deref_mut() {
+                    stream.write(&ticker, run.detail)?;
+                }
+                let kept = match sinks.budget {
+                    Some(budget) => budget.apply(py, &ticker, run.detail, run.metric.to_py(py)?)?,
+                    None => Kept::Inline,
+                };
+                match (kept, spilled.as_mut()) {
+                    (Kept::Inline, _) => py_details_map.set_item(ticker.clone(), run.detail)?,
+                    (Kept::Spilled, Some(spilled)) => spilled.push(ticker.clone()),
+                    _ => {}
+                }
+                next_state.tickers.insert(ticker.clone(), run.state);
+                timings.push((ticker.clone(), timing));
+                equity_curves.push(run.equity_curve);
+                position_curves.push(run.position_values);
+                metrics_vec.push(run.metric);
+            }
+            Ok(())
+        })?;
+
+        let mut warnings: Vec<String> = Vec::new();
+        let pattern = Self::data_pattern(data_folder);
+        if paths.is_empty() {
+            warnings.push(format!("no data files matched '{}'", pattern));
+        } else if metrics_vec.is_empty() && files.up_to_date == 0 {
+            warnings.push(format!(
+                "all {} files matching '{}' were skipped: {} unreadable, {} with no more than history_size + 1 = {} bars",
+                paths.len(), pattern, files.skipped_read_error, files.skipped_too_short, self.history_size + 1
+            ));
+        }
+        if self.strict
+            && let Some(w) = warnings.first()
+        {
+            return Err(PyValueError::new_err(w.clone()));
+        }
+        for w in &warnings {
+            log::warn!("{}", w);
+        }
+
+        Ok(RunOutput {
+            details: py_details_map,
+            metrics: metrics_vec,
+            equity_curves,
+            position_curves,
+            files,
+            warnings,
+            state: next_state,
+            spilled,
+            timings,
+            invalid,
+            errors,
+        })
+    }
+
+    /// Candlestick pattern signals over `price_data` for each subscribed pattern.
+    fn pattern_signals(subscribed: &[String], price_data: &[Bar]) -> Vec<(String, Array1<i32>)> {
+        if subscribed.is_empty() {
+            return Vec::new();
+        }
+        let open = Array1::from_iter(price_data.iter().map(|b| b.open));
+        let high = Array1::from_iter(price_data.iter().map(|b| b.high));
+        let low = Array1::from_iter(price_data.iter().map(|b| b.low));
+        let close = Array1::from_iter(price_data.iter().map(|b| b.close));
+        subscribed.iter()
+            .filter_map(|name| patterns::detect(name, &open, &high, &low, &close).map(|sig| (name.clone(), sig)))
+            .collect()
+    }
+
+    /// Calls `strategy.step` for bar `i` with the closes before it and the current position,
+    /// plus the subscribed pattern values and the regime label of bar `i - 1`. An invalid return
+    /// value holds (`None`), and so does a raising strategy unless `on_error` propagates it.
+    #[allow(clippy::too_many_arguments)]
+    fn step_signal(
+        &self,
+        py: Python<'_>,
+        strategy: &PyObject,
+        ticker: &str,
+        price_data: &[Bar],
+        pattern_signals: &[(String, Array1<i32>)],
+        i: usize,
+        history: &HistoryBuffer,
+        position: i32,
+    ) -> PyResult<Option<Signal>> {
+        let view = history.view(py)?;
+        let py_history = if self.history_view { view } else { view.call_method0("copy")? };
+        self.call_step(py, strategy, ticker, price_data, pattern_signals, i, py_history, position)
+    }
+
+    /// [`step_signal`](Self::step_signal) with any `py_history`, such as the spread of a pair.
+    #[allow(clippy::too_many_arguments)]
+    fn call_step(
+        &self,
+        py: Python<'_>,
+        strategy: &PyObject,
+        ticker: &str,
+        price_data: &[Bar],
+        pattern_signals: &[(String, Array1<i32>)],
+        i: usize,
+        py_history: &PyAny,
+        position: i32,
+    ) -> PyResult<Option<Signal>> {
+        // Regime labels are passed as a keyword so strategies without regimes are unaffected.
+        let kwargs = match &self.regimes {
+            Some(regimes) => {
+                let kwargs = PyDict::new(py);
+                kwargs.set_item("regime", regimes.label(&price_data[i - 1]))?;
+                Some(kwargs)
+            }
+            None => None,
+        };
+
+        // Call Strategy. Pattern values come from bar i - 1, the last bar the strategy can see.
+        let step_result = if pattern_signals.is_empty() {
+            strategy.call_method(py, "step", (py_history, position), kwargs)
+        } else {
+            let py_patterns = PyDict::new(py);
+            for (name, sig) in pattern_signals {
+                py_patterns.set_item(name, sig[i - 1])?;
+            }
+            strategy.call_method(py, "step", (py_history, position, py_patterns), kwargs)
+        };
+        Ok(match step_result {
+            Ok(obj) => Signal::from_py(obj.as_ref(py)).map_err(|e| {
+                log::debug!("strategy.step for {} at index {} did not return a valid signal: {}", ticker, i, e);
+            }).ok(),
+            Err(e) => {
+                log::error!("Error calling strategy.step for {} at index {} ({}): {}", ticker, i, price_data[i].date, e);
+                if self.on_error.propagates() {
+                    return Err(e);
+                }
+                None
+            }
+        })
+    }
+
+    /// Signals for bars `start..` from one `strategy.step_batch(history_matrix)` call. Row `k` of
+    /// the matrix is a read-only strided view of the `history_size` closes before bar
+    /// `start + k`, the same window `step` would get; no copy of the closes is made per row.
+    /// Subscribed pattern values and regime labels (of the bar before each row's bar) are passed
+    /// as `patterns=` (name -> array) and `regimes=` (list) keywords. The result must hold one
+    /// signal per row, as ints or `(signal, fraction)` tuples. Batch strategies don't see the
+    /// position, so their signals must not depend on it.
+    ///
+    /// If `step_batch` raises, every bar counts as a strategy error and holds, as with `step`,
+    /// unless `on_error` propagates the exception.
+    fn batch_signals(
+        &self,
+        py: Python<'_>,
+        strategy: &PyObject,
+        ticker: &str,
+        price_data: &[Bar],
+        start: usize,
+        pattern_signals: &[(String, Array1<i32>)],
+    ) -> PyResult<Vec<Option<Signal>>> {
+        let rows = price_data.len() - start;
+        let closes = PyArray1::from_vec(py, price_data.iter().map(|b| self.signal_price.of(b)).collect());
+        let windows = py.import("numpy.lib.stride_tricks")?
+            .call_method1("sliding_window_view", (closes, self.history_size))?;
+        let first = start - self.history_size;
+        let matrix = windows.get_item(PySlice::new(py, first as isize, (first + rows) as isize, 1))?;
+
+        let kwargs = PyDict::new(py);
+        if !pattern_signals.is_empty() {
+            let py_patterns = PyDict::new(py);
+            for (name, sig) in pattern_signals {
+                py_patterns.set_item(name, PyArray1::from_slice(py, &sig.as_slice().unwrap()[start - 1..price_data.len() - 1]))?;
+            }
+            kwargs.set_item("patterns", py_patterns)?;
+        }
+        if let Some(regimes) = &self.regimes {
+            let labels: Vec<Option<&str>> = price_data[start - 1..price_data.len() - 1].iter().map(|b| regimes.label(b)).collect();
+            kwargs.set_item("regimes", labels)?;
+        }
+
+        let result = match strategy.call_method(py, "step_batch", (matrix,), Some(kwargs)) {
+            Ok(result) => result,
+            Err(e) => {
+                log::error!("Error calling strategy.step_batch for {}: {}", ticker, e);
+                if self.on_error.propagates() {
+                    return Err(e);
+                }
+                return Ok(vec![None; rows]);
+            }
+        };
+        let result = result.as_ref(py);
+        if result.len()? != rows {
+            return Err(PyValueError::new_err(format!(
+                "strategy.step_batch for {} returned {} signals for {} bars", ticker, result.len()?, rows
+            )));
+        }
+        result.iter()?
+            .enumerate()
+            .map(|(k, obj)| Ok(Signal::from_py(obj?).map_err(|e| {
+                log::debug!("strategy.step_batch for {} at index {} did not return a valid signal: {}", ticker, start + k, e);
+            }).ok()))
+            .collect()
+    }
+
+    /// Shares `budget` buys at `fill_price` and the commission on them. With a lot size only
+    /// whole lots are bought and commission is charged on the notional actually bought.
+    fn buy_size(&self, spec: &SymbolSpec, fill_price: f64, budget: f64) -> (f64, f64) {
+        if spec.lot_size > 0.0 {
+            let affordable = if fill_price > 0.0 { budget / (fill_price * (1.0 + self.commission_rate)) } else { 0.0 };
+            let shares = spec.round_quantity(affordable);
+            (shares, shares * fill_price * self.commission_rate)
+        } else {
+            let commission = budget * self.commission_rate;
+            (if fill_price > 0.0 { (budget - commission) / fill_price } else { 0.0 }, commission)
+        }
+    }
+
+    /// Fills and accounting for one ticker from bar `start` on. `next_signal(i, history, position)`
+    /// supplies the signal for bar `i` given a ring of up to `history_size` closes before it, or `None` when
+    /// the signal source failed; that counts as a strategy error and is treated as 0.
+    ///
+    /// A sell for a fraction of the position scales out: the sold shares release their share of
+    /// the entry cost (average-cost basis) and the rest stays open. Win/loss counters and sell
+    /// indices count round trips, classified on the PnL of all legs once the position is flat;
+    /// partial sells are listed in `scale_out_indices` and as their own rows in the ledger.
+    #[allow(clippy::too_many_arguments)]
+    fn simulate_ticker<'py>(
+        &self,
+        py: Python<'py>,
+        ticker: &str,
+        price_data: &[Bar],
+        start: usize,
+        mut st: TickerState,
+        trace_enabled: bool,
+        mut next_signal: impl FnMut(usize, &HistoryBuffer, i32) -> PyResult<Option<Signal>>,
+    ) -> PyResult<TickerRun<'py>> {
+        // Closes before the current bar, in a fixed-size ring the strategy sees a view of
+        let mut history = HistoryBuffer::with_capacity(py, self.history_size)?;
+        for bar in &price_data[start.saturating_sub(self.history_size)..start] {
+            history.push(py, self.signal_price.of(bar));
+        }
+        let spec = self.symbol_spec(ticker);
+        let delisting = self.delistings.get(ticker);
+        let mut forced_exit = false;
+        let mut strategy_errors = 0;
+
+        // Exposure / turnover accounting
+        let mut entry_bar = if st.in_position {
+            price_data.iter().position(|b| b.date == st.entry_date).unwrap_or(start)
+        } else { 0 };
+        let mut bars_in_position = 0;
+        let mut held_bars_closed = 0;
+        let mut closed_trips = 0;
+
+        // Entry rules: bars since the last round trip closed, and the signals they refused
+        let mut last_exit_bar = if st.last_exit_date.is_empty() { None } else {
+            price_data.iter().position(|b| b.date == st.last_exit_date)
+        };
+        let mut overrides = Overrides::default();
+        let mut exposure_sum = 0.0;
+        let mut traded_notional = 0.0;
+
+        // Trade ledger with excursion tracking for the open position
+        let mut trade_log: Vec<Trade> = Vec::new();
+        let mut orders = OrderLog::default();
+        let mut participation = Participation::new(self.volume_limit);
+        if participation.is_limited() && price_data[start..].iter().all(|b| b.volume.is_nan() || b.volume <= 0.0) {
+            log::warn!("{}: no bar has volume, so the volume limit lets no order fill", ticker);
+        }
+        let mut bar_trace = BarTrace::default();
+
+        // Arrays for calculations
+        let mut portfolio_values: Vec<f64> = Vec::with_capacity(price_data.len() - start);
+        let mut bh_values: Vec<f64> = Vec::with_capacity(price_data.len() - start);
+
+        // Vectors to return to Python
+        let mut dates: Vec<String> = Vec::with_capacity(price_data.len() - start);
+        let mut closes: Vec<f64> = Vec::with_capacity(price_data.len() - start);
+        let mut signals: Vec<i32> = Vec::with_capacity(price_data.len() - start);
+        let mut balance_history: Vec<f64> = Vec::with_capacity(price_data.len() - start);
+        
+        // Indices (usize), typically converted to lists or arrays
+        let mut buy_indices: Vec<usize> = Vec::new();
+        let mut sell_win_indices: Vec<usize> = Vec::new();
+        let mut sell_loss_indices: Vec<usize> = Vec::new();
+        let mut sell_breakeven_indices: Vec<usize> = Vec::new();
+        let mut scale_out_indices: Vec<usize> = Vec::new();
+        let mut scale_in_indices: Vec<usize> = Vec::new();
+
+        // FIFO tax lots: cumulative realized PnL and open-position unrealized PnL per bar
+        let mut closed_lots: Vec<ClosedLot> = Vec::new();
+        let mut realized_pnl: Vec<f64> = Vec::with_capacity(price_data.len() - start);
+        // Equity overlay: shadow curve and entry scale per bar
+        let mut overlay_equity: Vec<f64> = Vec::new();
+        let mut overlay_scale: Vec<f64> = Vec::new();
+        let mut unrealized_pnl: Vec<f64> = Vec::with_capacity(price_data.len() - start);
+        // Open position in shares and the split of equity into cash and market value
+        let mut position_size: Vec<f64> = Vec::with_capacity(price_data.len() - start);
+        let mut cash: Vec<f64> = Vec::with_capacity(price_data.len() - start);
+        let mut invested: Vec<f64> = Vec::with_capacity(price_data.len() - start);
+
+        for i in start..price_data.len() {
+            let date = &price_data[i].date;
+            let current_price = price_data[i].close;
+
+            // Signal source sees the closes before bar i
+            let crr_pos_int = if st.in_position { 1 } else { 0 };
+            let next = next_signal(i, &history, crr_pos_int)?;
+            let step_failed = next.is_none();
+            let order = match next {
+                Some(Signal { target: Some(target), .. }) => {
+                    let invested = if st.in_position { st.shares * current_price } else { 0.0 };
+                    let equity = if st.in_position { invested + st.cash } else { st.balance };
+                    let exposure = if equity > 0.0 { invested / equity } else { 0.0 };
+                    Signal::rebalance(target, exposure, self.rebalance_threshold)
+                }
+                Some(order) => order,
+                None => Signal::full(0),
             };
+            let signal = order.side;
+            if step_failed { strategy_errors += 1; }
 
-            if price_data.len() <= self.history_size + 1 {
-                continue;
+            let was_in_position = st.in_position;
+            let scale_outs_before = scale_out_indices.len();
+            let scale_ins_before = scale_in_indices.len();
+            let overrides_before = overrides.len();
+
+            // Signal filters: a held-back signal acts as a hold; `signals` keeps the raw value.
+            if signal == st.streak_signal {
+                st.streak_bars += 1;
+            } else {
+                st.streak_signal = signal;
+                st.streak_bars = 1;
+            }
+            let held_back = self.signal_filters.blocked_by(signal, st.in_position, st.streak_bars, i - entry_bar);
+            if let Some(filter) = held_back {
+                log::debug!("{}: signal {} at index {} held back by {}", ticker, signal, i, filter);
+                overrides.record(i - start, signal, filter);
             }
+            let side = if held_back.is_some() { 0 } else { signal };
+            // The bar before a delisting sells the whole position at the recovery value.
+            let delisted = price_data[i].delisted && delisting.is_some();
+            if delisted && st.in_position {
+                forced_exit = true;
+                if held_back.is_none() && signal != -1 {
+                    overrides.record(i - start, signal, "delisting");
+                }
+            }
+            let (side, fraction) = if delisted { (-1, 1.0) } else { (side, order.fraction) };
 
-            // --- Simulation State ---
-            let mut balance = INITIAL_CAPITAL_PER_STOCK;
-            let mut shares = 0.0;
-            let mut in_position = false;
-            let mut trades = 0;
-            let mut wins = 0;
-            let mut entry_price = 0.0;
-
-            // Arrays for calculations
-            let mut portfolio_values: Vec<f64> = Vec::with_capacity(price_data.len() - self.history_size);
-            let bh_start_price = price_data[self.history_size].1;
-            let bh_shares = INITIAL_CAPITAL_PER_STOCK / bh_start_price;
-            let mut bh_values: Vec<f64> = Vec::with_capacity(price_data.len() - self.history_size);
-
-            // Vectors to return to Python
-            let mut dates: Vec<String> = Vec::with_capacity(price_data.len() - self.history_size);
-            let mut closes: Vec<f64> = Vec::with_capacity(price_data.len() - self.history_size);
-            let mut signals: Vec<i32> = Vec::with_capacity(price_data.len() - self.history_size);
-            let mut balance_history: Vec<f64> = Vec::with_capacity(price_data.len() - self.history_size);
-            
-            // Indices (usize), typically converted to lists or arrays
-            let mut buy_indices: Vec<usize> = Vec::new();
-            let mut sell_win_indices: Vec<usize> = Vec::new();
-            let mut sell_loss_indices: Vec<usize> = Vec::new();
-
-            for i in self.history_size..price_data.len() {
-                let (ref date, current_price) = price_data[i];
-
-                // Prepare history slice for Python Strategy
-                let history_slice: Vec<f64> = price_data[i - self.history_size..i].iter().map(|(_d,p)| *p).collect();
-                let py_history = PyArray1::from_slice(py, &history_slice);
-                let crr_pos_int = if in_position { 1 } else { 0 };
-
-                // Call Strategy
-                let signal: i32 = match self.strategy.call_method1(py, "step", (py_history, crr_pos_int)) {
-                    Ok(obj) => obj.extract(py).unwrap_or(0),
-                    Err(e) => {
-                        eprintln!("Error calling strategy.step for {} at index {}: {}", ticker, i, e);
-                        0
+            // Shares the volume limit carried from earlier bars keep filling while the strategy
+            // holds; an opposite signal cancels them.
+            let bar_volume = price_data[i].volume;
+            let capacity = participation.capacity(bar_volume, &spec);
+            if side == 1 && st.pending_sell > 0.0 {
+                participation.cancel(st.pending_sell);
+                st.pending_sell = 0.0;
+            }
+            if side == -1 && st.pending_buy > 0.0 {
+                participation.cancel(st.pending_buy);
+                st.pending_buy = 0.0;
+            }
+            let carry_buy = side == 0 && st.pending_buy > 0.0 && st.in_position;
+            let (side, fraction) = if side == 0 && st.pending_sell > 0.0 && st.in_position {
+                (-1, (st.pending_sell / st.shares).min(1.0))
+            } else {
+                (side, fraction)
+            };
+
+            let entry_scale = match &self.equity_overlay {
+                Some(overlay) => {
+                    let prev_close = if i > 0 { Some(price_data[i - 1].close) } else { None };
+                    let (equity, scale) = overlay.step(&mut st, prev_close, current_price, signal);
+                    overlay_equity.push(equity);
+                    overlay_scale.push(scale);
+                    scale
+                }
+                None => 1.0,
+            };
+            if let Some(rf) = self.risk_free.as_ref().filter(|rf| rf.accrue_cash)
+                && i > start
+            {
+                let growth = rf.growth(&price_data[i - 1].session, &price_data[i].session);
+                if st.in_position { st.cash *= growth } else { st.balance *= growth }
+            }
+            let value_before = if st.in_position { st.shares * current_price + st.cash } else { st.balance };
+
+            // Apply Logic
+            if st.in_position {
+                st.lowest_since_entry = f64::min(st.lowest_since_entry, price_data[i].low);
+                st.highest_since_entry = f64::max(st.highest_since_entry, price_data[i].high);
+
+                // Whole lots only; a partial sell that rounds to the full position closes it.
+                let mut exit_shares = st.shares * fraction;
+                if fraction < 1.0 && spec.lot_size > 0.0 {
+                    exit_shares = spec.round_quantity(exit_shares);
+                }
+                let mut closes_position = exit_shares >= st.shares * (1.0 - 1e-9);
+                if closes_position { exit_shares = st.shares; }
+                // The volume limit may leave part of an exit for later bars; delisting sells everything.
+                let requested_exit = exit_shares;
+                if side == -1 && !delisted {
+                    let (filled, carried) = participation.fill(exit_shares, capacity, bar_volume);
+                    st.pending_sell = carried;
+                    if filled < exit_shares {
+                        exit_shares = filled;
+                        closes_position = false;
                     }
-                };
+                }
 
-                // Apply Logic
-                if in_position {
-                    if signal == -1 {
-                        let revenue = shares * current_price;
-                        let profit = revenue - (shares * entry_price);
-                        if profit > 0.0 { wins += 1; sell_win_indices.push(i - self.history_size); }
-                        else { sell_loss_indices.push(i - self.history_size); }
-
-                        balance = revenue;
-                        in_position = false;
-                        shares = 0.0;
-                        trades += 1;
+                if (side == 1 && order.target.is_some()) || carry_buy {
+                    // Float target above the current exposure: add `fraction` of equity from cash.
+                    // Carried buys add the shares still pending, as far as the cash goes.
+                    if entry_scale <= 0.0 && !carry_buy {
+                        log::debug!("{}: scale-in at index {} overridden by equity_overlay", ticker, i);
+                        overrides.record(i - start, signal, "equity_overlay");
+                    } else {
+                        let fill_price = spec.round_buy_price(self.fill_model.buy_price(&price_data[i]));
+                        let equity = st.shares * current_price + st.cash;
+                        let budget = if carry_buy { st.cash } else { (equity * order.fraction * entry_scale).min(st.cash) };
+                        let (affordable, commission) = self.buy_size(&spec, fill_price, budget);
+                        let wanted = if carry_buy {
+                            participation.cancel((st.pending_buy - affordable).max(0.0));
+                            affordable.min(st.pending_buy)
+                        } else {
+                            affordable
+                        };
+                        let (shares, carried) = participation.fill(wanted, capacity, bar_volume);
+                        st.pending_buy = carried;
+                        let commission = if shares < affordable { shares * fill_price * self.commission_rate } else { commission };
+                        if shares <= 0.0 || shares * fill_price < spec.min_notional {
+                            if carried <= 0.0 {
+                                log::debug!("{}: scale-in at index {} is sized below one lot, the minimum notional or the volume limit, ignored", ticker, i);
+                                orders.rejected(i - start, date, "BUY", wanted, fill_price);
+                            }
+                        } else {
+                            orders.filled(i - start, date, "BUY", shares, wanted, fill_price);
+                            let cost = shares * fill_price + commission;
+                            st.entry_price = (st.entry_price * st.shares + fill_price * shares) / (st.shares + shares);
+                            st.shares += shares;
+                            st.entry_commission += commission;
+                            st.entry_cash += cost;
+                            st.cash -= cost;
+                            st.lots.buy(date, shares, cost);
+                            scale_in_indices.push(i - start);
+                            traded_notional += shares * fill_price;
+                        }
+                    }
+                } else if side == -1 && exit_shares <= 0.0 {
+                    if st.pending_sell <= 0.0 {
+                        log::debug!("{}: sell at index {} is below one lot or the volume limit, ignored", ticker, i);
+                        orders.rejected(i - start, date, "SELL", requested_exit, spec.round_sell_price(self.fill_model.sell_price(&price_data[i])));
                     }
+                } else if side == -1 {
+                    let exit_price = match delisting {
+                        Some(d) if delisted => current_price * d.recovery,
+                        _ => spec.round_sell_price(self.fill_model.sell_price(&price_data[i])),
+                    };
+                    orders.filled(i - start, date, "SELL", exit_shares, requested_exit, exit_price);
+                    let sold_share = exit_shares / st.shares;
+                    let cost = st.entry_cash * sold_share;
+                    let entry_commission = st.entry_commission * sold_share;
+                    let gross_revenue = exit_shares * exit_price;
+                    let exit_commission = gross_revenue * self.commission_rate;
+                    let revenue = gross_revenue - exit_commission;
+                    let gross_pnl = gross_revenue - (exit_shares * st.entry_price);
+                    let profit = revenue - cost;
+                    let leg_outcome = |gross: f64, net: f64, base: f64| match self.win_basis {
+                        WinBasis::Gross => TradeOutcome::classify(gross, base, self.breakeven_pct),
+                        WinBasis::Net => TradeOutcome::classify(net, base, self.breakeven_pct),
+                    };
+
+                    let sold = st.lots.sell(date, exit_shares, revenue);
+                    st.realized_pnl += sold.iter().map(|l| l.pnl()).sum::<f64>();
+                    closed_lots.extend(sold);
+                    traded_notional += revenue;
+
+                    let excursion = |p: f64| if st.entry_price > 0.0 { (p / st.entry_price - 1.0) * 100.0 } else { 0.0 };
+                    let mut trade = Trade {
+                        entry_index: entry_bar.saturating_sub(start),
+                        exit_index: i - start,
+                        entry_date: price_data[entry_bar].date.clone(),
+                        exit_date: date.clone(),
+                        entry_price: st.entry_price,
+                        exit_price,
+                        shares: exit_shares,
+                        closes_position,
+                        gross_pnl,
+                        pnl: profit,
+                        commission: entry_commission + exit_commission,
+                        outcome: leg_outcome(gross_pnl, profit, cost),
+                        mae_pct: excursion(st.lowest_since_entry),
+                        mfe_pct: excursion(st.highest_since_entry),
+                    };
+
+                    if closes_position {
+                        // The round trip is judged on every leg together against its full entry cost.
+                        let trip_gross = gross_pnl + st.scaled_out_gross_pnl;
+                        let trip_net = profit + st.scaled_out_pnl;
+                        let trip_cost = cost + st.scaled_out_cost;
+                        let gross_outcome = TradeOutcome::classify(trip_gross, trip_cost, self.breakeven_pct);
+                        let net_outcome = TradeOutcome::classify(trip_net, trip_cost, self.breakeven_pct);
+                        if gross_outcome == TradeOutcome::Win { st.gross_wins += 1; }
+                        if net_outcome == TradeOutcome::Win { st.net_wins += 1; }
+                        let trip_outcome = leg_outcome(trip_gross, trip_net, trip_cost);
+                        let trip_return = if trip_cost > 0.0 { trip_net / trip_cost } else { 0.0 };
+                        match trip_outcome {
+                            TradeOutcome::Win => st.win_return_sum += trip_return,
+                            TradeOutcome::Loss => st.loss_return_sum -= trip_return,
+                            TradeOutcome::Breakeven => {}
+                        }
+                        match trip_outcome {
+                            TradeOutcome::Win => { st.wins += 1; sell_win_indices.push(i - start); }
+                            TradeOutcome::Loss => sell_loss_indices.push(i - start),
+                            TradeOutcome::Breakeven => { st.breakevens += 1; sell_breakeven_indices.push(i - start); }
+                        }
+                        // A single full exit keeps the commission of the whole round trip.
+                        if st.scaled_out_cost == 0.0 { trade.commission = st.entry_commission + exit_commission; }
+
+                        st.balance = revenue + st.cash;
+                        st.cash = 0.0;
+                        st.in_position = false;
+                        st.shares = 0.0;
+                        // A closed position (e.g. by delisting) drops whatever the volume limit still carried.
+                        participation.cancel(st.pending_buy + st.pending_sell);
+                        st.pending_buy = 0.0;
+                        st.pending_sell = 0.0;
+                        st.scaled_out_cost = 0.0;
+                        st.scaled_out_gross_pnl = 0.0;
+                        st.scaled_out_pnl = 0.0;
+                        st.last_exit_date = date.clone();
+                        st.last_exit_loss = trip_outcome == TradeOutcome::Loss;
+                        last_exit_bar = Some(i);
+                        st.trades += 1;
+                        held_bars_closed += i - entry_bar;
+                        closed_trips += 1;
+                    } else {
+                        st.shares -= exit_shares;
+                        st.entry_cash -= cost;
+                        st.entry_commission -= entry_commission;
+                        st.cash += revenue;
+                        st.scaled_out_cost += cost;
+                        st.scaled_out_gross_pnl += gross_pnl;
+                        st.scaled_out_pnl += profit;
+                        scale_out_indices.push(i - start);
+                    }
+                    trade_log.push(trade);
+                }
+            } else if side == 1 {
+                let session = &price_data[i].session;
+                let entries_today = if st.entries_session == *session { st.entries_today } else { 0 };
+                let blocked = self.entry_rules.blocked_by(last_exit_bar.map(|b| i - b), st.last_exit_loss, entries_today)
+                    .or(if entry_scale <= 0.0 { Some("equity_overlay") } else { None });
+                if let Some(rule) = blocked {
+                    log::debug!("{}: buy at index {} overridden by {}", ticker, i, rule);
+                    overrides.record(i - start, signal, rule);
                 } else {
-                    if signal == 1 {
-                        in_position = true;
-                        entry_price = current_price;
-                        shares = if current_price > 0.0 { balance / current_price } else { 0.0 };
-                        buy_indices.push(i - self.history_size);
+                    let fill_price = spec.round_buy_price(self.fill_model.buy_price(&price_data[i]));
+                    let record = TradeRecord {
+                        wins: st.wins,
+                        losses: st.trades - st.wins - st.breakevens,
+                        win_return_sum: st.win_return_sum,
+                        loss_return_sum: st.loss_return_sum,
+                    };
+                    // A float target sizes the entry itself.
+                    let fraction = match order.target {
+                        Some(_) => order.fraction,
+                        None => self.position_sizer.fraction(price_data, i, &record),
+                    };
+                    let budget = st.balance * fraction.clamp(0.0, 1.0) * entry_scale;
+                    let (affordable, commission) = self.buy_size(&spec, fill_price, budget);
+                    let (shares, carried) = participation.fill(affordable, capacity, bar_volume);
+                    let commission = if shares < affordable { shares * fill_price * self.commission_rate } else { commission };
+
+                    if shares <= 0.0 || shares * fill_price < spec.min_notional {
+                        // Carrying needs an open position, so an entry that gets nothing is refused.
+                        participation.refuse(carried);
+                        log::debug!("{}: buy at index {} is sized below one lot, the minimum notional or the volume limit, ignored", ticker, i);
+                        orders.rejected(i - start, date, "BUY", affordable, fill_price);
+                    } else {
+                        orders.filled(i - start, date, "BUY", shares, affordable, fill_price);
+                        st.pending_buy = carried;
+                        st.in_position = true;
+                        st.entry_price = fill_price;
+                        st.entry_date = date.clone();
+                        st.shares = shares;
+                        st.entry_commission = commission;
+                        st.entry_cash = shares * fill_price + commission;
+                        st.cash = st.balance - st.entry_cash;
+                        st.lots.buy(date, shares, st.entry_cash);
+                        buy_indices.push(i - start);
+                        st.entries_session = session.clone();
+                        st.entries_today = entries_today + 1;
+                        entry_bar = i;
+                        traded_notional += st.shares * fill_price;
+                        st.lowest_since_entry = fill_price;
+                        st.highest_since_entry = fill_price;
                     }
                 }
+            }
 
-                // Record Data
-                signals.push(signal);
-                dates.push(date.clone());
-                closes.push(current_price);
+            // Record Data
+            signals.push(signal);
+            dates.push(date.clone());
+            closes.push(current_price);
 
-                let current_value = if in_position { shares * current_price } else { balance };
-                portfolio_values.push(current_value);
-                balance_history.push(current_value);
+            let current_value = if st.in_position { st.shares * current_price + st.cash } else { st.balance };
+            portfolio_values.push(current_value);
+            balance_history.push(current_value);
+            realized_pnl.push(st.realized_pnl);
+            unrealized_pnl.push(if st.in_position { st.shares * current_price - st.lots.cost_basis() } else { 0.0 });
+            position_size.push(if st.in_position { st.shares } else { 0.0 });
+            cash.push(if st.in_position { st.cash } else { st.balance });
+            invested.push(if st.in_position { st.shares * current_price } else { 0.0 });
 
-                bh_values.push(bh_shares * current_price);
+            if trace_enabled {
+                let action = if step_failed { BarAction::StrategyError }
+                    else if st.in_position && !was_in_position { BarAction::Buy }
+                    else if !st.in_position && was_in_position { BarAction::Sell }
+                    else if scale_out_indices.len() > scale_outs_before { BarAction::ScaleOut }
+                    else if scale_in_indices.len() > scale_ins_before { BarAction::ScaleIn }
+                    else if overrides.len() > overrides_before { BarAction::Overridden }
+                    else if signal != 0 { BarAction::Ignored }
+                    else { BarAction::Hold };
+                bar_trace.record(
+                    history.as_slice(py),
+                    signal,
+                    action,
+                    was_in_position as i32,
+                    st.in_position as i32,
+                    value_before,
+                    current_value,
+                );
             }
 
-            // --- Calc Metrics (Same as before) ---
-            let final_balance = *portfolio_values.last().unwrap_or(&balance);
-            let roi_pct = ((final_balance - INITIAL_CAPITAL_PER_STOCK) / INITIAL_CAPITAL_PER_STOCK) * 100.0;
-
-            let buy_and_hold_pct = if bh_values.len() > 0 {
-                let first = bh_values.first().unwrap();
-                let last = bh_values.last().unwrap();
-                ((last / first) - 1.0) * 100.0
-            } else { 0.0 };
-
-            let strategy_returns = pct_changes(&portfolio_values);
-            let annualized_return = if portfolio_values.len() > 0 {
-                let n_days = portfolio_values.len() as f64;
-                (portfolio_values.last().unwrap() / portfolio_values.first().unwrap()).powf(TRADING_DAYS_PER_YEAR / n_days) - 1.0
-            } else { 0.0 };
-
-            let std_daily = std_sample(&strategy_returns);
-            let annualized_vol = std_daily * TRADING_DAYS_PER_YEAR.sqrt();
-            let sharpe = if annualized_vol > 0.0 {
-                (annualized_return - self.risk_free_rate_annual) / annualized_vol
-            } else { 0.0 };
-
-            let max_dd = max_drawdown(&portfolio_values);
-            let alpha = roi_pct - buy_and_hold_pct;
-
-            let metric = StockMetric {
-                ticker: ticker.clone(),
-                final_balance,
-                trades,
-                wins,
-                roi_pct,
-                buy_and_hold_pct,
-                alpha_pct: alpha,
-                max_drawdown_pct: max_dd * 100.0,
-                sharpe,
-                n_periods: portfolio_values.len(),
-            };
+            if st.in_position {
+                bars_in_position += 1;
+                if current_value > 0.0 { exposure_sum += st.shares * current_price / current_value; }
+            }
+
+            bh_values.push(st.bh_shares * current_price);
+            history.push(py, self.signal_price.of(&price_data[i]));
+        }
+
+        // --- Calc Metrics (Same as before) ---
+        let metrics_started = Instant::now();
+        let final_balance = *portfolio_values.last().unwrap_or(&st.balance);
+        let roi_pct = ((final_balance - INITIAL_CAPITAL_PER_STOCK) / INITIAL_CAPITAL_PER_STOCK) * 100.0;
+
+        // Measured against the initial capital so resumed runs report lifetime figures like ROI does.
+        let buy_and_hold_pct = if bh_values.len() > 0 {
+            let last = bh_values.last().unwrap();
+            ((last / INITIAL_CAPITAL_PER_STOCK) - 1.0) * 100.0
+        } else { 0.0 };
 
-            // --- BUILD PYTHON RETURN OBJECT FOR THIS STOCK ---
-            let stock_detail = PyDict::new(py);
-            
-            // Convert Strings to Python List
-            stock_detail.set_item("dates", dates)?;
-            
-            // Convert numerical Vecs to NumPy Arrays (Zero-copy if possible, otherwise efficient copy)
-            stock_detail.set_item("closes", PyArray1::from_vec(py, closes))?;
-            stock_detail.set_item("signals", PyArray1::from_vec(py, signals))?;
-            stock_detail.set_item("balance_history", PyArray1::from_vec(py, balance_history))?;
-            
-            // Indices
-            stock_detail.set_item("buy_indices", PyArray1::from_vec(py, buy_indices))?;
-            stock_detail.set_item("sell_win_indices", PyArray1::from_vec(py, sell_win_indices))?;
-            stock_detail.set_item("sell_loss_indices", PyArray1::from_vec(py, sell_loss_indices))?;
-
-            // Add metric summary to details as well for convenience
-            let py_metric_dict = PyDict::new(py);
-            py_metric_dict.set_item("roi_pct", metric.roi_pct)?;
-            py_metric_dict.set_item("sharpe", metric.sharpe)?;
-            py_metric_dict.set_item("trades", metric.trades)?;
-            stock_detail.set_item("metrics", py_metric_dict)?;
-
-            // Store in main details map
-            py_details_map.set_item(ticker.clone(), stock_detail)?;
-
-            // --- Store Summary Metrics for Aggregate Calculation ---
-            metrics_vec.push(metric.clone());
-
-            // Add to summary list
-            let py_metric = PyDict::new(py);
-            py_metric.set_item("ticker", metric.ticker.clone())?;
-            py_metric.set_item("final_balance", metric.final_balance)?;
-            py_metric.set_item("trades", metric.trades)?;
-            py_metric.set_item("wins", metric.wins)?;
-            py_metric.set_item("roi_pct", metric.roi_pct)?;
-            py_metric.set_item("sharpe", metric.sharpe)?;
-            py_metrics_list.append(py_metric)?;
-        }
-
-        // --- Calculate Portfolio Aggregates (Unchanged Logic) ---
-        let mut total_final_balance = 0.0;
-        let mut total_initial_balance = 0.0;
-        let mut total_trades = 0;
-        let mut total_wins = 0;
-        let mut sum_alpha_pct = 0.0;
-        let mut count_roi_positive: i32 = 0;
-        let mut avg_sharpe: f64 = 0.0;
-
-        for r in &metrics_vec {
-            total_initial_balance += INITIAL_CAPITAL_PER_STOCK;
-            total_final_balance += r.final_balance;
-            total_trades += r.trades;
-            total_wins += r.wins;
-            avg_sharpe += r.sharpe;
-            sum_alpha_pct += r.alpha_pct;
-            if r.roi_pct > 0.0 { count_roi_positive += 1; }
-        }
-
-        let nstocks = metrics_vec.len() as f64;
-        if nstocks > 0.0 { avg_sharpe /= nstocks; }
-
-        let portfolio_roi = if total_initial_balance > 0.0 {
-            ((total_final_balance - total_initial_balance) / total_initial_balance) * 100.0
+        let sharpe = self.sharpe(&dates, &portfolio_values);
+
+        let max_dd = max_drawdown(&portfolio_values);
+        let alpha = roi_pct - buy_and_hold_pct;
+
+        let n_periods = portfolio_values.len();
+        let time_in_market_pct = if n_periods > 0 { bars_in_position as f64 / n_periods as f64 * 100.0 } else { 0.0 };
+        let avg_exposure_pct = if n_periods > 0 { exposure_sum / n_periods as f64 * 100.0 } else { 0.0 };
+        // Traded notional relative to average equity, scaled to one year of bars.
+        let avg_equity = mean(&portfolio_values);
+        let annual_turnover = if n_periods > 0 && avg_equity > 0.0 {
+            (traded_notional / avg_equity) * (TRADING_DAYS_PER_YEAR / n_periods as f64)
         } else { 0.0 };
+        let avg_holding_bars = if closed_trips > 0 { held_bars_closed as f64 / closed_trips as f64 } else { 0.0 };
+
+        let (beta, asset_beta, rolling_beta) = match &self.benchmark {
+            Some(bench) => {
+                let sessions: Vec<String> = price_data[start..].iter().map(|b| b.session.clone()).collect();
+                let (beta, rolling) = bench.betas(&dates, Some(&sessions), &portfolio_values);
+                let (asset_beta, _) = bench.betas(&dates, Some(&sessions), &closes);
+                (Some(beta), Some(asset_beta), Some(rolling))
+            }
+            None => (None, None, None),
+        };
+
+        st.last_date = price_data.last().unwrap().date.clone();
+
+        let metric = StockMetric {
+            ticker: ticker.to_string(),
+            final_balance,
+            trades: st.trades,
+            wins: st.wins,
+            gross_wins: st.gross_wins,
+            net_wins: st.net_wins,
+            breakevens: st.breakevens,
+            roi_pct,
+            buy_and_hold_pct,
+            alpha_pct: alpha,
+            max_drawdown_pct: max_dd * 100.0,
+            sharpe,
+            n_periods,
+            time_in_market_pct,
+            avg_exposure_pct,
+            annual_turnover,
+            avg_holding_bars,
+            beta,
+            asset_beta,
+            delisted: price_data.last().is_some_and(|b| b.delisted) && delisting.is_some(),
+        };
 
-        let win_rate = if total_trades > 0 { (total_wins as f64 / total_trades as f64) * 100.0 } else { 0.0 };
-        let avg_alpha_pct = if nstocks > 0.0 { sum_alpha_pct / nstocks } else { 0.0 };
-
-        let py_summary = PyDict::new(py);
-        py_summary.set_item("stocks_processed", metrics_vec.len())?;
-        py_summary.set_item("total_roi_pct", portfolio_roi)?;
-        py_summary.set_item("total_trades", total_trades)?;
-        py_summary.set_item("win_rate_pct", win_rate)?;
-        py_summary.set_item("final_capital", total_final_balance)?;
-        py_summary.set_item("average_alpha_pct", avg_alpha_pct)?;
-        py_summary.set_item("average_sharpe", avg_sharpe)?;
-
-        // --- Final Return ---
-        let py_out = PyDict::new(py);
-        py_out.set_item("metrics", py_metrics_list)?;
-        py_out.set_item("portfolio_summary", py_summary)?;
+        let daily_returns = pct_changes(&portfolio_values);
+
+        // --- BUILD PYTHON RETURN OBJECT FOR THIS STOCK ---
+        let stock_detail = PyDict::new(py);
+        
+        // Convert Strings to Python List
+        stock_detail.set_item("dates", &dates)?;
+        
+        // Convert numerical Vecs to NumPy Arrays (Zero-copy if possible, otherwise efficient copy)
+        stock_detail.set_item("closes", PyArray1::from_vec(py, closes))?;
+        stock_detail.set_item("signals", PyArray1::from_vec(py, signals))?;
+        stock_detail.set_item("balance_history", PyArray1::from_vec(py, balance_history))?;
         
-        // This is the new part: returning the huge data structure instead of file paths
-        py_out.set_item("details", py_details_map)?; 
+        // Indices
+        stock_detail.set_item("buy_indices", PyArray1::from_vec(py, buy_indices))?;
+        stock_detail.set_item("sell_win_indices", PyArray1::from_vec(py, sell_win_indices))?;
+        stock_detail.set_item("sell_loss_indices", PyArray1::from_vec(py, sell_loss_indices))?;
+        stock_detail.set_item("sell_breakeven_indices", PyArray1::from_vec(py, sell_breakeven_indices))?;
+        stock_detail.set_item("scale_out_indices", PyArray1::from_vec(py, scale_out_indices))?;
+        stock_detail.set_item("scale_in_indices", PyArray1::from_vec(py, scale_in_indices))?;
+        if self.entry_rules.is_active() || self.signal_filters.is_active() || self.equity_overlay.is_some() || !self.delistings.is_empty() {
+            stock_detail.set_item("overridden_signals", overrides.into_py(py)?)?;
+        }
+
+        if self.equity_overlay.is_some() {
+            let overlay = PyDict::new(py);
+            overlay.set_item("equity", PyArray1::from_vec(py, overlay_equity))?;
+            overlay.set_item("scale", PyArray1::from_vec(py, overlay_scale))?;
+            stock_detail.set_item("equity_overlay", overlay)?;
+        }
+        stock_detail.set_item("realized_pnl", PyArray1::from_vec(py, realized_pnl))?;
+        stock_detail.set_item("unrealized_pnl", PyArray1::from_vec(py, unrealized_pnl))?;
+        stock_detail.set_item("position_size", PyArray1::from_vec(py, position_size))?;
+        stock_detail.set_item("cash", PyArray1::from_vec(py, cash))?;
+        stock_detail.set_item("invested", PyArray1::from_slice(py, &invested))?;
+
+        stock_detail.set_item("trades", trades_to_py(py, &trade_log)?)?;
+        stock_detail.set_item("orders", orders.into_py(py)?)?;
+        if let Some(stats) = participation.to_py(py, st.pending_buy + st.pending_sell)? {
+            stock_detail.set_item("participation", stats)?;
+        }
+        stock_detail.set_item("tax_lots", closed_lots_to_py(py, &closed_lots)?)?;
+        if trace_enabled {
+            stock_detail.set_item("trace", bar_trace.into_py(py)?)?;
+        }
+        stock_detail.set_item("return_stats", return_stats_to_py(py, &daily_returns)?)?;
+        stock_detail.set_item("returns", PyArray1::from_vec(py, daily_returns))?;
+        stock_detail.set_item("drawdowns", drawdowns_to_py(py, &portfolio_values, &dates)?)?;
+        stock_detail.set_item("underwater", PyArray1::from_vec(py, underwater_curve(&portfolio_values)))?;
+        if let Some(rolling_beta) = rolling_beta {
+            stock_detail.set_item("rolling_beta", PyArray1::from_vec(py, rolling_beta))?;
+        }
+        if let Some(d) = delisting.filter(|_| metric.delisted) {
+            stock_detail.set_item("delisting", d.to_py(py, forced_exit)?)?;
+        }
+
+        // Add metric summary to details as well for convenience
+        stock_detail.set_item("metrics", metric.to_py(py)?)?;
+
+        if let Some(regimes) = &self.regimes {
+            let labels: Vec<Option<&str>> = price_data[start..].iter().map(|b| regimes.label(b)).collect();
+            let entries: Vec<(usize, bool)> = trade_log.iter()
+                .map(|t| (t.entry_index, t.outcome == TradeOutcome::Win))
+                .collect();
+            stock_detail.set_item("regime_metrics", regimes::segment_metrics_to_py(py, &labels, &portfolio_values, Some(&entries))?)?;
+            stock_detail.set_item("regimes", labels)?;
+        }
+
+        Ok(TickerRun {
+            detail: stock_detail,
+            metric,
+            equity_curve: (dates, portfolio_values),
+            position_values: invested,
+            state: st,
+            strategy_errors,
+            metrics_time: metrics_started.elapsed(),
+        })
+    }
+}
+
+/// Everything a pass over one data folder produces, before it is assembled into the result dict.
+struct RunOutput<'py> {
+    details: &'py PyDict,
+    metrics: Vec<StockMetric>,
+    /// (dates, equity) per ticker, in the same order as `metrics`
+    equity_curves: Vec<(Vec<String>, Vec<f64>)>,
+    /// Signed market value of each ticker's position per bar, aligned with `equity_curves`
+    position_curves: Vec<Vec<f64>>,
+    files: FileCounts,
+    warnings: Vec<String>,
+    state: EngineState,
+    /// Tickers whose details went to disk instead of `details`
+    spilled: Option<LazyDetails>,
+    /// Where each simulated ticker's run spent its time
+    timings: Vec<(String, TickerTiming)>,
+    /// (ticker, reason) of tickers left out under the NaN policy
+    invalid: Vec<(String, String)>,
+    /// Tickers stopped by an exception under `on_error="collect"`
+    errors: Vec<(String, TickerError)>,
+}
+
+/// Where `simulate` sends each finished ticker's details besides the result: an optional
+/// stream, and the budget deciding what is kept.
+#[derive(Default)]
+struct DetailSinks<'a> {
+    stream: Option<&'a mut ResultStream>,
+    budget: Option<&'a DetailBudget>,
+}
+
+/// One ticker's simulated result.
+struct TickerRun<'py> {
+    detail: &'py PyDict,
+    metric: StockMetric,
+    equity_curve: (Vec<String>, Vec<f64>),
+    /// Market value of the position per bar
+    position_values: Vec<f64>,
+    state: TickerState,
+    strategy_errors: usize,
+    /// Time spent on metrics and result arrays after the bar loop
+    metrics_time: Duration,
+}
+
+/// A ticker's bars and pattern signals, produced ahead of its simulation.
+struct PreparedTicker {
+    file_path: String,
+    ticker: String,
+    loaded: Result<Vec<Bar>, std::io::Error>,
+    load_time: Duration,
+    pattern_signals: Vec<(String, Array1<i32>)>,
+}
+
+#[derive(Debug, Default)]
+struct FileCounts {
+    skipped_read_error: usize,
+    skipped_too_short: usize,
+    strategy_errors: usize,
+    up_to_date: usize,
+}
+
+/// Builds the dict returned by `run` and `update`: per-ticker metrics, portfolio summary and
+/// curves, warnings, timing, details and the engine state as JSON text.
+fn assemble(engine: &BacktestEngine, py: Python<'_>, out: RunOutput<'_>) -> PyResult<PyObject> {
+    let py_metrics_list = PyList::empty(py);
+    for metric in &out.metrics {
+        py_metrics_list.append(metric.to_py(py)?)?;
+    }
+
+    // --- Calculate Portfolio Aggregates (Unchanged Logic) ---
+    let metrics_vec = &out.metrics;
+    let mut total_final_balance = 0.0;
+    let mut total_initial_balance = 0.0;
+    let mut total_trades = 0;
+    let mut total_wins = 0;
+    let mut total_gross_wins = 0;
+    let mut total_net_wins = 0;
+    let mut total_breakevens = 0;
+    let mut sum_alpha_pct = 0.0;
+    let mut count_roi_positive: i32 = 0;
+    let mut avg_sharpe: f64 = 0.0;
+
+    // Exposure stats are weighted by bars (holding period by closed trades)
+    let mut total_periods = 0.0;
+    let mut weighted_time_in_market = 0.0;
+    let mut weighted_exposure = 0.0;
+    let mut weighted_turnover = 0.0;
+    let mut total_held_bars = 0.0;
+
+    for r in metrics_vec {
+        let n = r.n_periods as f64;
+        total_periods += n;
+        weighted_time_in_market += r.time_in_market_pct * n;
+        weighted_exposure += r.avg_exposure_pct * n;
+        weighted_turnover += r.annual_turnover * n;
+        total_held_bars += r.avg_holding_bars * r.trades as f64;
 
-        Ok(py_out.to_object(py))
+        total_initial_balance += INITIAL_CAPITAL_PER_STOCK;
+        total_final_balance += r.final_balance;
+        total_trades += r.trades;
+        total_wins += r.wins;
+        total_gross_wins += r.gross_wins;
+        total_net_wins += r.net_wins;
+        total_breakevens += r.breakevens;
+        avg_sharpe += r.sharpe;
+        sum_alpha_pct += r.alpha_pct;
+        if r.roi_pct > 0.0 { count_roi_positive += 1; }
     }
+
+    let nstocks = metrics_vec.len() as f64;
+    if nstocks > 0.0 { avg_sharpe /= nstocks; }
+
+    let portfolio_roi = if total_initial_balance > 0.0 {
+        ((total_final_balance - total_initial_balance) / total_initial_balance) * 100.0
+    } else { 0.0 };
+
+    let rate_pct = |count: i32| if total_trades > 0 { (count as f64 / total_trades as f64) * 100.0 } else { 0.0 };
+    let win_rate = rate_pct(total_wins);
+    let avg_alpha_pct = if nstocks > 0.0 { sum_alpha_pct / nstocks } else { 0.0 };
+
+    let per_bar = |weighted: f64| if total_periods > 0.0 { weighted / total_periods } else { 0.0 };
+
+    // Weighted averages: by capital (mean equity over the run) and by exposure (bars in market)
+    let capital: Vec<f64> = out.equity_curves.iter().map(|(_, equity)| mean(equity)).collect();
+    let exposure: Vec<f64> = metrics_vec.iter().map(|r| r.time_in_market_pct * r.n_periods as f64).collect();
+    let weighted_mean = |weights: &[f64], value: fn(&StockMetric) -> f64| {
+        let total: f64 = weights.iter().sum();
+        if total > 0.0 {
+            metrics_vec.iter().zip(weights).map(|(r, w)| value(r) * w).sum::<f64>() / total
+        } else { 0.0 }
+    };
+    let avg_holding_bars = if total_trades > 0 { total_held_bars / total_trades as f64 } else { 0.0 };
+
+    let py_summary = PyDict::new(py);
+    py_summary.set_item("stocks_processed", metrics_vec.len())?;
+    py_summary.set_item("total_roi_pct", portfolio_roi)?;
+    py_summary.set_item("total_trades", total_trades)?;
+    py_summary.set_item("win_rate_pct", win_rate)?;
+    py_summary.set_item("gross_win_rate_pct", rate_pct(total_gross_wins))?;
+    py_summary.set_item("net_win_rate_pct", rate_pct(total_net_wins))?;
+    py_summary.set_item("breakeven_trades", total_breakevens)?;
+    py_summary.set_item("final_capital", total_final_balance)?;
+    py_summary.set_item("average_alpha_pct", avg_alpha_pct)?;
+    py_summary.set_item("average_sharpe", avg_sharpe)?;
+    py_summary.set_item("capital_weighted_sharpe", weighted_mean(&capital, |r| r.sharpe))?;
+    py_summary.set_item("exposure_weighted_sharpe", weighted_mean(&exposure, |r| r.sharpe))?;
+    py_summary.set_item("capital_weighted_alpha_pct", weighted_mean(&capital, |r| r.alpha_pct))?;
+    py_summary.set_item("exposure_weighted_alpha_pct", weighted_mean(&exposure, |r| r.alpha_pct))?;
+    py_summary.set_item("time_in_market_pct", per_bar(weighted_time_in_market))?;
+    py_summary.set_item("average_exposure_pct", per_bar(weighted_exposure))?;
+    py_summary.set_item("annual_turnover", per_bar(weighted_turnover))?;
+    py_summary.set_item("average_holding_bars", avg_holding_bars)?;
+    py_summary.set_item("files_skipped_read_error", out.files.skipped_read_error)?;
+    py_summary.set_item("files_skipped_too_short", out.files.skipped_too_short)?;
+    py_summary.set_item("strategy_errors", out.files.strategy_errors)?;
+    py_summary.set_item("files_up_to_date", out.files.up_to_date)?;
+    py_summary.set_item("delisted_tickers", out.metrics.iter().filter(|m| m.delisted).count())?;
+    py_summary.set_item("invalid_tickers", out.invalid.iter().cloned().collect::<HashMap<_, _>>())?;
+    py_summary.set_item("profile", timing::profile_to_py(py, &out.timings)?)?;
+
+    let (portfolio_dates, portfolio_equity) = combine_equity_curves(&out.equity_curves);
+    py_summary.set_item("portfolio_sharpe", engine.sharpe(&portfolio_dates, &portfolio_equity))?;
+    let portfolio_returns = pct_changes(&portfolio_equity);
+    let positions = exposure::align_positions(&portfolio_dates, &out.equity_curves, &out.position_curves);
+    let exposure = Exposure::new(positions.iter().map(|v| v.as_slice()), &portfolio_equity);
+    exposure.summarize(py_summary)?;
+    let py_portfolio = PyDict::new(py);
+    py_portfolio.set_item("exposure", exposure.series_to_py(py)?)?;
+    py_portfolio.set_item("drawdowns", drawdowns_to_py(py, &portfolio_equity, &portfolio_dates)?)?;
+    if let Some(regimes) = &engine.regimes {
+        let labels: Vec<Option<&str>> = portfolio_dates.iter().map(|d| regimes.label_for_date(d)).collect();
+        py_portfolio.set_item("regime_metrics", regimes::segment_metrics_to_py(py, &labels, &portfolio_equity, None)?)?;
+    }
+    py_portfolio.set_item("underwater", PyArray1::from_vec(py, underwater_curve(&portfolio_equity)))?;
+    py_portfolio.set_item("dates", portfolio_dates)?;
+    py_portfolio.set_item("equity", PyArray1::from_vec(py, portfolio_equity))?;
+    py_portfolio.set_item("return_stats", return_stats_to_py(py, &portfolio_returns)?)?;
+    py_portfolio.set_item("returns", PyArray1::from_vec(py, portfolio_returns))?;
+
+    // --- Final Return ---
+    let py_out = PyDict::new(py);
+    py_out.set_item("metrics", py_metrics_list)?;
+    py_out.set_item("portfolio_summary", py_summary)?;
+    py_out.set_item("portfolio", py_portfolio)?;
+    py_out.set_item("warnings", out.warnings)?;
+    let py_errors = PyDict::new(py);
+    for (ticker, error) in &out.errors {
+        py_errors.set_item(ticker, error.to_py(py)?)?;
+    }
+    py_out.set_item("errors", py_errors)?;
+    let py_timing = PyDict::new(py);
+    for (ticker, t) in &out.timings {
+        py_timing.set_item(ticker, t.to_py(py)?)?;
+    }
+    py_out.set_item("timing", py_timing)?;
+    
+    // This is the new part: returning the huge data structure instead of file paths
+    match out.spilled {
+        Some(spilled) => py_out.set_item("details", Py::new(py, spilled)?)?,
+        None => py_out.set_item("details", out.details)?,
+    }
+    py_out.set_item("state", out.state.to_json()?)?;
+
+    Ok(py_out.to_object(py))
 }
 
 // ----------------- Helper functions (Unchanged) -----------------
-fn load_date_and_prices(path: &str) -> Result<Vec<(String, f64)>, std::io::Error> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let mut rows = Vec::new();
-
-    for (index, line) in reader.lines().enumerate() {
-        if let Ok(l) = line {
-            if index == 0 { continue; } // skip header
-            let parts: Vec<&str> = l.split(',').collect();
-            if parts.len() > 4 {
-                let date = parts[0].trim().to_string();
-                if let Ok(p) = parts[4].trim().parse::<f64>() {
-                    rows.push((date, p));
-                }
-            }
-        }
+
+/// Trade ledger as a dict of column arrays, one entry per closed trade.
+fn trades_to_py<'py>(py: Python<'py>, trades: &[Trade]) -> PyResult<&'py PyDict> {
+    let ledger = PyDict::new(py);
+    ledger.set_item("entry_index", PyArray1::from_vec(py, trades.iter().map(|t| t.entry_index).collect()))?;
+    ledger.set_item("exit_index", PyArray1::from_vec(py, trades.iter().map(|t| t.exit_index).collect()))?;
+    ledger.set_item("entry_date", trades.iter().map(|t| t.entry_date.clone()).collect::<Vec<_>>())?;
+    ledger.set_item("exit_date", trades.iter().map(|t| t.exit_date.clone()).collect::<Vec<_>>())?;
+    ledger.set_item("entry_price", PyArray1::from_vec(py, trades.iter().map(|t| t.entry_price).collect()))?;
+    ledger.set_item("exit_price", PyArray1::from_vec(py, trades.iter().map(|t| t.exit_price).collect()))?;
+    ledger.set_item("shares", PyArray1::from_vec(py, trades.iter().map(|t| t.shares).collect()))?;
+    ledger.set_item("closes_position", trades.iter().map(|t| t.closes_position).collect::<Vec<_>>())?;
+    ledger.set_item("gross_pnl", PyArray1::from_vec(py, trades.iter().map(|t| t.gross_pnl).collect()))?;
+    ledger.set_item("pnl", PyArray1::from_vec(py, trades.iter().map(|t| t.pnl).collect()))?;
+    ledger.set_item("commission", PyArray1::from_vec(py, trades.iter().map(|t| t.commission).collect()))?;
+    ledger.set_item("outcome", trades.iter().map(|t| t.outcome.as_str()).collect::<Vec<_>>())?;
+    ledger.set_item("mae_pct", PyArray1::from_vec(py, trades.iter().map(|t| t.mae_pct).collect()))?;
+    ledger.set_item("mfe_pct", PyArray1::from_vec(py, trades.iter().map(|t| t.mfe_pct).collect()))?;
+    Ok(ledger)
+}
+
+/// Distribution summary of per-bar returns with pre-binned histogram counts.
+fn return_stats_to_py<'py>(py: Python<'py>, returns: &Vec<f64>) -> PyResult<&'py PyDict> {
+    let (counts, edges) = histogram(returns, RETURN_HISTOGRAM_BINS);
+    let out = PyDict::new(py);
+    out.set_item("mean", mean(returns))?;
+    out.set_item("std", std_sample(returns))?;
+    out.set_item("skewness", skewness(returns))?;
+    out.set_item("kurtosis", kurtosis(returns))?;
+    out.set_item("histogram_counts", PyArray1::from_vec(py, counts))?;
+    out.set_item("histogram_edges", PyArray1::from_vec(py, edges))?;
+    Ok(out)
+}
+
+/// Deepest drawdown episodes of an equity curve as a list of dicts with dates, bar counts and depth.
+fn drawdowns_to_py<'py>(py: Python<'py>, equity: &Vec<f64>, dates: &[String]) -> PyResult<&'py PyList> {
+    let out = PyList::empty(py);
+    for dd in drawdowns(equity, TOP_DRAWDOWNS) {
+        let item = PyDict::new(py);
+        item.set_item("start_date", &dates[dd.start])?;
+        item.set_item("trough_date", &dates[dd.trough])?;
+        item.set_item("recovery_date", dd.recovery.map(|r| dates[r].clone()))?;
+        item.set_item("depth_pct", dd.depth * 100.0)?;
+        item.set_item("duration_bars", dd.duration(equity.len()))?;
+        item.set_item("bars_to_trough", dd.trough - dd.start)?;
+        item.set_item("recovery_bars", dd.recovery.map(|r| r - dd.trough))?;
+        out.append(item)?;
     }
-    Ok(rows)
+    Ok(out)
 }
 
-fn pct_changes(series: &Vec<f64>) -> Vec<f64> {
-    if series.len() < 2 { return Vec::new(); }
-    let mut res = Vec::with_capacity(series.len() - 1);
-    for i in 1..series.len() {
-        let prev = series[i-1];
-        if prev.abs() < f64::EPSILON { res.push(0.0); }
-        else { res.push((series[i] / prev) - 1.0); }
+/// Sums per-ticker equity curves over the union of their dates. Before its first bar a ticker
+/// contributes its initial capital and after its last bar its final value.
+fn combine_equity_curves(curves: &[(Vec<String>, Vec<f64>)]) -> (Vec<String>, Vec<f64>) {
+    let mut all_dates: Vec<String> = curves.iter().flat_map(|(d, _)| d.iter().cloned()).collect();
+    all_dates.sort();
+    all_dates.dedup();
+
+    let mut equity = vec![0.0; all_dates.len()];
+    for (dates, values) in curves {
+        let mut k = 0;
+        let mut last = INITIAL_CAPITAL_PER_STOCK;
+        for (j, date) in all_dates.iter().enumerate() {
+            while k < dates.len() && dates[k] <= *date {
+                last = values[k];
+                k += 1;
+            }
+            equity[j] += last;
+        }
     }
-    res
+    (all_dates, equity)
 }
 
-fn mean(x: &Vec<f64>) -> f64 {
-    if x.is_empty() { return 0.0; }
-    x.iter().sum::<f64>() / (x.len() as f64)
+#[derive(Debug, Clone)]
+struct Bar {
+    date: String,
+    /// Trading session date; the date part of `date` unless a timezone applies
+    session: String,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+    /// Adjusted close from the data file; the close when it has none
+    adj_close: f64,
+    /// Last bar before the ticker's delisting (see `set_delistings`)
+    delisted: bool,
 }
 
-fn var_sample(x: &Vec<f64>) -> f64 {
-    let n = x.len();
-    if n < 2 { return 0.0; }
-    let m = mean(x);
-    x.iter().map(|v| (v - m).powi(2)).sum::<f64>() / ((n - 1) as f64)
+impl Bar {
+    fn new(date: String, open: f64, high: f64, low: f64, close: f64, volume: f64) -> Self {
+        let session = date.get(..10).unwrap_or(&date).to_string();
+        Bar { date, session, open, high, low, close, volume, adj_close: close, delisted: false }
+    }
 }
 
-fn std_sample(x: &Vec<f64>) -> f64 {
-    var_sample(x).sqrt()
+/// Reads a data file with the shared loader (see `series::read_csv_adjusted`).
+fn load_ohlcv(path: &str) -> Result<Vec<Bar>, std::io::Error> {
+    let (rows, adjusted) = series::read_csv_adjusted(path)?;
+    let mut bars: Vec<Bar> = rows.into_iter()
+        .map(|b| Bar::new(b.timestamp, b.open, b.high, b.low, b.close, b.volume))
+        .collect();
+    if let Some(adjusted) = adjusted {
+        for (bar, adj_close) in bars.iter_mut().zip(adjusted) {
+            bar.adj_close = adj_close;
+        }
+    }
+    Ok(bars)
 }
 
-fn max_drawdown(series: &Vec<f64>) -> f64 {
-    if series.is_empty() { return 0.0; }
-    let mut peak = series[0];
-    let mut max_dd = 0.0;
-    for &v in series {
-        if v > peak { peak = v; }
-        let dd = if peak > 0.0 { (peak - v) / peak } else { 0.0 };
-        if dd > max_dd { max_dd = dd; }
-    }
-    max_dd
-}
\ No newline at end of file
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outcome_within_breakeven_band() {
+        assert_eq!(TradeOutcome::classify(0.5, 1000.0, 0.1), TradeOutcome::Breakeven);
+        assert_eq!(TradeOutcome::classify(-1.0, 1000.0, 0.1), TradeOutcome::Breakeven);
+        assert_eq!(TradeOutcome::classify(0.0, 1000.0, 0.0), TradeOutcome::Breakeven);
+    }
+
+    #[test]
+    fn outcome_outside_breakeven_band() {
+        assert_eq!(TradeOutcome::classify(1.5, 1000.0, 0.1), TradeOutcome::Win);
+        assert_eq!(TradeOutcome::classify(-1.5, 1000.0, 0.1), TradeOutcome::Loss);
+        assert_eq!(TradeOutcome::classify(0.01, 1000.0, 0.0), TradeOutcome::Win);
+    }
+
+    #[test]
+    fn outcome_without_cost_basis_is_breakeven() {
+        assert_eq!(TradeOutcome::classify(5.0, 0.0, 0.0), TradeOutcome::Breakeven);
+    }
+
+    fn order(target: f64, exposure: f64, threshold: f64) -> (i32, f64) {
+        let s = Signal::rebalance(target, exposure, threshold);
+        (s.side, s.fraction)
+    }
+
+    #[test]
+    fn rebalance_buys_the_missing_share_of_equity() {
+        let (side, fraction) = order(0.8, 0.5, 0.05);
+        assert_eq!(side, 1);
+        assert!((fraction - 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rebalance_sells_a_share_of_the_position() {
+        let (side, fraction) = order(0.25, 0.5, 0.05);
+        assert_eq!(side, -1);
+        assert!((fraction - 0.5).abs() < 1e-12);
+        assert_eq!(order(0.0, 0.5, 0.05), (-1, 1.0));
+    }
+
+    #[test]
+    fn rebalance_holds_within_the_threshold() {
+        assert_eq!(order(0.52, 0.5, 0.05).0, 0);
+        assert_eq!(order(0.5, 0.5, 0.0).0, 0);
+    }
+
+    #[test]
+    fn rebalance_treats_negative_targets_as_flat() {
+        assert_eq!(order(-0.5, 0.5, 0.05), (-1, 1.0));
+        assert_eq!(order(-1.0, 0.0, 0.05).0, 0);
+    }
+}
@@ -0,0 +1,55 @@
+use ndarray::Array1;
+use std::collections::HashMap;
+
+use crate::indicators::corr_method::rolling_beta;
+use crate::stats::beta;
+
+/// Benchmark closes keyed by date. A bar is matched on its exact timestamp, or else on its
+/// session date, so a daily benchmark also covers intraday bars.
+pub(super) struct Benchmark {
+    closes: HashMap<String, f64>,
+    window: usize,
+}
+
+impl Benchmark {
+    pub fn new(closes: HashMap<String, f64>, window: usize) -> Self {
+        Benchmark { closes, window }
+    }
+
+    fn close(&self, date: &str, session: Option<&str>) -> Option<f64> {
+        self.closes.get(date)
+            .or_else(|| session.and_then(|s| self.closes.get(s)))
+            .copied()
+    }
+
+    /// Full-period beta of the bar-to-bar returns of `series` against the benchmark, plus the
+    /// rolling beta over the engine's window (NaN until the window fills). Bars whose return
+    /// can't be paired with a benchmark return are left out of both.
+    pub fn betas(&self, dates: &[String], sessions: Option<&[String]>, series: &[f64]) -> (f64, Vec<f64>) {
+        let bench: Vec<Option<f64>> = dates.iter().enumerate()
+            .map(|(k, d)| self.close(d, sessions.map(|s| s[k].as_str())))
+            .collect();
+
+        let mut positions = Vec::new();
+        let mut returns = Vec::new();
+        let mut bench_returns = Vec::new();
+        for k in 1..series.len() {
+            if let (Some(b0), Some(b1)) = (bench[k - 1], bench[k])
+                && series[k - 1].abs() > f64::EPSILON
+                && b0.abs() > f64::EPSILON
+            {
+                positions.push(k);
+                returns.push(series[k] / series[k - 1] - 1.0);
+                bench_returns.push(b1 / b0 - 1.0);
+            }
+        }
+
+        let full = beta(&returns, &bench_returns);
+        let rolling = rolling_beta(&Array1::from(returns), &Array1::from(bench_returns), self.window);
+        let mut out = vec![f64::NAN; series.len()];
+        for (k, b) in positions.into_iter().zip(rolling) {
+            out[k] = b;
+        }
+        (full, out)
+    }
+}
@@ -0,0 +1,174 @@
+use pyo3::exceptions::{PyIOError, PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::pct_changes;
+
+/// Per-bar entries of a ticker's details that downsampling thins out.
+const PER_BAR_KEYS: [&str; 13] = [
+    "dates", "closes", "signals", "balance_history", "realized_pnl", "unrealized_pnl", "position_size",
+    "cash", "invested", "underwater", "sessions", "regimes", "rolling_beta",
+];
+/// Dicts of per-bar columns, thinned out column by column.
+const PER_BAR_COLUMN_KEYS: [&str; 3] = ["trace", "patterns", "equity_overlay"];
+
+/// Limits on how much per-ticker detail a run keeps in memory.
+pub(super) struct DetailBudget {
+    /// Keep every `every`-th bar of the per-bar series (and always the last)
+    every: usize,
+    /// Tickers whose metrics fall below any of these minimums get no details
+    min_metrics: Vec<(String, f64)>,
+    /// Pickle kept details here instead of holding them in the result
+    spill_dir: Option<PathBuf>,
+}
+
+/// What happened to one ticker's details.
+pub(super) enum Kept {
+    Inline,
+    Spilled,
+    Dropped,
+}
+
+impl DetailBudget {
+    pub fn new(downsample: Option<usize>, min_metrics: Option<HashMap<String, f64>>, spill_dir: Option<String>) -> PyResult<Self> {
+        let every = downsample.unwrap_or(1);
+        if every == 0 {
+            return Err(PyValueError::new_err("downsample must be at least 1"));
+        }
+        let spill_dir = spill_dir.map(PathBuf::from);
+        if let Some(dir) = &spill_dir {
+            fs::create_dir_all(dir).map_err(|e| PyIOError::new_err(format!("creating {}: {}", dir.display(), e)))?;
+        }
+        Ok(DetailBudget { every, min_metrics: min_metrics.unwrap_or_default().into_iter().collect(), spill_dir })
+    }
+
+    pub fn spill_dir(&self) -> Option<&PathBuf> {
+        self.spill_dir.as_ref()
+    }
+
+    /// Filters, thins out and spills one finished ticker's details in place.
+    pub fn apply(&self, py: Python<'_>, ticker: &str, detail: &PyDict, metrics: &PyDict) -> PyResult<Kept> {
+        for (name, min) in &self.min_metrics {
+            let value: f64 = metrics.get_item(name.as_str())
+                .ok_or_else(|| PyValueError::new_err(format!("min_metrics names unknown metric '{}'", name)))?
+                .extract()?;
+            if value.is_nan() || value < *min {
+                log::debug!("Dropping details for {}: {} = {} is below {}", ticker, name, value, min);
+                return Ok(Kept::Dropped);
+            }
+        }
+        if self.every > 1 {
+            self.downsample(py, detail)?;
+        }
+        match &self.spill_dir {
+            Some(dir) => {
+                let bytes: &PyBytes = py.import("pickle")?.call_method1("dumps", (detail,))?.downcast()?;
+                let path = dir.join(format!("{}.pkl", ticker));
+                fs::write(&path, bytes.as_bytes()).map_err(|e| PyIOError::new_err(format!("writing {}: {}", path.display(), e)))?;
+                Ok(Kept::Spilled)
+            }
+            None => Ok(Kept::Inline),
+        }
+    }
+
+    /// Keeps every `every`-th bar plus the last. `bar_index` records the kept bar positions, so
+    /// the index arrays and trade ledger (left as they are) still locate bars; `returns` become
+    /// the returns between kept bars.
+    fn downsample(&self, py: Python<'_>, detail: &PyDict) -> PyResult<()> {
+        let Some(dates) = detail.get_item("dates") else { return Ok(()) };
+        let n = dates.len()?;
+        let mut kept: Vec<usize> = (0..n).step_by(self.every).collect();
+        if n > 0 && kept.last() != Some(&(n - 1)) {
+            kept.push(n - 1);
+        }
+
+        let numpy = py.import("numpy")?;
+        let take = |value: &PyAny| -> PyResult<PyObject> {
+            if let Ok(list) = value.downcast::<PyList>() {
+                Ok(PyList::new(py, kept.iter().map(|&k| list.get_item(k)).collect::<PyResult<Vec<_>>>()?).into())
+            } else {
+                Ok(numpy.call_method1("take", (value, kept.clone()))?.into())
+            }
+        };
+        for key in PER_BAR_KEYS {
+            if let Some(value) = detail.get_item(key) {
+                detail.set_item(key, take(value)?)?;
+            }
+        }
+        for key in PER_BAR_COLUMN_KEYS {
+            if let Some(columns) = detail.get_item(key) {
+                let columns: &PyDict = columns.downcast()?;
+                for (name, value) in columns.iter() {
+                    columns.set_item(name, take(value)?)?;
+                }
+            }
+        }
+        if let Some(equity) = detail.get_item("balance_history") {
+            let equity: Vec<f64> = equity.extract()?;
+            detail.set_item("returns", numpy.call_method1("array", (pct_changes(&equity),))?)?;
+        }
+        detail.set_item("bar_index", numpy.call_method1("array", (kept,))?)?;
+        Ok(())
+    }
+}
+
+/// Read-only mapping of ticker -> details over pickles spilled to disk; each access loads the
+/// ticker's file, so only the details in use occupy memory.
+#[pyclass]
+pub struct LazyDetails {
+    dir: PathBuf,
+    tickers: Vec<String>,
+}
+
+impl LazyDetails {
+    pub(super) fn new(dir: PathBuf) -> Self {
+        LazyDetails { dir, tickers: Vec::new() }
+    }
+
+    pub(super) fn push(&mut self, ticker: String) {
+        self.tickers.push(ticker);
+    }
+}
+
+#[pymethods]
+impl LazyDetails {
+    fn __getitem__(&self, py: Python<'_>, ticker: &str) -> PyResult<PyObject> {
+        if !self.tickers.iter().any(|t| t == ticker) {
+            return Err(PyKeyError::new_err(ticker.to_string()));
+        }
+        let path = self.dir.join(format!("{}.pkl", ticker));
+        let bytes = fs::read(&path).map_err(|e| PyIOError::new_err(format!("reading {}: {}", path.display(), e)))?;
+        Ok(py.import("pickle")?.call_method1("loads", (PyBytes::new(py, &bytes),))?.into())
+    }
+
+    fn get(&self, py: Python<'_>, ticker: &str, default: Option<PyObject>) -> PyResult<PyObject> {
+        if self.tickers.iter().any(|t| t == ticker) {
+            self.__getitem__(py, ticker)
+        } else {
+            Ok(default.unwrap_or_else(|| py.None()))
+        }
+    }
+
+    fn __contains__(&self, ticker: &str) -> bool {
+        self.tickers.iter().any(|t| t == ticker)
+    }
+
+    fn __len__(&self) -> usize {
+        self.tickers.len()
+    }
+
+    fn __iter__(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(PyList::new(py, &self.tickers).call_method0("__iter__")?.into())
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.tickers.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("LazyDetails({} tickers in {})", self.tickers.len(), self.dir.display())
+    }
+}
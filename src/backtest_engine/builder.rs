@@ -0,0 +1,228 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::errors::OnError;
+use super::rules::{EntryRules, SignalFilters};
+use super::sampling::SharpeSampling;
+use super::schedule::Scheduling;
+use super::signal_price::SignalPrice;
+use super::strategy_scope::StrategyScope;
+use super::symbols::SymbolSpec;
+use super::validation::NanPolicy;
+use super::{fills, sizing, BacktestEngine, WinBasis};
+use crate::timestamps;
+
+/// Step-by-step construction of a [`BacktestEngine`] from Rust, with every setting checked in
+/// [`build`](Self::build). Settings mirror the Python constructor's keyword arguments, which
+/// go through the same builder; anything not set takes the same default.
+///
+/// ```no_run
+/// use pyo3::prelude::*;
+/// use tradekit_rust::BacktestEngine;
+///
+/// Python::with_gil(|py| -> PyResult<()> {
+///     let strategy = py.import("strategies")?.getattr("Momentum")?.call0()?;
+///     let engine = BacktestEngine::builder()
+///         .strategy(strategy.into())
+///         .data_folder("historical_data")
+///         .history_size(20)
+///         .commission_bps(5.0)
+///         .fill_model("next_open")
+///         .build()?;
+///     Ok(())
+/// })
+/// .unwrap();
+/// ```
+#[derive(Default)]
+pub struct BacktestEngineBuilder {
+    strategy: Option<PyObject>,
+    history_size: Option<usize>,
+    data_folder: Option<String>,
+    risk_free_rate_annual: Option<f64>,
+    commission_bps: Option<f64>,
+    breakeven_pct: Option<f64>,
+    win_basis: Option<String>,
+    strict: Option<bool>,
+    timezone: Option<String>,
+    ticker_timezones: Option<HashMap<String, String>>,
+    fill_model: Option<String>,
+    symbols: Option<HashMap<String, HashMap<String, f64>>>,
+    entry_rules: Option<HashMap<String, usize>>,
+    position_sizer: Option<String>,
+    sizer_params: Option<HashMap<String, f64>>,
+    signal_filters: Option<HashMap<String, usize>>,
+    rebalance_threshold: Option<f64>,
+}
+
+impl BacktestEngineBuilder {
+    /// The Python object whose `step` (or `step_batch`) gives the signals. Required.
+    pub fn strategy(mut self, strategy: PyObject) -> Self {
+        self.strategy = Some(strategy);
+        self
+    }
+
+    /// Number of closes the strategy sees before each bar. Required, at least 1.
+    pub fn history_size(mut self, history_size: usize) -> Self {
+        self.history_size = Some(history_size);
+        self
+    }
+
+    /// Folder of `*_meso.csv` data files. Required, and must exist.
+    pub fn data_folder(mut self, data_folder: impl Into<String>) -> Self {
+        self.data_folder = Some(data_folder.into());
+        self
+    }
+
+    pub fn risk_free_rate_annual(mut self, rate: f64) -> Self {
+        self.risk_free_rate_annual = Some(rate);
+        self
+    }
+
+    pub fn commission_bps(mut self, bps: f64) -> Self {
+        self.commission_bps = Some(bps);
+        self
+    }
+
+    pub fn breakeven_pct(mut self, pct: f64) -> Self {
+        self.breakeven_pct = Some(pct);
+        self
+    }
+
+    /// "net" (default) or "gross".
+    pub fn win_basis(mut self, basis: impl Into<String>) -> Self {
+        self.win_basis = Some(basis.into());
+        self
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = Some(strict);
+        self
+    }
+
+    /// IANA name of the exchange zone of naive intraday timestamps.
+    pub fn timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
+    pub fn ticker_timezones(mut self, timezones: HashMap<String, String>) -> Self {
+        self.ticker_timezones = Some(timezones);
+        self
+    }
+
+    /// "close" (default), "next_open", "vwap" or "worst".
+    pub fn fill_model(mut self, name: impl Into<String>) -> Self {
+        self.fill_model = Some(name.into());
+        self
+    }
+
+    pub fn symbols(mut self, symbols: HashMap<String, HashMap<String, f64>>) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    pub fn entry_rules(mut self, rules: HashMap<String, usize>) -> Self {
+        self.entry_rules = Some(rules);
+        self
+    }
+
+    /// A sizer name and its parameters, e.g. ("fixed_fraction", {"fraction": 0.5}).
+    pub fn position_sizer(mut self, name: impl Into<String>, params: HashMap<String, f64>) -> Self {
+        self.position_sizer = Some(name.into());
+        self.sizer_params = Some(params);
+        self
+    }
+
+    pub fn signal_filters(mut self, filters: HashMap<String, usize>) -> Self {
+        self.signal_filters = Some(filters);
+        self
+    }
+
+    pub fn rebalance_threshold(mut self, threshold: f64) -> Self {
+        self.rebalance_threshold = Some(threshold);
+        self
+    }
+
+    /// Checks every setting and builds the engine; the error names the first bad one.
+    pub fn build(self) -> PyResult<BacktestEngine> {
+        let strategy = self.strategy.ok_or_else(|| PyValueError::new_err("a strategy is required"))?;
+        let history_size = self.history_size.ok_or_else(|| PyValueError::new_err("history_size is required"))?;
+        if history_size == 0 {
+            return Err(PyValueError::new_err("history_size must be at least 1"));
+        }
+        let data_folder = self.data_folder.ok_or_else(|| PyValueError::new_err("data_folder is required"))?;
+        if !Path::new(&data_folder).is_dir() {
+            return Err(PyValueError::new_err(format!("data_folder '{}' is not a directory", data_folder)));
+        }
+
+        let risk_free_rate_annual = self.risk_free_rate_annual.unwrap_or(0.0);
+        if !risk_free_rate_annual.is_finite() {
+            return Err(PyValueError::new_err(format!("risk_free_rate_annual must be finite, got {}", risk_free_rate_annual)));
+        }
+        let commission_bps = self.commission_bps.unwrap_or(0.0);
+        if commission_bps.is_nan() || !(0.0..10_000.0).contains(&commission_bps) {
+            return Err(PyValueError::new_err(format!("commission_bps must be in [0, 10000), got {}", commission_bps)));
+        }
+        let breakeven_pct = self.breakeven_pct.unwrap_or(0.0);
+        if !breakeven_pct.is_finite() {
+            return Err(PyValueError::new_err(format!("breakeven_pct must be finite, got {}", breakeven_pct)));
+        }
+        let win_basis = match self.win_basis.as_deref().unwrap_or("net") {
+            "net" => WinBasis::Net,
+            "gross" => WinBasis::Gross,
+            other => return Err(PyValueError::new_err(format!("win_basis must be 'net' or 'gross', got '{}'", other))),
+        };
+
+        let rebalance_threshold = self.rebalance_threshold.unwrap_or(0.05);
+        if !(0.0..1.0).contains(&rebalance_threshold) {
+            return Err(PyValueError::new_err(format!("rebalance_threshold must be in [0, 1), got {}", rebalance_threshold)));
+        }
+
+        let timezone = self.timezone.as_deref().map(timestamps::parse_tz).transpose()?;
+        let ticker_timezones = self.ticker_timezones.unwrap_or_default()
+            .into_iter()
+            .map(|(ticker, name)| Ok((ticker, timestamps::parse_tz(&name)?)))
+            .collect::<PyResult<HashMap<_, _>>>()?;
+        let symbol_specs = self.symbols.unwrap_or_default()
+            .iter()
+            .map(|(ticker, fields)| Ok((ticker.clone(), SymbolSpec::from_map(ticker, fields)?)))
+            .collect::<PyResult<HashMap<_, _>>>()?;
+
+        Ok(BacktestEngine {
+            strategy,
+            history_size,
+            data_folder,
+            risk_free_rate_annual,
+            commission_rate: commission_bps / 10_000.0,
+            breakeven_pct: breakeven_pct.abs(),
+            win_basis,
+            strict: self.strict.unwrap_or(false),
+            timezone,
+            ticker_timezones,
+            fill_model: fills::fill_model(self.fill_model.as_deref().unwrap_or("close"))?,
+            position_sizer: sizing::position_sizer(self.position_sizer.as_deref().unwrap_or("full"), &self.sizer_params.unwrap_or_default())?,
+            symbol_specs,
+            entry_rules: EntryRules::from_map(&self.entry_rules.unwrap_or_default())?,
+            signal_filters: SignalFilters::from_map(&self.signal_filters.unwrap_or_default())?,
+            rebalance_threshold,
+            regimes: None,
+            benchmark: None,
+            equity_overlay: None,
+            synthetics: Vec::new(),
+            detail_budget: None,
+            scheduling: Scheduling::Serial,
+            delistings: HashMap::new(),
+            risk_free: None,
+            nan_policy: NanPolicy::Mark,
+            signal_price: SignalPrice::Close,
+            on_error: OnError::Hold,
+            sharpe_sampling: SharpeSampling::Daily,
+            strategy_scope: StrategyScope::Shared,
+            whole_shares: false,
+            volume_limit: None,
+            history_view: false,
+        })
+    }
+}
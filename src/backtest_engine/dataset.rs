@@ -0,0 +1,146 @@
+use ndarray::{Array1, Array3};
+use numpy::{PyArray1, PyArray3};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use super::{BacktestEngine, Bar};
+
+const FEATURE_NAMES: [&str; 6] = ["close", "open", "high", "low", "returns", "log_returns"];
+const LABEL_NAMES: [&str; 3] = ["return", "log_return", "direction"];
+
+#[derive(Clone, Copy)]
+enum Feature {
+    Close,
+    Open,
+    High,
+    Low,
+    Returns,
+    LogReturns,
+}
+
+impl Feature {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "close" => Ok(Feature::Close),
+            "open" => Ok(Feature::Open),
+            "high" => Ok(Feature::High),
+            "low" => Ok(Feature::Low),
+            "returns" => Ok(Feature::Returns),
+            "log_returns" => Ok(Feature::LogReturns),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown feature '{}', expected one of {}", name, FEATURE_NAMES.join(", ")
+            ))),
+        }
+    }
+
+    /// Value at bar `j`; return features need bar `j - 1` too.
+    fn value(self, bars: &[Bar], j: usize) -> f64 {
+        match self {
+            Feature::Close => bars[j].close,
+            Feature::Open => bars[j].open,
+            Feature::High => bars[j].high,
+            Feature::Low => bars[j].low,
+            Feature::Returns => bars[j].close / bars[j - 1].close - 1.0,
+            Feature::LogReturns => (bars[j].close / bars[j - 1].close).ln(),
+        }
+    }
+
+    fn needs_previous(self) -> bool {
+        matches!(self, Feature::Returns | Feature::LogReturns)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Label {
+    Return,
+    LogReturn,
+    /// 1 above `threshold`, -1 below `-threshold`, else 0
+    Direction(f64),
+}
+
+impl Label {
+    fn parse(name: &str, threshold: f64) -> PyResult<Self> {
+        match name {
+            "return" => Ok(Label::Return),
+            "log_return" => Ok(Label::LogReturn),
+            "direction" => Ok(Label::Direction(threshold)),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown label '{}', expected one of {}", name, LABEL_NAMES.join(", ")
+            ))),
+        }
+    }
+
+    fn value(self, from: f64, to: f64) -> f64 {
+        let r = to / from - 1.0;
+        match self {
+            Label::Return => r,
+            Label::LogReturn => (to / from).ln(),
+            Label::Direction(t) => if r > t { 1.0 } else if r < -t { -1.0 } else { 0.0 },
+        }
+    }
+}
+
+/// Samples of every ticker in the engine's data folder. Each sample stands for a bar
+/// `i`: its window covers bars `i - window .. i` (what `step` sees at bar `i`) and its label
+/// compares the close of bar `i + horizon` to the close of bar `i`, where a signal for bar `i`
+/// would be filled.
+pub(super) fn make_dataset(
+    engine: &BacktestEngine,
+    py: Python<'_>,
+    window: usize,
+    horizon: usize,
+    feature_names: &[String],
+    label: &str,
+    threshold: f64,
+) -> PyResult<PyObject> {
+    if window == 0 || horizon == 0 {
+        return Err(PyValueError::new_err("window and horizon must be at least 1"));
+    }
+    let label = Label::parse(label, threshold)?;
+    let features = feature_names.iter().map(|f| Feature::parse(f)).collect::<PyResult<Vec<_>>>()?;
+    if features.is_empty() {
+        return Err(PyValueError::new_err("features must name at least one feature"));
+    }
+    let lead = features.iter().any(|f| f.needs_previous()) as usize;
+
+    let mut values: Vec<f64> = Vec::new();
+    let mut y: Vec<f64> = Vec::new();
+    let mut tickers: Vec<String> = Vec::new();
+    let mut dates: Vec<String> = Vec::new();
+
+    for path in BacktestEngine::data_files_in(&engine.data_folder) {
+        let file_path = path.to_str().unwrap();
+        let ticker = path.file_stem().unwrap().to_str().unwrap().replace("_meso", "");
+        let bars = match engine.load_bars(file_path, &ticker) {
+            Ok(b) => b,
+            Err(e) => {
+                log::warn!("Skipping {} because of read error: {}", file_path, e);
+                continue;
+            }
+        };
+        let first = window + lead;
+        if bars.len() < first + horizon + 1 {
+            log::info!("Skipping {}: {} bars is too short for window {} and horizon {}", file_path, bars.len(), window, horizon);
+            continue;
+        }
+        for i in first..bars.len() - horizon {
+            for j in i - window..i {
+                values.extend(features.iter().map(|f| f.value(&bars, j)));
+            }
+            y.push(label.value(bars[i].close, bars[i + horizon].close));
+            tickers.push(ticker.clone());
+            dates.push(bars[i].date.clone());
+        }
+    }
+
+    let x = Array3::from_shape_vec((y.len(), window, features.len()), values)
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let out = PyDict::new(py);
+    out.set_item("X", PyArray3::from_owned_array(py, x))?;
+    out.set_item("y", PyArray1::from_owned_array(py, Array1::from(y)))?;
+    out.set_item("tickers", tickers)?;
+    out.set_item("dates", dates)?;
+    out.set_item("features", feature_names)?;
+    Ok(out.to_object(py))
+}
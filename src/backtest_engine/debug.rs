@@ -0,0 +1,230 @@
+use ndarray::Array1;
+use pyo3::exceptions::{PyIOError, PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::Path;
+
+use super::history::HistoryBuffer;
+use super::state::TickerState;
+use super::{validation, BacktestEngine, Bar, Signal, INITIAL_CAPITAL_PER_STOCK};
+
+/// One ticker under an engine, simulated a bar at a time for inspection in a notebook.
+///
+/// Each stepped bar asks the strategy for its signal once, then the engine replays the recorded
+/// signals from the first bar through `simulate_ticker`, so fills, risk overrides and balances
+/// come from exactly the code `run` uses. Replaying costs one pass over the bars stepped so
+/// far. Moving back with `jump_to` keeps the recorded signals, and stepping forward again
+/// reuses them instead of calling the strategy a second time.
+#[pyclass]
+pub struct DebugEngine {
+    engine: Py<BacktestEngine>,
+    /// The strategy object trading this ticker, per the engine's strategy scope
+    strategy: PyObject,
+    ticker: String,
+    bars: Vec<Bar>,
+    pattern_signals: Vec<(String, Array1<i32>)>,
+    /// First simulated bar, once `history_size` closes precede it
+    start: usize,
+    /// Signals of bars `start..` asked for so far; precomputed for batch strategies
+    signals: Vec<Option<Signal>>,
+    /// Bars stepped past `start`
+    stepped: usize,
+    /// Engine state and details replayed up to the first `replayed` stepped bars
+    replayed: usize,
+    state: Option<TickerState>,
+    detail: Option<PyObject>,
+}
+
+impl DebugEngine {
+    /// Bar index of the last stepped bar.
+    fn index(&self) -> Option<usize> {
+        self.stepped.checked_sub(1).map(|k| self.start + k)
+    }
+
+    /// Steps past one more bar, asking the strategy for its signal if it has not been asked yet.
+    fn advance(&mut self, py: Python<'_>, engine: &BacktestEngine) -> PyResult<()> {
+        let i = self.start + self.stepped;
+        if i >= self.bars.len() {
+            return Err(PyIndexError::new_err(format!("all {} bars of {} have been stepped", self.bars.len(), self.ticker)));
+        }
+        if self.stepped == self.signals.len() {
+            // The strategy sees the position left by the bars before this one.
+            self.replay(py, engine)?;
+            let mut history = HistoryBuffer::with_capacity(py, engine.history_size)?;
+            for bar in &self.bars[i - engine.history_size..i] {
+                history.push(py, engine.signal_price.of(bar));
+            }
+            let position = if self.state.as_ref().is_some_and(|st| st.in_position) { 1 } else { 0 };
+            let signal = engine.step_signal(py, &self.strategy, &self.ticker, &self.bars, &self.pattern_signals, i, &history, position)?;
+            self.signals.push(signal);
+        }
+        self.stepped += 1;
+        Ok(())
+    }
+
+    /// Re-simulates bars `start..=index()` from a fresh state with the recorded signals, unless
+    /// that is what the current state already holds.
+    fn replay(&mut self, py: Python<'_>, engine: &BacktestEngine) -> PyResult<()> {
+        if self.replayed == self.stepped {
+            return Ok(());
+        }
+        let Some(last) = self.index() else {
+            self.state = None;
+            self.detail = None;
+            self.replayed = 0;
+            return Ok(());
+        };
+        let start = self.start;
+        let signals = &self.signals;
+        let st = TickerState::new(INITIAL_CAPITAL_PER_STOCK, INITIAL_CAPITAL_PER_STOCK / self.bars[start].close);
+        let run = engine.simulate_ticker(py, &self.ticker, &self.bars[..=last], start, st, true, |i, _, _| Ok(signals[i - start]))?;
+        self.state = Some(run.state);
+        self.detail = Some(run.detail.to_object(py));
+        self.replayed = self.stepped;
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl DebugEngine {
+    /// Loads `ticker` from the engine's data folder (or builds it, for a synthetic instrument)
+    /// and positions before the first bar `run` would simulate. Strategies with `step_batch`
+    /// get their signals for every bar up front, as in `run`. The strategy is reset or built
+    /// for the ticker as its strategy scope says.
+    #[new]
+    fn new(py: Python<'_>, engine: Py<BacktestEngine>, ticker: String) -> PyResult<Self> {
+        let (strategy, bars, pattern_signals, signals, start) = {
+            let engine = engine.borrow(py);
+            let bars = match engine.synthetics.iter().find(|(name, _)| *name == ticker) {
+                Some((_, synthetic)) => synthetic.bars(&engine, &engine.data_folder),
+                None => {
+                    let path = Path::new(&engine.data_folder).join(format!("{}_meso.csv", ticker));
+                    engine.load_bars(path.to_str().unwrap(), &ticker)
+                }
+            }.map_err(|e| PyIOError::new_err(format!("failed to load {}: {}", ticker, e)))?;
+            if let Some(reason) = engine.nan_policy.screen(&ticker, || validation::check_bars(&bars))? {
+                return Err(PyValueError::new_err(format!("{}: {}", ticker, reason)));
+            }
+            let start = engine.history_size;
+            if bars.len() <= start + 1 {
+                return Err(PyValueError::new_err(format!(
+                    "{} has {} bars, which is not more than history_size + 1", ticker, bars.len()
+                )));
+            }
+            engine.strategy_scope.check(py, &engine.strategy)?;
+            let strategy = engine.strategy_scope.instance(py, &engine.strategy, &ticker)?;
+            let subscribed = BacktestEngine::subscribed_patterns(py, &strategy);
+            let pattern_signals = BacktestEngine::pattern_signals(&subscribed, &bars);
+            let signals = if strategy.as_ref(py).hasattr("step_batch")? {
+                engine.batch_signals(py, &strategy, &ticker, &bars, start, &pattern_signals)?
+            } else {
+                Vec::new()
+            };
+            (strategy, bars, pattern_signals, signals, start)
+        };
+        Ok(DebugEngine { engine, strategy, ticker, bars, pattern_signals, start, signals, stepped: 0, replayed: 0, state: None, detail: None })
+    }
+
+    /// Simulates the next `n` bars (default 1) and returns `peek_state()`. Raises IndexError
+    /// past the last bar.
+    fn step(&mut self, py: Python<'_>, n: Option<usize>) -> PyResult<PyObject> {
+        let engine = self.engine.clone_ref(py);
+        let engine = engine.borrow(py);
+        for _ in 0..n.unwrap_or(1) {
+            self.advance(py, &engine)?;
+        }
+        self.replay(py, &engine)?;
+        self.peek_state(py)
+    }
+
+    /// Moves to the first bar dated on or after `date` (ISO dates compare as text), stepping
+    /// forward or back, and returns `peek_state()`.
+    fn jump_to(&mut self, py: Python<'_>, date: &str) -> PyResult<PyObject> {
+        let Some(target) = self.bars[self.start..].iter().position(|b| b.date.as_str() >= date) else {
+            return Err(PyValueError::new_err(format!("{} has no bar on or after {}", self.ticker, date)));
+        };
+        let engine = self.engine.clone_ref(py);
+        let engine = engine.borrow(py);
+        if target < self.stepped {
+            self.stepped = target + 1;
+        }
+        while self.stepped <= target {
+            self.advance(py, &engine)?;
+        }
+        self.replay(py, &engine)?;
+        self.peek_state(py)
+    }
+
+    /// The engine's view after the last stepped bar: its `index`, `date` and prices, the
+    /// strategy's `signal` (None when `step` failed), the `action` taken and the `override`
+    /// rule that refused the signal, if any, the position, `cash`, `equity`, entry and PnL
+    /// figures, and the trade counts. None before the first step.
+    fn peek_state(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let (Some(i), Some(st), Some(detail)) = (self.index(), &self.state, &self.detail) else {
+            return Ok(py.None());
+        };
+        let bar = &self.bars[i];
+        let detail = detail.as_ref(py);
+        let last = |key: &str| -> PyResult<&PyAny> { detail.get_item(key)?.get_item(-1) };
+        let action = detail.get_item("trace")?.get_item("action")?.get_item(-1)?;
+        // Rule that refused this bar's signal, from the replay's override log
+        let rule = match detail.downcast::<PyDict>()?.get_item("overridden_signals") {
+            Some(overrides) => {
+                let index: Vec<usize> = overrides.get_item("index")?.extract()?;
+                let rules: Vec<String> = overrides.get_item("rule")?.extract()?;
+                index.iter().rposition(|&k| k == self.stepped - 1).map(|k| rules[k].clone())
+            }
+            None => None,
+        };
+
+        let d = PyDict::new(py);
+        d.set_item("index", i)?;
+        d.set_item("date", &bar.date)?;
+        d.set_item("open", bar.open)?;
+        d.set_item("high", bar.high)?;
+        d.set_item("low", bar.low)?;
+        d.set_item("close", bar.close)?;
+        d.set_item("signal", self.signals[self.stepped - 1].map(|s| s.side))?;
+        d.set_item("action", action)?;
+        d.set_item("override", rule)?;
+        d.set_item("in_position", st.in_position)?;
+        d.set_item("shares", st.shares)?;
+        d.set_item("cash", last("cash")?)?;
+        d.set_item("equity", if st.in_position { st.shares * bar.close + st.cash } else { st.balance })?;
+        d.set_item("entry_date", st.in_position.then_some(st.entry_date.as_str()))?;
+        d.set_item("entry_price", st.in_position.then_some(st.entry_price))?;
+        d.set_item("realized_pnl", st.realized_pnl)?;
+        d.set_item("unrealized_pnl", last("unrealized_pnl")?)?;
+        d.set_item("trades", st.trades)?;
+        d.set_item("wins", st.wins)?;
+        Ok(d.to_object(py))
+    }
+
+    /// `run`-style details of the bars stepped so far, with a `trace`; None before the first step.
+    #[getter]
+    fn details(&self, py: Python<'_>) -> PyObject {
+        self.detail.as_ref().map_or_else(|| py.None(), |d| d.clone_ref(py))
+    }
+
+    /// Bar index of the last stepped bar, or None before the first step.
+    #[getter(index)]
+    fn py_index(&self) -> Option<usize> {
+        self.index()
+    }
+
+    #[getter]
+    fn done(&self) -> bool {
+        self.start + self.stepped >= self.bars.len()
+    }
+
+    fn __len__(&self) -> usize {
+        self.bars.len()
+    }
+
+    fn __repr__(&self) -> String {
+        match self.index() {
+            Some(i) => format!("DebugEngine({}, bar {} of {}, {})", self.ticker, i, self.bars.len(), self.bars[i].date),
+            None => format!("DebugEngine({}, before bar {} of {})", self.ticker, self.start, self.bars.len()),
+        }
+    }
+}
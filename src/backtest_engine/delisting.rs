@@ -0,0 +1,112 @@
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
+use std::fs;
+
+use super::Bar;
+
+/// When a ticker stopped trading and what its shares were worth afterwards.
+#[derive(Debug, Clone)]
+pub(super) struct Delisting {
+    /// Session date (YYYY-MM-DD) of the delisting
+    pub date: String,
+    /// Share of the last close paid out per share still held
+    pub recovery: f64,
+}
+
+impl Delisting {
+    /// Whether `bar` belongs to a session on or before the delisting date.
+    pub fn listed_on(&self, bar: &Bar) -> bool {
+        bar.session.as_str() <= self.date.as_str()
+    }
+
+    /// Drops the bars after the delisting and marks the last one left as delisted, but only if
+    /// it is the delisting session or bars were dropped. Data that ends before the delisting
+    /// date (a stale file, or one not yet updated) is left as it is.
+    pub fn truncate(&self, bars: &mut Vec<Bar>) {
+        let listed = bars.partition_point(|b| self.listed_on(b));
+        let dropped = listed < bars.len();
+        bars.truncate(listed);
+        if let Some(last) = bars.last_mut()
+            && (dropped || last.session == self.date)
+        {
+            last.delisted = true;
+        }
+    }
+
+    pub fn to_py<'py>(&self, py: Python<'py>, forced_exit: bool) -> PyResult<&'py PyDict> {
+        let d = PyDict::new(py);
+        d.set_item("date", &self.date)?;
+        d.set_item("recovery", self.recovery)?;
+        d.set_item("forced_exit", forced_exit)?;
+        Ok(d)
+    }
+}
+
+/// Reads a delisting file: a CSV whose header names a `ticker` and a `delisting_date` column and
+/// optionally a `recovery` column (blank or missing means 0).
+pub(super) fn load(path: &str) -> PyResult<HashMap<String, Delisting>> {
+    let text = fs::read_to_string(path).map_err(|e| PyIOError::new_err(format!("reading {}: {}", path, e)))?;
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let header: Vec<&str> = lines.next().unwrap_or("").split(',').map(|c| c.trim()).collect();
+    let column = |name: &str| header.iter().position(|c| *c == name);
+    let (Some(ticker_col), Some(date_col)) = (column("ticker"), column("delisting_date")) else {
+        return Err(PyValueError::new_err(format!("{} needs 'ticker' and 'delisting_date' columns", path)));
+    };
+    let recovery_col = column("recovery");
+
+    let mut out = HashMap::new();
+    for (k, line) in lines.enumerate() {
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        let row_err = |what: &str| PyValueError::new_err(format!("{} row {}: {}", path, k + 1, what));
+        let ticker = fields.get(ticker_col).filter(|t| !t.is_empty()).ok_or_else(|| row_err("no ticker"))?;
+        let date = fields.get(date_col).and_then(|d| d.get(..10)).ok_or_else(|| row_err("no delisting_date"))?;
+        let recovery = match recovery_col.and_then(|c| fields.get(c)).filter(|r| !r.is_empty()) {
+            Some(r) => r.parse::<f64>().map_err(|_| row_err(&format!("recovery '{}' is not a number", r)))?,
+            None => 0.0,
+        };
+        if recovery.is_nan() || recovery < 0.0 {
+            return Err(row_err(&format!("recovery must be >= 0, got {}", recovery)));
+        }
+        out.insert(ticker.to_string(), Delisting { date: date.to_string(), recovery });
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bars(dates: &[&str]) -> Vec<Bar> {
+        dates.iter().map(|d| Bar::new(d.to_string(), 1.0, 1.0, 1.0, 1.0, 0.0)).collect()
+    }
+
+    fn delisting(date: &str) -> Delisting {
+        Delisting { date: date.to_string(), recovery: 0.0 }
+    }
+
+    #[test]
+    fn bars_after_the_delisting_are_dropped() {
+        let mut b = bars(&["2020-01-02", "2020-01-03", "2020-01-06"]);
+        delisting("2020-01-04").truncate(&mut b);
+        assert_eq!(b.len(), 2);
+        assert!(b[1].delisted && !b[0].delisted);
+    }
+
+    #[test]
+    fn delisting_session_is_marked() {
+        let mut b = bars(&["2020-01-02", "2020-01-03"]);
+        delisting("2020-01-03").truncate(&mut b);
+        assert_eq!(b.len(), 2);
+        assert!(b[1].delisted);
+    }
+
+    #[test]
+    fn delisting_after_the_last_bar_leaves_the_data_listed() {
+        let mut b = bars(&["2020-01-02", "2020-01-03"]);
+        delisting("2020-06-30").truncate(&mut b);
+        assert_eq!(b.len(), 2);
+        assert!(b.iter().all(|bar| !bar.delisted));
+    }
+}
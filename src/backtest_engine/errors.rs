@@ -0,0 +1,60 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+pub(super) const ON_ERROR_NAMES: [&str; 3] = ["hold", "raise", "collect"];
+
+/// What a run does when the strategy raises.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum OnError {
+    /// The bar holds and counts as a strategy error
+    Hold,
+    /// The exception ends the run
+    Raise,
+    /// The exception ends the ticker, which is listed under `errors`; other tickers go on
+    Collect,
+}
+
+impl OnError {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "hold" => Ok(OnError::Hold),
+            "raise" => Ok(OnError::Raise),
+            "collect" => Ok(OnError::Collect),
+            other => Err(PyValueError::new_err(format!(
+                "unknown on_error '{}', expected one of {}", other, ON_ERROR_NAMES.join(", ")
+            ))),
+        }
+    }
+
+    /// Whether strategy exceptions leave `step_signal` / `batch_signals` instead of holding.
+    pub fn propagates(self) -> bool {
+        self != OnError::Hold
+    }
+}
+
+/// The exception that stopped one ticker under "collect".
+#[derive(Debug, Clone)]
+pub(super) struct TickerError {
+    kind: String,
+    message: String,
+    traceback: Option<String>,
+}
+
+impl TickerError {
+    pub fn new(py: Python<'_>, err: &PyErr) -> Self {
+        TickerError {
+            kind: err.get_type(py).name().map_or_else(|_| "Exception".to_string(), str::to_string),
+            message: err.value(py).to_string(),
+            traceback: err.traceback(py).and_then(|tb| tb.format().ok()),
+        }
+    }
+
+    pub fn to_py<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let d = PyDict::new(py);
+        d.set_item("type", &self.kind)?;
+        d.set_item("message", &self.message)?;
+        d.set_item("traceback", &self.traceback)?;
+        Ok(d)
+    }
+}
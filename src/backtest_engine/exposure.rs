@@ -0,0 +1,84 @@
+use numpy::PyArray1;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::stats::mean;
+
+/// Long and short market value of a book on every bar, as percentages of its equity.
+pub(super) struct Exposure {
+    long: Vec<f64>,
+    short: Vec<f64>,
+}
+
+impl Exposure {
+    /// From the signed market value of each position (negative for shorts) on every bar.
+    pub fn new<'a>(positions: impl Iterator<Item = &'a [f64]>, equity: &[f64]) -> Self {
+        let mut long = vec![0.0; equity.len()];
+        let mut short = vec![0.0; equity.len()];
+        for values in positions {
+            for (t, v) in values.iter().enumerate() {
+                if *v > 0.0 { long[t] += v } else { short[t] -= v }
+            }
+        }
+        for (t, e) in equity.iter().enumerate() {
+            let scale = if *e > 0.0 { 100.0 / e } else { 0.0 };
+            long[t] *= scale;
+            short[t] *= scale;
+        }
+        Exposure { long, short }
+    }
+
+    fn gross(&self) -> Vec<f64> {
+        self.long.iter().zip(&self.short).map(|(l, s)| l + s).collect()
+    }
+
+    fn net(&self) -> Vec<f64> {
+        self.long.iter().zip(&self.short).map(|(l, s)| l - s).collect()
+    }
+
+    /// Per-bar `gross`, `net`, `long` and `short` exposure arrays.
+    pub fn series_to_py<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let d = PyDict::new(py);
+        d.set_item("gross", PyArray1::from_vec(py, self.gross()))?;
+        d.set_item("net", PyArray1::from_vec(py, self.net()))?;
+        d.set_item("long", PyArray1::from_slice(py, &self.long))?;
+        d.set_item("short", PyArray1::from_slice(py, &self.short))?;
+        Ok(d)
+    }
+
+    /// Average and extreme exposures, written into `summary`.
+    pub fn summarize(&self, summary: &PyDict) -> PyResult<()> {
+        let (gross, net) = (self.gross(), self.net());
+        let max = |v: &[f64]| v.iter().copied().fold(0.0, f64::max);
+        let min = |v: &[f64]| v.iter().copied().fold(0.0, f64::min);
+        summary.set_item("average_gross_exposure_pct", mean(&gross))?;
+        summary.set_item("max_gross_exposure_pct", max(&gross))?;
+        summary.set_item("average_net_exposure_pct", mean(&net))?;
+        summary.set_item("min_net_exposure_pct", min(&net))?;
+        summary.set_item("max_net_exposure_pct", max(&net))?;
+        summary.set_item("average_long_exposure_pct", mean(&self.long))?;
+        summary.set_item("average_short_exposure_pct", mean(&self.short))?;
+        Ok(())
+    }
+}
+
+/// Signed position values of each ticker on the portfolio's dates; a ticker holds nothing
+/// before its first bar and keeps its last value after its last one, as its equity does in
+/// the combined curve.
+pub(super) fn align_positions(all_dates: &[String], curves: &[(Vec<String>, Vec<f64>)], positions: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    curves.iter().zip(positions)
+        .map(|((dates, _), values)| {
+            let mut k = 0;
+            let mut last = 0.0;
+            all_dates.iter()
+                .map(|date| {
+                    while k < dates.len() && dates[k] <= *date {
+                        last = values.get(k).copied().unwrap_or(0.0);
+                        k += 1;
+                    }
+                    last
+                })
+                .collect()
+        })
+        .collect()
+}
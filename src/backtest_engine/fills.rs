@@ -0,0 +1,59 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use super::Bar;
+
+pub(super) const FILL_MODEL_NAMES: [&str; 4] = ["close", "next_open", "vwap", "worst"];
+
+/// Price at which the engine fills an order placed for bar `i`. The strategy decides from the
+/// closes before bar `i`, so any price of bar `i` itself is available without look-ahead.
+pub(super) trait FillModel: Send + Sync {
+    fn buy_price(&self, bar: &Bar) -> f64;
+    fn sell_price(&self, bar: &Bar) -> f64;
+}
+
+/// Fill at the bar's close (the historical behaviour).
+struct CloseFill;
+
+impl FillModel for CloseFill {
+    fn buy_price(&self, bar: &Bar) -> f64 { bar.close }
+    fn sell_price(&self, bar: &Bar) -> f64 { bar.close }
+}
+
+/// Fill at the open following the last bar the strategy saw.
+struct NextOpenFill;
+
+impl FillModel for NextOpenFill {
+    fn buy_price(&self, bar: &Bar) -> f64 { bar.open }
+    fn sell_price(&self, bar: &Bar) -> f64 { bar.open }
+}
+
+/// Fill at the bar's typical price (high + low + close) / 3, the usual VWAP proxy without intrabar volume.
+struct BarVwapFill;
+
+impl FillModel for BarVwapFill {
+    fn buy_price(&self, bar: &Bar) -> f64 { (bar.high + bar.low + bar.close) / 3.0 }
+    fn sell_price(&self, bar: &Bar) -> f64 { (bar.high + bar.low + bar.close) / 3.0 }
+}
+
+/// Pessimistic fills: buy at the bar's high, sell at its low.
+struct WorstCaseFill;
+
+impl FillModel for WorstCaseFill {
+    fn buy_price(&self, bar: &Bar) -> f64 { bar.high }
+    fn sell_price(&self, bar: &Bar) -> f64 { bar.low }
+}
+
+pub(super) fn fill_model(name: &str) -> PyResult<Box<dyn FillModel>> {
+    Ok(match name {
+        "close" => Box::new(CloseFill),
+        "next_open" => Box::new(NextOpenFill),
+        "vwap" => Box::new(BarVwapFill),
+        "worst" => Box::new(WorstCaseFill),
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "fill_model must be one of {:?}, got '{}'", FILL_MODEL_NAMES, other
+            )))
+        }
+    })
+}
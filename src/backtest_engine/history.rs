@@ -0,0 +1,110 @@
+use numpy::PyArray1;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PySlice;
+
+/// Fixed-size look-back window over a stream of values: O(1) appends, and the newest values are
+/// always contiguous, so `window()` is a numpy view of the buffer rather than a copy. Memory is
+/// bounded by `capacity` however many values go through it, which suits live loops that feed
+/// one bar at a time.
+///
+/// The engine keeps the closes before each bar in one and hands `step` a copy, or the view
+/// itself under `set_history_view(True)`. Views are read-only and only valid until the next
+/// `append`, which overwrites the oldest value; copy one to keep it.
+#[pyclass]
+pub struct HistoryBuffer {
+    /// Every value is stored twice, `capacity` slots apart, so the newest `len` values always
+    /// end at `head + capacity` without wrapping
+    data: Py<PyArray1<f64>>,
+    capacity: usize,
+    /// Slot the next value goes to
+    head: usize,
+    len: usize,
+}
+
+impl HistoryBuffer {
+    pub(super) fn with_capacity(py: Python<'_>, capacity: usize) -> PyResult<Self> {
+        let data = PyArray1::<f64>::zeros(py, 2 * capacity, false);
+        // Only `push` writes, and views of a read-only array stay read-only.
+        data.getattr("flags")?.setattr("writeable", false)?;
+        Ok(HistoryBuffer { data: data.into(), capacity, head: 0, len: 0 })
+    }
+
+    pub(super) fn push(&mut self, py: Python<'_>, value: f64) {
+        if self.capacity == 0 {
+            return;
+        }
+        // SAFETY: the buffer is a contiguous array owned by this struct and the GIL is held, so
+        // no Python code reads it during the write.
+        let slots = unsafe { self.data.as_ref(py).as_slice_mut() }.expect("history buffer is contiguous");
+        slots[self.head] = value;
+        slots[self.head + self.capacity] = value;
+        self.head = (self.head + 1) % self.capacity;
+        self.len = (self.len + 1).min(self.capacity);
+    }
+
+    /// The stored values, oldest first.
+    pub(super) fn as_slice<'a>(&'a self, py: Python<'a>) -> &'a [f64] {
+        let end = self.head + self.capacity;
+        // SAFETY: as in `push`; the returned slice borrows `self`, so no push can overlap it.
+        let slots = unsafe { self.data.as_ref(py).as_slice() }.expect("history buffer is contiguous");
+        &slots[end - self.len..end]
+    }
+
+    /// Numpy view of the stored values, oldest first.
+    pub(super) fn view<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        let end = (self.head + self.capacity) as isize;
+        self.data.clone_ref(py).into_ref(py).get_item(PySlice::new(py, end - self.len as isize, end, 1))
+    }
+}
+
+#[pymethods]
+impl HistoryBuffer {
+    #[new]
+    fn new(py: Python<'_>, capacity: usize) -> PyResult<Self> {
+        if capacity == 0 {
+            return Err(PyValueError::new_err("capacity must be at least 1"));
+        }
+        HistoryBuffer::with_capacity(py, capacity)
+    }
+
+    /// Appends one value, dropping the oldest once the buffer is full.
+    fn append(&mut self, py: Python<'_>, value: f64) {
+        self.push(py, value);
+    }
+
+    fn extend(&mut self, py: Python<'_>, values: Vec<f64>) {
+        for v in values {
+            self.push(py, v);
+        }
+    }
+
+    /// Read-only numpy view of the last `len(self)` values, oldest first, valid until the
+    /// next append.
+    fn window<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        self.view(py)
+    }
+
+    fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    #[getter]
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[getter]
+    fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
+
+    fn __len__(&self) -> usize {
+        self.len
+    }
+
+    fn __repr__(&self) -> String {
+        format!("HistoryBuffer({}/{} values)", self.len, self.capacity)
+    }
+}
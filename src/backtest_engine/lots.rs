@@ -0,0 +1,139 @@
+use numpy::PyArray1;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Shares bought in one fill, with their total cost including commission.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Lot {
+    pub date: String,
+    pub shares: f64,
+    pub cost: f64,
+}
+
+/// The part of a lot disposed of by one sale.
+#[derive(Debug, Clone)]
+pub(super) struct ClosedLot {
+    pub entry_date: String,
+    pub exit_date: String,
+    pub shares: f64,
+    pub cost: f64,
+    pub proceeds: f64,
+}
+
+impl ClosedLot {
+    pub fn pnl(&self) -> f64 {
+        self.proceeds - self.cost
+    }
+}
+
+/// Open lots of one ticker, disposed of first-in first-out.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(super) struct TaxLots {
+    lots: VecDeque<Lot>,
+}
+
+impl TaxLots {
+    pub fn buy(&mut self, date: &str, shares: f64, cost: f64) {
+        if shares > 0.0 {
+            self.lots.push_back(Lot { date: date.to_string(), shares, cost });
+        }
+    }
+
+    /// Sells `shares` for net `proceeds`, consuming the oldest lots first. Proceeds are split
+    /// across the lots in proportion to shares; a lot that is only partly sold keeps its
+    /// remaining shares at their original cost per share.
+    pub fn sell(&mut self, date: &str, shares: f64, proceeds: f64) -> Vec<ClosedLot> {
+        let mut closed = Vec::new();
+        if shares <= 0.0 {
+            return closed;
+        }
+        let proceeds_per_share = proceeds / shares;
+        let mut remaining = shares;
+        while remaining > 0.0 {
+            let Some(lot) = self.lots.front_mut() else { break };
+            let take = remaining.min(lot.shares);
+            let cost = lot.cost * take / lot.shares;
+            closed.push(ClosedLot {
+                entry_date: lot.date.clone(),
+                exit_date: date.to_string(),
+                shares: take,
+                cost,
+                proceeds: proceeds_per_share * take,
+            });
+            lot.shares -= take;
+            lot.cost -= cost;
+            remaining -= take;
+            // Treat float dust as fully sold so a lot is never left with ~0 shares.
+            if lot.shares <= f64::EPSILON * take.max(1.0) {
+                self.lots.pop_front();
+            }
+        }
+        closed
+    }
+
+    pub fn cost_basis(&self) -> f64 {
+        self.lots.iter().map(|l| l.cost).sum()
+    }
+}
+
+/// Closed tax lots as a dict of column arrays.
+pub(super) fn closed_lots_to_py<'py>(py: Python<'py>, lots: &[ClosedLot]) -> PyResult<&'py PyDict> {
+    let out = PyDict::new(py);
+    out.set_item("entry_date", lots.iter().map(|l| l.entry_date.clone()).collect::<Vec<_>>())?;
+    out.set_item("exit_date", lots.iter().map(|l| l.exit_date.clone()).collect::<Vec<_>>())?;
+    out.set_item("shares", PyArray1::from_vec(py, lots.iter().map(|l| l.shares).collect()))?;
+    out.set_item("cost", PyArray1::from_vec(py, lots.iter().map(|l| l.cost).collect()))?;
+    out.set_item("proceeds", PyArray1::from_vec(py, lots.iter().map(|l| l.proceeds).collect()))?;
+    out.set_item("pnl", PyArray1::from_vec(py, lots.iter().map(|l| l.pnl()).collect()))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sells_oldest_lots_first() {
+        let mut lots = TaxLots::default();
+        lots.buy("d1", 10.0, 100.0);
+        lots.buy("d2", 10.0, 200.0);
+        let closed = lots.sell("d3", 15.0, 450.0);
+        assert_eq!(closed.len(), 2);
+        assert_eq!((closed[0].entry_date.as_str(), closed[0].shares, closed[0].cost), ("d1", 10.0, 100.0));
+        assert_eq!((closed[1].entry_date.as_str(), closed[1].shares, closed[1].cost), ("d2", 5.0, 100.0));
+        assert_eq!(closed[0].proceeds + closed[1].proceeds, 450.0);
+        assert_eq!(closed[0].pnl(), 200.0);
+        assert_eq!(closed[1].pnl(), 50.0);
+    }
+
+    #[test]
+    fn partly_sold_lot_keeps_its_cost_per_share() {
+        let mut lots = TaxLots::default();
+        lots.buy("d1", 10.0, 100.0);
+        lots.sell("d2", 4.0, 60.0);
+        assert_eq!(lots.cost_basis(), 60.0);
+        let closed = lots.sell("d3", 6.0, 90.0);
+        assert_eq!((closed.len(), closed[0].cost), (1, 60.0));
+        assert_eq!(lots.cost_basis(), 0.0);
+    }
+
+    #[test]
+    fn float_dust_does_not_leave_an_empty_lot() {
+        let mut lots = TaxLots::default();
+        lots.buy("d1", 0.3, 3.0);
+        lots.sell("d2", 0.1, 1.0);
+        lots.sell("d3", 0.2, 2.0);
+        assert!(lots.lots.is_empty());
+    }
+
+    #[test]
+    fn empty_trades_are_ignored() {
+        let mut lots = TaxLots::default();
+        lots.buy("d1", 0.0, 0.0);
+        assert!(lots.sell("d2", 0.0, 0.0).is_empty());
+        assert!(lots.sell("d2", 5.0, 50.0).is_empty());
+        assert_eq!(lots.cost_basis(), 0.0);
+    }
+}
@@ -0,0 +1,330 @@
+use ndarray::Array2;
+use numpy::{PyArray1, PyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use super::state::EngineState;
+use super::{combine_equity_curves, BacktestEngine, DetailSinks, RunOutput, INITIAL_CAPITAL_PER_STOCK};
+use crate::cv::purged_kfold;
+use crate::stats::{max_drawdown, mean};
+use std::ops::Range;
+
+pub(super) const OBJECTIVE_NAMES: [&str; 5] = ["sharpe", "roi_pct", "max_drawdown_pct", "average_sharpe", "win_rate_pct"];
+
+/// Portfolio-level figure a parameter search ranks trials by.
+#[derive(Clone, Copy)]
+pub(super) enum Objective {
+    /// Sharpe of the combined equity curve
+    Sharpe,
+    RoiPct,
+    MaxDrawdownPct,
+    /// Mean of the per-ticker Sharpe ratios
+    AverageSharpe,
+    WinRatePct,
+}
+
+impl Objective {
+    pub fn parse(name: Option<&str>) -> PyResult<Self> {
+        Ok(match name.unwrap_or("sharpe") {
+            "sharpe" => Objective::Sharpe,
+            "roi_pct" => Objective::RoiPct,
+            "max_drawdown_pct" => Objective::MaxDrawdownPct,
+            "average_sharpe" => Objective::AverageSharpe,
+            "win_rate_pct" => Objective::WinRatePct,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "metric must be one of {:?}, got '{}'", OBJECTIVE_NAMES, other
+                )))
+            }
+        })
+    }
+
+    /// Drawdown is the only objective where lower is better by default.
+    pub fn maximize_by_default(self) -> bool {
+        !matches!(self, Objective::MaxDrawdownPct)
+    }
+
+    fn score(self, engine: &BacktestEngine, out: &RunOutput<'_>) -> f64 {
+        let n = out.metrics.len() as f64;
+        match self {
+            Objective::Sharpe => {
+                let (dates, equity) = combine_equity_curves(&out.equity_curves);
+                engine.sharpe(&dates, &equity)
+            }
+            Objective::RoiPct => {
+                let final_capital: f64 = out.metrics.iter().map(|m| m.final_balance).sum();
+                if n > 0.0 { (final_capital / (INITIAL_CAPITAL_PER_STOCK * n) - 1.0) * 100.0 } else { 0.0 }
+            }
+            Objective::MaxDrawdownPct => max_drawdown(&combine_equity_curves(&out.equity_curves).1) * 100.0,
+            Objective::AverageSharpe => {
+                if n > 0.0 { out.metrics.iter().map(|m| m.sharpe).sum::<f64>() / n } else { 0.0 }
+            }
+            Objective::WinRatePct => {
+                let trades: i32 = out.metrics.iter().map(|m| m.trades).sum();
+                let wins: i32 = out.metrics.iter().map(|m| m.wins).sum();
+                if trades > 0 { wins as f64 / trades as f64 * 100.0 } else { 0.0 }
+            }
+        }
+    }
+}
+
+/// Purged K-fold settings for scoring trials out of sample.
+pub(super) struct CrossValidation {
+    pub folds: usize,
+    pub purge: usize,
+    pub embargo: usize,
+}
+
+/// Scores of one trial over the full sample and, under cross-validation, per fold on its
+/// (train, test) bars.
+pub(super) struct Evaluation {
+    pub score: f64,
+    pub folds: Vec<(f64, f64)>,
+}
+
+/// Runs a fresh backtest over the engine's data folder with `factory(**params)` as the strategy
+/// and scores it. Folds are cut per ticker from its own simulated bars, so one run serves every
+/// fold.
+pub(super) fn evaluate(
+    engine: &BacktestEngine,
+    py: Python<'_>,
+    factory: &PyObject,
+    params: &PyDict,
+    objective: Objective,
+    cv: Option<&CrossValidation>,
+) -> PyResult<Evaluation> {
+    let strategy = factory.call(py, (), Some(params))?;
+    let out = engine.simulate(py, &strategy, &engine.data_folder, false, &EngineState::new(engine.history_size), DetailSinks::default())?;
+    let score = objective.score(engine, &out);
+    log::info!("Trial {} scored {}", params, score);
+
+    let folds = match cv {
+        Some(cv) => {
+            let segments = TickerSegments::new(&out)?;
+            (0..cv.folds)
+                .map(|f| {
+                    let fold = |n: usize| purged_kfold(n, cv.folds, cv.purge, cv.embargo).swap_remove(f);
+                    let train = segments.score(objective, engine, |n| fold(n).train);
+                    let test = segments.score(objective, engine, |n| vec![fold(n).test]);
+                    (train, test)
+                })
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    Ok(Evaluation { score, folds })
+}
+
+/// Per-ticker equity and closed-trade outcomes of one run, for scoring subsets of bars.
+struct TickerSegments {
+    dates: Vec<Vec<String>>,
+    equity: Vec<Vec<f64>>,
+    /// (exit bar, won) of every round trip
+    exits: Vec<Vec<(usize, bool)>>,
+}
+
+impl TickerSegments {
+    fn new(out: &RunOutput<'_>) -> PyResult<Self> {
+        let mut exits = Vec::with_capacity(out.metrics.len());
+        for metric in &out.metrics {
+            let trades: &PyDict = out.details.get_item(&metric.ticker)
+                .and_then(|d| d.downcast::<PyDict>().ok()?.get_item("trades"))
+                .ok_or_else(|| PyValueError::new_err(format!("no trade ledger for {}", metric.ticker)))?
+                .downcast()?;
+            let get = |key: &str| trades.get_item(key).ok_or_else(|| PyValueError::new_err(format!("trade ledger has no '{}'", key)));
+            let exit_index: Vec<usize> = get("exit_index")?.extract()?;
+            let outcome: Vec<String> = get("outcome")?.extract()?;
+            let closes: Vec<bool> = get("closes_position")?.extract()?;
+            exits.push(exit_index.into_iter().zip(outcome).zip(closes)
+                .filter(|(_, closes)| *closes)
+                .map(|((i, o), _)| (i, o == "win"))
+                .collect());
+        }
+        Ok(TickerSegments {
+            dates: out.equity_curves.iter().map(|(d, _)| d.clone()).collect(),
+            equity: out.equity_curves.iter().map(|(_, e)| e.clone()).collect(),
+            exits,
+        })
+    }
+
+    /// `objective` over the bars `ranges(n)` picks from each ticker's `n` bars: the per-ticker
+    /// figure from the returns ending on those bars averaged across tickers, or for the win rate
+    /// the share of round trips exiting on them.
+    fn score(&self, objective: Objective, engine: &BacktestEngine, ranges: impl Fn(usize) -> Vec<Range<usize>>) -> f64 {
+        let (mut trades, mut wins) = (0, 0);
+        let mut per_ticker = Vec::new();
+        for ((dates, equity), exits) in self.dates.iter().zip(&self.equity).zip(&self.exits) {
+            let ranges = ranges(equity.len());
+            let inside = |k: usize| ranges.iter().any(|r| r.contains(&k));
+            for &(exit, won) in exits {
+                if inside(exit) {
+                    trades += 1;
+                    if won { wins += 1; }
+                }
+            }
+            // Compound the returns ending on the picked bars into one curve.
+            let mut curve = vec![1.0];
+            let mut curve_dates = Vec::new();
+            for k in ranges.iter().flat_map(|r| r.clone()).filter(|&k| k > 0) {
+                let r = if equity[k - 1].abs() > f64::EPSILON { equity[k] / equity[k - 1] - 1.0 } else { 0.0 };
+                curve.push(curve[curve.len() - 1] * (1.0 + r));
+                curve_dates.push(dates[k].clone());
+            }
+            if curve.len() < 2 { continue; }
+            per_ticker.push(match objective {
+                Objective::Sharpe | Objective::AverageSharpe => engine.sharpe(&curve_dates, &curve),
+                Objective::RoiPct => (curve[curve.len() - 1] - 1.0) * 100.0,
+                Objective::MaxDrawdownPct => max_drawdown(&curve) * 100.0,
+                Objective::WinRatePct => 0.0,
+            });
+        }
+        match objective {
+            Objective::WinRatePct => if trades > 0 { wins as f64 / trades as f64 * 100.0 } else { 0.0 },
+            _ => if per_ticker.is_empty() { f64::NAN } else { mean(&per_ticker) },
+        }
+    }
+}
+
+pub(super) fn grid_search(
+    engine: &BacktestEngine,
+    py: Python<'_>,
+    factory: PyObject,
+    grid: &PyDict,
+    objective: Objective,
+    maximize: bool,
+    cv: Option<CrossValidation>,
+) -> PyResult<PyObject> {
+    let names: Vec<String> = grid.keys().extract()?;
+    let axes: Vec<&PyList> = grid.values().iter()
+        .map(|v| Ok(PyList::new(py, v.iter()?.collect::<PyResult<Vec<_>>>()?)))
+        .collect::<PyResult<_>>()?;
+    if names.is_empty() || axes.iter().any(|a| a.is_empty()) {
+        return Err(PyValueError::new_err("grid must map at least one parameter to a non-empty list of values"));
+    }
+
+    // Odometer over the axes, last parameter fastest, so the scores fill a row-major array.
+    let shape: Vec<usize> = axes.iter().map(|a| a.len()).collect();
+    let total: usize = shape.iter().product();
+    let mut position = vec![0; names.len()];
+    let trials = PyList::empty(py);
+    let mut scores = Vec::with_capacity(total);
+    let mut fold_scores: Vec<Vec<(f64, f64)>> = Vec::with_capacity(total);
+    for _ in 0..total {
+        let params = PyDict::new(py);
+        for (k, name) in names.iter().enumerate() {
+            params.set_item(name, axes[k].get_item(position[k])?)?;
+        }
+        let evaluation = evaluate(engine, py, &factory, params, objective, cv.as_ref())?;
+        scores.push(evaluation.score);
+        fold_scores.push(evaluation.folds);
+        trials.append(params)?;
+
+        for k in (0..position.len()).rev() {
+            position[k] += 1;
+            if position[k] < shape[k] { break; }
+            position[k] = 0;
+        }
+    }
+
+    // Under cross-validation trials rank by their mean test-fold score.
+    let full_sample = scores.clone();
+    if cv.is_some() {
+        scores = fold_scores.iter().map(|f| mean(&f.iter().map(|s| s.1).collect())).collect();
+    }
+    let out = search_result(py, trials, &scores, objective, maximize)?;
+    if let Some(cv) = &cv {
+        out.set_item("cross_validation", cv_result(py, cv, trials, &fold_scores, &full_sample, maximize)?)?;
+    }
+    if names.len() == 2 {
+        let heatmap = PyDict::new(py);
+        heatmap.set_item("rows_param", &names[0])?;
+        heatmap.set_item("rows", axes[0])?;
+        heatmap.set_item("cols_param", &names[1])?;
+        heatmap.set_item("cols", axes[1])?;
+        let values = Array2::from_shape_vec((shape[0], shape[1]), scores)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        heatmap.set_item("values", PyArray2::from_owned_array(py, values))?;
+        out.set_item("heatmap", heatmap)?;
+    }
+    Ok(out.into())
+}
+
+/// Fold-by-fold detail: the test scores of every trial, and for each fold the trial that scored
+/// best on its training bars together with how it then did on the held-out block.
+fn cv_result<'py>(
+    py: Python<'py>,
+    cv: &CrossValidation,
+    trials: &'py PyList,
+    fold_scores: &[Vec<(f64, f64)>],
+    full_sample: &[f64],
+    maximize: bool,
+) -> PyResult<&'py PyDict> {
+    let better = |a: f64, b: f64| if maximize { a > b } else { a < b };
+    let test_scores = Array2::from_shape_fn((fold_scores.len(), cv.folds), |(t, f)| fold_scores[t][f].1);
+    let folds = PyList::empty(py);
+    let mut selected_test = Vec::with_capacity(cv.folds);
+    for f in 0..cv.folds {
+        let chosen = (0..fold_scores.len())
+            .filter(|&t| !fold_scores[t][f].0.is_nan())
+            .reduce(|best, t| if better(fold_scores[t][f].0, fold_scores[best][f].0) { t } else { best });
+        let fold = PyDict::new(py);
+        fold.set_item("fold", f)?;
+        match chosen {
+            Some(t) => {
+                fold.set_item("selected_params", trials.get_item(t)?)?;
+                fold.set_item("train_score", fold_scores[t][f].0)?;
+                fold.set_item("test_score", fold_scores[t][f].1)?;
+                selected_test.push(fold_scores[t][f].1);
+            }
+            None => {
+                fold.set_item("selected_params", py.None())?;
+                fold.set_item("train_score", f64::NAN)?;
+                fold.set_item("test_score", f64::NAN)?;
+            }
+        }
+        folds.append(fold)?;
+    }
+
+    let out = PyDict::new(py);
+    out.set_item("k", cv.folds)?;
+    out.set_item("purge", cv.purge)?;
+    out.set_item("embargo", cv.embargo)?;
+    out.set_item("test_scores", PyArray2::from_owned_array(py, test_scores))?;
+    out.set_item("full_sample_scores", PyArray1::from_slice(py, full_sample))?;
+    out.set_item("folds", folds)?;
+    // Out-of-sample estimate of the whole select-then-trade procedure.
+    out.set_item("selected_test_score", if selected_test.is_empty() { f64::NAN } else { mean(&selected_test) })?;
+    Ok(out)
+}
+
+/// Trials, their scores and the best one, shared by every search.
+pub(super) fn search_result<'py>(
+    py: Python<'py>,
+    trials: &'py PyList,
+    scores: &[f64],
+    objective: Objective,
+    maximize: bool,
+) -> PyResult<&'py PyDict> {
+    let better = |a: f64, b: f64| if maximize { a > b } else { a < b };
+    let best = (0..scores.len())
+        .filter(|&k| !scores[k].is_nan())
+        .reduce(|best, k| if better(scores[k], scores[best]) { k } else { best });
+
+    let out = PyDict::new(py);
+    out.set_item("metric", OBJECTIVE_NAMES[objective as usize])?;
+    out.set_item("maximize", maximize)?;
+    out.set_item("params", trials)?;
+    out.set_item("scores", PyArray1::from_slice(py, scores))?;
+    match best {
+        Some(k) => {
+            out.set_item("best_params", trials.get_item(k)?)?;
+            out.set_item("best_score", scores[k])?;
+        }
+        None => {
+            out.set_item("best_params", py.None())?;
+            out.set_item("best_score", f64::NAN)?;
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,102 @@
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use numpy::PyArray1;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::timestamps;
+
+/// Every order the engine sent for one ticker: executed fills (partial ones when the volume
+/// limit cut them), and orders a broker would refuse because they size to nothing, fall below
+/// the minimum notional or exceed the volume limit. Signals held back by entry rules or
+/// filters never become orders and are listed in `overridden_signals`.
+#[derive(Default)]
+pub(super) struct OrderLog {
+    index: Vec<usize>,
+    date: Vec<String>,
+    side: Vec<&'static str>,
+    quantity: Vec<f64>,
+    price: Vec<f64>,
+    status: Vec<&'static str>,
+}
+
+impl OrderLog {
+    fn push(&mut self, index: usize, date: &str, side: &'static str, quantity: f64, price: f64, status: &'static str) {
+        self.index.push(index);
+        self.date.push(date.to_string());
+        self.side.push(side);
+        self.quantity.push(quantity);
+        self.price.push(price);
+        self.status.push(status);
+    }
+
+    /// A fill of `quantity` shares out of `requested`; partial when the volume limit left
+    /// some out.
+    pub fn filled(&mut self, index: usize, date: &str, side: &'static str, quantity: f64, requested: f64, price: f64) {
+        let status = if quantity < requested * (1.0 - 1e-9) { "PARTIALLY_FILLED" } else { "FILLED" };
+        self.push(index, date, side, quantity, price, status);
+    }
+
+    pub fn rejected(&mut self, index: usize, date: &str, side: &'static str, quantity: f64, price: f64) {
+        self.push(index, date, side, quantity, price, "REJECTED");
+    }
+
+    pub fn into_py(self, py: Python<'_>) -> PyResult<&PyDict> {
+        let d = PyDict::new(py);
+        d.set_item("index", PyArray1::from_vec(py, self.index))?;
+        d.set_item("date", self.date)?;
+        d.set_item("side", self.side)?;
+        d.set_item("quantity", PyArray1::from_vec(py, self.quantity))?;
+        d.set_item("price", PyArray1::from_vec(py, self.price))?;
+        d.set_item("status", self.status)?;
+        Ok(d)
+    }
+}
+
+/// FIX UTCTimestamp ("YYYYMMDD-HH:MM:SS") for intraday bars, read in `tz` when naive, or
+/// LocalMktDate ("YYYYMMDD") for date-only bars.
+fn fix_timestamp(date: &str, tz: Tz) -> String {
+    if let Some(t) = timestamps::parse_instant(date, tz) {
+        return t.format("%Y%m%d-%H:%M:%S").to_string();
+    }
+    match date.get(..10).and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()) {
+        Some(d) => d.format("%Y%m%d").to_string(),
+        None => date.to_string(),
+    }
+}
+
+/// Writes the `orders` of every ticker in a run's `details` to `path` as one CSV in time
+/// order, with `timestamp,symbol,side,qty,price,order_type,status` columns. Returns the
+/// number of rows written.
+pub(super) fn export(results: &PyAny, path: &str, tz: Tz) -> PyResult<usize> {
+    let details = results.get_item("details").map_err(|_| PyValueError::new_err("results have no 'details'"))?;
+    let mut rows: Vec<(String, String, usize, String, f64, f64, String)> = Vec::new();
+    for ticker in details.call_method0("keys")?.iter()? {
+        let ticker: String = ticker?.extract()?;
+        let orders = details.get_item(&ticker)?.get_item("orders")
+            .map_err(|_| PyValueError::new_err(format!("details for {} have no 'orders'", ticker)))?;
+        let column = |key: &str| orders.get_item(key);
+        let dates: Vec<String> = column("date")?.extract()?;
+        let index: Vec<usize> = column("index")?.extract()?;
+        let side: Vec<String> = column("side")?.extract()?;
+        let quantity: Vec<f64> = column("quantity")?.extract()?;
+        let price: Vec<f64> = column("price")?.extract()?;
+        let status: Vec<String> = column("status")?.extract()?;
+        for (k, date) in dates.into_iter().enumerate() {
+            rows.push((fix_timestamp(&date, tz), ticker.clone(), index[k], side[k].clone(), quantity[k], price[k], status[k].clone()));
+        }
+    }
+    rows.sort_by(|a, b| (&a.0, &a.1, a.2).cmp(&(&b.0, &b.1, b.2)));
+
+    let io_err = |e: std::io::Error| PyIOError::new_err(format!("writing {}: {}", path, e));
+    let mut out = BufWriter::new(File::create(path).map_err(io_err)?);
+    writeln!(out, "timestamp,symbol,side,qty,price,order_type,status").map_err(io_err)?;
+    for (timestamp, symbol, _, side, qty, price, status) in &rows {
+        writeln!(out, "{},{},{},{},{},MARKET,{}", timestamp, symbol, side, qty, price, status).map_err(io_err)?;
+    }
+    out.flush().map_err(io_err)?;
+    Ok(rows.len())
+}
@@ -0,0 +1,54 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use super::state::TickerState;
+
+/// Equity-curve trading: the strategy's own signals are followed on a shadow curve (fully
+/// invested while long, no costs), and while that curve is below its moving average new
+/// entries are scaled by `scale` (0 pauses them). The shadow curve keeps following the
+/// signals while real trading is paused, so the overlay notices when the strategy recovers.
+pub(super) struct EquityOverlay {
+    window: usize,
+    scale: f64,
+}
+
+impl EquityOverlay {
+    pub fn new(window: usize, scale: f64) -> PyResult<Self> {
+        if window < 2 {
+            return Err(PyValueError::new_err("equity overlay window must be at least 2 bars"));
+        }
+        if !(0.0..=1.0).contains(&scale) {
+            return Err(PyValueError::new_err(format!("equity overlay scale must be in [0, 1], got {}", scale)));
+        }
+        Ok(EquityOverlay { window, scale })
+    }
+
+    /// Marks the shadow curve to `close` and returns it with the exposure scale for entries at
+    /// this bar, then applies the strategy's raw `signal` to the shadow position. Until the
+    /// curve has `window` points the scale is 1.
+    pub fn step(&self, st: &mut TickerState, prev_close: Option<f64>, close: f64, signal: i32) -> (f64, f64) {
+        let last = st.overlay_equity.last().copied().unwrap_or(1.0);
+        let equity = match prev_close {
+            Some(prev) if st.overlay_in_position && prev > 0.0 => last * close / prev,
+            _ => last,
+        };
+        st.overlay_equity.push(equity);
+        if st.overlay_equity.len() > self.window {
+            st.overlay_equity.remove(0);
+        }
+
+        let scale = if st.overlay_equity.len() < self.window {
+            1.0
+        } else {
+            let average = st.overlay_equity.iter().sum::<f64>() / self.window as f64;
+            if equity < average { self.scale } else { 1.0 }
+        };
+
+        match signal {
+            1 => st.overlay_in_position = true,
+            -1 => st.overlay_in_position = false,
+            _ => {}
+        }
+        (equity, scale)
+    }
+}
@@ -0,0 +1,331 @@
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+use std::collections::HashMap;
+
+use super::exposure::Exposure;
+use super::{BacktestEngine, Bar, TradeOutcome, WinBasis, INITIAL_CAPITAL_PER_STOCK};
+use crate::stats::max_drawdown;
+
+pub(super) enum HedgeRatio {
+    Fixed(f64),
+    /// OLS beta of leg A on leg B over the trailing window, re-estimated every bar.
+    RollingOls(usize),
+}
+
+/// Inner-joins two bar series on date, keeping the ordering of `a`: the bars of `a` and the
+/// closes of `b` on the shared dates.
+fn align(a: &[Bar], b: &[Bar]) -> (Vec<Bar>, Vec<f64>) {
+    let b_by_date: HashMap<&str, f64> = b.iter().map(|bar| (bar.date.as_str(), bar.close)).collect();
+    let mut a_bars = Vec::new();
+    let mut b_closes = Vec::new();
+    for bar in a {
+        if let Some(&b_close) = b_by_date.get(bar.date.as_str()) {
+            a_bars.push(bar.clone());
+            b_closes.push(b_close);
+        }
+    }
+    (a_bars, b_closes)
+}
+
+/// Least-squares slope of `a` regressed on `b`. Falls back to 1.0 when `b` has no variance.
+pub(super) fn ols_hedge_ratio(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    if n < 2.0 { return 1.0; }
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+    let mut cov = 0.0;
+    let mut var_b = 0.0;
+    for (x, y) in a.iter().zip(b.iter()) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_b += (y - mean_b).powi(2);
+    }
+    if var_b > f64::EPSILON { cov / var_b } else { 1.0 }
+}
+
+/// Open spread position. Leg quantities are fixed at entry, both as share counts; `direction`
+/// is 1 for a long spread and -1 for a short one. A long spread is long A and short B when the
+/// hedge ratio is positive. A negative hedge ratio means the legs move against each other, so
+/// the spread `A - beta * B` holds both legs on the same side: a long spread is long A and
+/// long B. `hedge_sign` keeps the sign of the hedge ratio at entry.
+struct SpreadPosition {
+    direction: f64,
+    hedge_sign: f64,
+    qty_a: f64,
+    qty_b: f64,
+    entry_a: f64,
+    entry_b: f64,
+    entry_commission: f64,
+}
+
+impl SpreadPosition {
+    /// 1 when the B leg is long, -1 when it is short.
+    fn side_b(&self) -> f64 {
+        -self.direction * self.hedge_sign
+    }
+
+    fn leg_pnl(&self, price_a: f64, price_b: f64) -> (f64, f64) {
+        (
+            self.direction * self.qty_a * (price_a - self.entry_a),
+            self.side_b() * self.qty_b * (price_b - self.entry_b),
+        )
+    }
+
+    /// Signed market value of each leg.
+    fn leg_values(&self, price_a: f64, price_b: f64) -> (f64, f64) {
+        (self.direction * self.qty_a * price_a, self.side_b() * self.qty_b * price_b)
+    }
+
+    /// Commission on each leg's traded notional at the given prices.
+    fn leg_commissions(&self, price_a: f64, price_b: f64, rate: f64) -> (f64, f64) {
+        (self.qty_a * price_a * rate, self.qty_b * price_b * rate)
+    }
+}
+
+pub(super) fn run_pair(
+    engine: &BacktestEngine,
+    py: Python<'_>,
+    ticker_a: &str,
+    ticker_b: &str,
+    hedge: HedgeRatio,
+) -> PyResult<PyObject> {
+    let history_size = engine.history_size;
+    let load = |ticker: &str| {
+        let path = format!("{}/{}_meso.csv", engine.data_folder, ticker);
+        engine.load_bars(&path, ticker).map_err(|e| PyIOError::new_err(format!("failed to read {}: {}", path, e)))
+    };
+    let bars_a = load(ticker_a)?;
+    let bars_b = load(ticker_b)?;
+    let (bars_a, prices_b) = align(&bars_a, &bars_b);
+    let dates: Vec<String> = bars_a.iter().map(|b| b.date.clone()).collect();
+    let prices_a: Vec<f64> = bars_a.iter().map(|b| b.close).collect();
+
+    if dates.len() <= history_size + 1 {
+        return Err(PyValueError::new_err(format!(
+            "{} and {} share only {} dates, need more than history_size + 1 = {}",
+            ticker_a, ticker_b, dates.len(), history_size + 1
+        )));
+    }
+
+    // Patterns and regime labels follow leg A, whose bars the spread is aligned to.
+    let pair = format!("{}/{}", ticker_a, ticker_b);
+    let pattern_signals = BacktestEngine::pattern_signals(&BacktestEngine::subscribed_patterns(py, &engine.strategy), &bars_a);
+
+    // --- Simulation State ---
+    let mut cash = INITIAL_CAPITAL_PER_STOCK;
+    let mut position: Option<SpreadPosition> = None;
+    let mut trades = 0;
+    let mut wins = 0;
+    let mut gross_wins = 0;
+    let mut net_wins = 0;
+    let mut breakevens = 0;
+    let mut total_commission = 0.0;
+    let mut strategy_errors = 0;
+    let mut realized_a = 0.0;
+    let mut realized_b = 0.0;
+
+    let n_out = dates.len() - history_size;
+    let mut out_dates: Vec<String> = Vec::with_capacity(n_out);
+    let mut spreads: Vec<f64> = Vec::with_capacity(n_out);
+    let mut hedge_ratios: Vec<f64> = Vec::with_capacity(n_out);
+    let mut signals: Vec<i32> = Vec::with_capacity(n_out);
+    let mut positions: Vec<i32> = Vec::with_capacity(n_out);
+    let mut leg_a_pnl: Vec<f64> = Vec::with_capacity(n_out);
+    let mut leg_b_pnl: Vec<f64> = Vec::with_capacity(n_out);
+    let mut balance_history: Vec<f64> = Vec::with_capacity(n_out);
+    let mut leg_a_value: Vec<f64> = Vec::with_capacity(n_out);
+    let mut leg_b_value: Vec<f64> = Vec::with_capacity(n_out);
+
+    for i in history_size..dates.len() {
+        let (price_a, price_b) = (prices_a[i], prices_b[i]);
+
+        let beta = match hedge {
+            HedgeRatio::Fixed(h) => h,
+            HedgeRatio::RollingOls(window) => {
+                let start = i.saturating_sub(window);
+                ols_hedge_ratio(&prices_a[start..i], &prices_b[start..i])
+            }
+        };
+
+        let history_slice: Vec<f64> = (i - history_size..i).map(|j| prices_a[j] - beta * prices_b[j]).collect();
+        let py_history = PyArray1::from_slice(py, &history_slice);
+        let crr_pos_int = position.as_ref().map_or(0, |p| p.direction as i32);
+
+        // Spread positions open and close whole, so only int signals apply; anything else
+        // holds and counts as a strategy error, as does a raising strategy under "hold".
+        let signal = match engine.call_step(py, &engine.strategy, &pair, &bars_a, &pattern_signals, i, py_history, crr_pos_int)? {
+            Some(order) if order.target.is_none() => order.side,
+            _ => {
+                strategy_errors += 1;
+                0
+            }
+        };
+
+        // 1 opens a long spread or closes a short one; -1 opens a short spread or closes a long one.
+        match position.take() {
+            Some(open) if signal != 0 && signal as f64 == -open.direction => {
+                let (pnl_a, pnl_b) = open.leg_pnl(price_a, price_b);
+                let (fee_a, fee_b) = open.leg_commissions(price_a, price_b, engine.commission_rate);
+                realized_a += pnl_a - fee_a;
+                realized_b += pnl_b - fee_b;
+                cash += pnl_a + pnl_b - fee_a - fee_b;
+                total_commission += fee_a + fee_b;
+
+                let gross = pnl_a + pnl_b;
+                let net = gross - open.entry_commission - fee_a - fee_b;
+                let entry_gross = open.qty_a * open.entry_a + open.qty_b * open.entry_b;
+                let gross_outcome = TradeOutcome::classify(gross, entry_gross, engine.breakeven_pct);
+                let net_outcome = TradeOutcome::classify(net, entry_gross, engine.breakeven_pct);
+                if gross_outcome == TradeOutcome::Win { gross_wins += 1; }
+                if net_outcome == TradeOutcome::Win { net_wins += 1; }
+                let outcome = match engine.win_basis {
+                    WinBasis::Gross => gross_outcome,
+                    WinBasis::Net => net_outcome,
+                };
+                match outcome {
+                    TradeOutcome::Win => wins += 1,
+                    TradeOutcome::Breakeven => breakevens += 1,
+                    TradeOutcome::Loss => {}
+                }
+                trades += 1;
+            }
+            Some(open) => position = Some(open),
+            None if signal == 1 || signal == -1 => {
+                // Both legs' notional and the commission on it come out of the pair's cash.
+                let gross_per_unit = (price_a + beta.abs() * price_b) * (1.0 + engine.commission_rate);
+                if gross_per_unit > 0.0 {
+                    let qty_a = cash / gross_per_unit;
+                    let mut open = SpreadPosition {
+                        direction: signal as f64,
+                        hedge_sign: if beta < 0.0 { -1.0 } else { 1.0 },
+                        qty_a,
+                        qty_b: beta.abs() * qty_a,
+                        entry_a: price_a,
+                        entry_b: price_b,
+                        entry_commission: 0.0,
+                    };
+                    let (fee_a, fee_b) = open.leg_commissions(price_a, price_b, engine.commission_rate);
+                    open.entry_commission = fee_a + fee_b;
+                    realized_a -= fee_a;
+                    realized_b -= fee_b;
+                    cash -= fee_a + fee_b;
+                    total_commission += fee_a + fee_b;
+                    position = Some(open);
+                }
+            }
+            None => {}
+        }
+
+        let (open_a, open_b) = position.as_ref().map_or((0.0, 0.0), |p| p.leg_pnl(price_a, price_b));
+        let (value_a, value_b) = position.as_ref().map_or((0.0, 0.0), |p| p.leg_values(price_a, price_b));
+
+        out_dates.push(dates[i].clone());
+        spreads.push(price_a - beta * price_b);
+        hedge_ratios.push(beta);
+        signals.push(signal);
+        positions.push(position.as_ref().map_or(0, |p| p.direction as i32));
+        leg_a_pnl.push(realized_a + open_a);
+        leg_b_pnl.push(realized_b + open_b);
+        balance_history.push(cash + open_a + open_b);
+        leg_a_value.push(value_a);
+        leg_b_value.push(value_b);
+    }
+
+    let final_balance = *balance_history.last().unwrap_or(&cash);
+    let roi_pct = ((final_balance - INITIAL_CAPITAL_PER_STOCK) / INITIAL_CAPITAL_PER_STOCK) * 100.0;
+    let sharpe = engine.sharpe(&out_dates, &balance_history);
+    let max_dd = max_drawdown(&balance_history);
+
+    let py_metrics = PyDict::new(py);
+    py_metrics.set_item("pair", pair)?;
+    py_metrics.set_item("final_balance", final_balance)?;
+    py_metrics.set_item("trades", trades)?;
+    py_metrics.set_item("wins", wins)?;
+    py_metrics.set_item("gross_wins", gross_wins)?;
+    py_metrics.set_item("net_wins", net_wins)?;
+    py_metrics.set_item("breakevens", breakevens)?;
+    py_metrics.set_item("commission", total_commission)?;
+    py_metrics.set_item("strategy_errors", strategy_errors)?;
+    py_metrics.set_item("roi_pct", roi_pct)?;
+    py_metrics.set_item("sharpe", sharpe)?;
+    py_metrics.set_item("max_drawdown_pct", max_dd * 100.0)?;
+    py_metrics.set_item("n_periods", balance_history.len())?;
+    let exposure = Exposure::new([leg_a_value.as_slice(), leg_b_value.as_slice()].into_iter(), &balance_history);
+    exposure.summarize(py_metrics)?;
+
+    let details = PyDict::new(py);
+    details.set_item("dates", out_dates)?;
+    details.set_item("spread", PyArray1::from_vec(py, spreads))?;
+    details.set_item("hedge_ratio", PyArray1::from_vec(py, hedge_ratios))?;
+    details.set_item("signals", PyArray1::from_vec(py, signals))?;
+    details.set_item("positions", PyArray1::from_vec(py, positions))?;
+    details.set_item("leg_a_pnl", PyArray1::from_vec(py, leg_a_pnl))?;
+    details.set_item("leg_b_pnl", PyArray1::from_vec(py, leg_b_pnl))?;
+    details.set_item("exposure", exposure.series_to_py(py)?)?;
+    details.set_item("balance_history", PyArray1::from_vec(py, balance_history))?;
+
+    let py_out = PyDict::new(py);
+    py_out.set_item("metrics", py_metrics)?;
+    py_out.set_item("details", details)?;
+    Ok(py_out.to_object(py))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(direction: f64, beta: f64) -> SpreadPosition {
+        SpreadPosition {
+            direction,
+            hedge_sign: if beta < 0.0 { -1.0 } else { 1.0 },
+            qty_a: 10.0,
+            qty_b: beta.abs() * 10.0,
+            entry_a: 100.0,
+            entry_b: 50.0,
+            entry_commission: 0.0,
+        }
+    }
+
+    #[test]
+    fn ols_recovers_the_slope() {
+        let b: Vec<f64> = (0..20).map(|i| 50.0 + i as f64).collect();
+        let a: Vec<f64> = b.iter().map(|x| 3.0 + 1.5 * x).collect();
+        assert!((ols_hedge_ratio(&a, &b) - 1.5).abs() < 1e-12);
+        let inverse: Vec<f64> = b.iter().map(|x| 200.0 - 0.5 * x).collect();
+        assert!((ols_hedge_ratio(&inverse, &b) + 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn ols_falls_back_without_variance() {
+        assert_eq!(ols_hedge_ratio(&[1.0, 2.0, 3.0], &[5.0, 5.0, 5.0]), 1.0);
+        assert_eq!(ols_hedge_ratio(&[1.0], &[2.0]), 1.0);
+    }
+
+    #[test]
+    fn positive_hedge_ratio_shorts_b_in_a_long_spread() {
+        let p = position(1.0, 2.0);
+        assert_eq!(p.qty_b, 20.0);
+        assert_eq!(p.leg_values(100.0, 50.0), (1000.0, -1000.0));
+        assert_eq!(p.leg_pnl(101.0, 51.0), (10.0, -20.0));
+        let short = position(-1.0, 2.0);
+        assert_eq!(short.leg_values(100.0, 50.0), (-1000.0, 1000.0));
+    }
+
+    #[test]
+    fn negative_hedge_ratio_holds_both_legs_on_one_side() {
+        let p = position(1.0, -2.0);
+        assert_eq!(p.qty_b, 20.0);
+        assert_eq!(p.leg_values(100.0, 50.0), (1000.0, 1000.0));
+        assert_eq!(p.leg_pnl(101.0, 51.0), (10.0, 20.0));
+        let short = position(-1.0, -2.0);
+        assert_eq!(short.leg_values(100.0, 50.0), (-1000.0, -1000.0));
+    }
+
+    #[test]
+    fn commission_is_on_each_legs_notional() {
+        let p = position(-1.0, -2.0);
+        let (fee_a, fee_b) = p.leg_commissions(100.0, 50.0, 0.001);
+        assert!((fee_a - 1.0).abs() < 1e-12 && (fee_b - 1.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,213 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use super::symbols::SymbolSpec;
+
+pub(super) const VOLUME_REMAINDER_NAMES: [&str; 3] = ["carry", "partial", "reject"];
+
+/// What happens to the part of an order a bar's volume cannot absorb.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Remainder {
+    /// Fills on the following bars until done or cancelled by an opposite signal
+    Carry,
+    /// Is dropped; the order fills as far as the bar allows
+    Partial,
+    /// Sinks the whole order
+    Reject,
+}
+
+impl Remainder {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "carry" => Ok(Remainder::Carry),
+            "partial" => Ok(Remainder::Partial),
+            "reject" => Ok(Remainder::Reject),
+            other => Err(PyValueError::new_err(format!(
+                "unknown volume remainder '{}', expected one of {}", other, VOLUME_REMAINDER_NAMES.join(", ")
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Remainder::Carry => "carry",
+            Remainder::Partial => "partial",
+            Remainder::Reject => "reject",
+        }
+    }
+}
+
+/// Largest share of a bar's volume one ticker's orders may trade.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct VolumeLimit {
+    max_participation: f64,
+    remainder: Remainder,
+}
+
+impl VolumeLimit {
+    pub fn new(max_participation: f64, remainder: &str) -> PyResult<Self> {
+        if !(max_participation > 0.0 && max_participation <= 1.0) {
+            return Err(PyValueError::new_err(format!("max_participation must be in (0, 1], got {}", max_participation)));
+        }
+        Ok(VolumeLimit { max_participation, remainder: Remainder::parse(remainder)? })
+    }
+}
+
+/// Applies the volume limit, if any, to one ticker's orders and keeps its participation
+/// statistics.
+#[derive(Default)]
+pub(super) struct Participation {
+    limit: Option<VolumeLimit>,
+    fills: usize,
+    rate_sum: f64,
+    max_rate: f64,
+    capped_orders: usize,
+    rejected_orders: usize,
+    unfilled_shares: f64,
+}
+
+impl Participation {
+    pub fn new(limit: Option<VolumeLimit>) -> Self {
+        Participation { limit, ..Participation::default() }
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.limit.is_some()
+    }
+
+    /// Shares a bar of `volume` can absorb, in whole lots. Unlimited without a volume limit;
+    /// bars without volume absorb nothing.
+    pub fn capacity(&self, volume: f64, spec: &SymbolSpec) -> f64 {
+        match self.limit {
+            Some(limit) => spec.round_quantity(limit.max_participation * volume.max(0.0)),
+            None => f64::INFINITY,
+        }
+    }
+
+    /// Splits an order for `wanted` shares into the shares filled now and the shares carried to
+    /// the next bar; whatever is neither is dropped.
+    pub fn fill(&mut self, wanted: f64, capacity: f64, volume: f64) -> (f64, f64) {
+        let Some(limit) = self.limit else { return (wanted, 0.0) };
+        let (filled, carried) = if wanted <= capacity {
+            (wanted, 0.0)
+        } else {
+            self.capped_orders += 1;
+            match limit.remainder {
+                Remainder::Carry => (capacity, wanted - capacity),
+                Remainder::Partial => (capacity, 0.0),
+                Remainder::Reject => (0.0, 0.0),
+            }
+        };
+        if filled <= 0.0 && carried <= 0.0 && wanted > 0.0 {
+            self.rejected_orders += 1;
+        }
+        self.unfilled_shares += wanted - filled - carried;
+        if filled > 0.0 && volume > 0.0 {
+            let rate = filled / volume;
+            self.fills += 1;
+            self.rate_sum += rate;
+            self.max_rate = self.max_rate.max(rate);
+        }
+        (filled, carried)
+    }
+
+    /// Drops carried shares an opposite signal or a closed position cancelled.
+    pub fn cancel(&mut self, shares: f64) {
+        self.unfilled_shares += shares;
+    }
+
+    /// Drops an order that could not open a position, with whatever it would have carried.
+    pub fn refuse(&mut self, carried: f64) {
+        if carried > 0.0 {
+            self.rejected_orders += 1;
+            self.unfilled_shares += carried;
+        }
+    }
+
+    /// Statistics for the details, or `None` without a volume limit. `carried_shares` are still
+    /// waiting to fill after the last bar.
+    pub fn to_py<'py>(&self, py: Python<'py>, carried_shares: f64) -> PyResult<Option<&'py PyDict>> {
+        let Some(limit) = self.limit else { return Ok(None) };
+        let d = PyDict::new(py);
+        d.set_item("max_participation", limit.max_participation)?;
+        d.set_item("remainder", limit.remainder.name())?;
+        d.set_item("fills", self.fills)?;
+        d.set_item("mean_rate_pct", if self.fills > 0 { self.rate_sum / self.fills as f64 * 100.0 } else { 0.0 })?;
+        d.set_item("max_rate_pct", self.max_rate * 100.0)?;
+        d.set_item("capped_orders", self.capped_orders)?;
+        d.set_item("rejected_orders", self.rejected_orders)?;
+        d.set_item("unfilled_shares", self.unfilled_shares)?;
+        d.set_item("carried_shares", carried_shares)?;
+        Ok(Some(d))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limited(max_participation: f64, remainder: &str) -> Participation {
+        Participation::new(Some(VolumeLimit::new(max_participation, remainder).unwrap()))
+    }
+
+    #[test]
+    fn capacity_is_whole_lots_of_the_bar_share() {
+        let p = limited(0.1, "carry");
+        let lots = SymbolSpec { lot_size: 10.0, ..SymbolSpec::default() };
+        assert_eq!(p.capacity(1234.0, &lots), 120.0);
+        assert_eq!(p.capacity(-5.0, &SymbolSpec::default()), 0.0);
+        assert_eq!(Participation::new(None).capacity(0.0, &lots), f64::INFINITY);
+    }
+
+    #[test]
+    fn carry_fills_the_capacity_and_keeps_the_rest() {
+        let mut p = limited(0.1, "carry");
+        assert_eq!(p.fill(150.0, 100.0, 1000.0), (100.0, 50.0));
+        assert_eq!((p.capped_orders, p.rejected_orders, p.unfilled_shares), (1, 0, 0.0));
+        assert_eq!(p.fill(50.0, 100.0, 1000.0), (50.0, 0.0));
+        assert_eq!(p.fills, 2);
+        assert!((p.max_rate - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn partial_drops_the_rest() {
+        let mut p = limited(0.1, "partial");
+        assert_eq!(p.fill(150.0, 100.0, 1000.0), (100.0, 0.0));
+        assert_eq!((p.capped_orders, p.rejected_orders, p.unfilled_shares), (1, 0, 50.0));
+    }
+
+    #[test]
+    fn reject_sinks_the_whole_order() {
+        let mut p = limited(0.1, "reject");
+        assert_eq!(p.fill(150.0, 100.0, 1000.0), (0.0, 0.0));
+        assert_eq!((p.capped_orders, p.rejected_orders, p.unfilled_shares), (1, 1, 150.0));
+        assert_eq!(p.fills, 0);
+        assert_eq!(p.fill(80.0, 100.0, 1000.0), (80.0, 0.0));
+    }
+
+    #[test]
+    fn cancel_and_refuse_count_unfilled_shares() {
+        let mut p = limited(0.5, "carry");
+        p.cancel(30.0);
+        p.refuse(20.0);
+        p.refuse(0.0);
+        assert_eq!((p.rejected_orders, p.unfilled_shares), (1, 50.0));
+    }
+
+    #[test]
+    fn unlimited_fills_everything() {
+        let mut p = Participation::new(None);
+        assert!(!p.is_limited());
+        assert_eq!(p.fill(1e9, f64::INFINITY, 0.0), (1e9, 0.0));
+    }
+
+    #[test]
+    fn limit_and_remainder_are_checked() {
+        assert!(VolumeLimit::new(0.0, "carry").is_err());
+        assert!(VolumeLimit::new(1.5, "carry").is_err());
+        assert!(VolumeLimit::new(f64::NAN, "carry").is_err());
+        assert!(VolumeLimit::new(1.0, "carry").is_ok());
+        assert!(Remainder::parse("queue").is_err());
+    }
+}
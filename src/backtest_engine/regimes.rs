@@ -0,0 +1,81 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::{BTreeMap, HashMap};
+
+use super::Bar;
+use crate::stats::{sharpe_of_returns, TRADING_DAYS_PER_YEAR};
+
+/// External regime labels keyed by date. A bar takes the label of its exact timestamp, or else
+/// of its session date, so daily labels also cover intraday bars.
+pub(super) struct Regimes {
+    labels: HashMap<String, String>,
+}
+
+impl Regimes {
+    pub fn new(labels: HashMap<String, String>) -> Self {
+        Regimes { labels }
+    }
+
+    pub fn label(&self, bar: &Bar) -> Option<&str> {
+        self.labels.get(&bar.date).or_else(|| self.labels.get(&bar.session)).map(String::as_str)
+    }
+
+    pub fn label_for_date(&self, date: &str) -> Option<&str> {
+        self.labels.get(date)
+            .or_else(|| date.get(..10).and_then(|d| self.labels.get(d)))
+            .map(String::as_str)
+    }
+}
+
+#[derive(Default)]
+struct Segment {
+    bars: usize,
+    returns: Vec<f64>,
+    trades: usize,
+    wins: usize,
+}
+
+/// Performance split by regime label. Each bar's return is attributed to the regime of the bar
+/// it ends on and each trade to the regime of its entry bar; unlabeled bars are left out.
+/// `trades` holds (entry index, won) pairs; without it the trade statistics are omitted.
+pub(super) fn segment_metrics_to_py<'py>(
+    py: Python<'py>,
+    labels: &[Option<&str>],
+    equity: &[f64],
+    trades: Option<&[(usize, bool)]>,
+) -> PyResult<&'py PyDict> {
+    let mut segments: BTreeMap<&str, Segment> = BTreeMap::new();
+    for (k, label) in labels.iter().enumerate() {
+        let Some(label) = label else { continue };
+        let seg = segments.entry(label).or_default();
+        seg.bars += 1;
+        if k > 0 && equity[k - 1].abs() > f64::EPSILON {
+            seg.returns.push(equity[k] / equity[k - 1] - 1.0);
+        }
+    }
+    for &(entry, won) in trades.unwrap_or(&[]) {
+        if let Some(Some(label)) = labels.get(entry) {
+            let seg = segments.entry(label).or_default();
+            seg.trades += 1;
+            if won { seg.wins += 1; }
+        }
+    }
+
+    let out = PyDict::new(py);
+    for (label, seg) in segments {
+        let growth: f64 = seg.returns.iter().map(|r| 1.0 + r).product();
+        let item = PyDict::new(py);
+        item.set_item("bars", seg.bars)?;
+        item.set_item("roi_pct", (growth - 1.0) * 100.0)?;
+        item.set_item("annualized_return_pct", if seg.bars > 0 {
+            (growth.powf(TRADING_DAYS_PER_YEAR / seg.bars as f64) - 1.0) * 100.0
+        } else { 0.0 })?;
+        item.set_item("sharpe", sharpe_of_returns(&seg.returns))?;
+        if trades.is_some() {
+            item.set_item("trades", seg.trades)?;
+            item.set_item("win_rate_pct", if seg.trades > 0 { seg.wins as f64 / seg.trades as f64 * 100.0 } else { 0.0 })?;
+        }
+        out.set_item(label, item)?;
+    }
+    Ok(out)
+}
@@ -0,0 +1,101 @@
+use chrono::NaiveDate;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+use std::fs;
+
+const DAYS_PER_YEAR: f64 = 365.0;
+
+fn day(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Annual risk-free rates on known dates, interpolated linearly in calendar days between them
+/// and held flat before the first and after the last.
+pub(super) struct RiskFreeRate {
+    /// Sorted by date, one rate per date
+    points: Vec<(NaiveDate, f64)>,
+    /// Uninvested cash earns the rate
+    pub accrue_cash: bool,
+}
+
+impl RiskFreeRate {
+    /// From a dict of date -> rate, a `(dates, rates)` pair of sequences, or the path of a CSV
+    /// file with a header and `date,rate` rows. Rates are annual fractions (0.05 for 5%).
+    pub fn from_py(rates: &PyAny, accrue_cash: bool) -> PyResult<Self> {
+        let pairs: Vec<(String, f64)> = if let Ok(path) = rates.downcast::<PyString>() {
+            read_csv(path.to_str()?)?
+        } else if let Ok(map) = rates.downcast::<PyDict>() {
+            map.iter().map(|(d, r)| Ok((d.extract()?, r.extract()?))).collect::<PyResult<_>>()?
+        } else {
+            let (dates, values): (Vec<String>, Vec<f64>) = rates.extract().map_err(|_| PyValueError::new_err(
+                "risk-free rates must be a dict of date -> rate, a (dates, rates) pair or a CSV path"
+            ))?;
+            if dates.len() != values.len() {
+                return Err(PyValueError::new_err(format!("{} risk-free dates for {} rates", dates.len(), values.len())));
+            }
+            dates.into_iter().zip(values).collect()
+        };
+
+        let mut points = Vec::with_capacity(pairs.len());
+        for (date, rate) in pairs {
+            let d = day(&date).ok_or_else(|| PyValueError::new_err(format!("cannot read risk-free date '{}'", date)))?;
+            if !rate.is_finite() {
+                return Err(PyValueError::new_err(format!("risk-free rate on {} is {}", date, rate)));
+            }
+            points.push((d, rate));
+        }
+        if points.is_empty() {
+            return Err(PyValueError::new_err("the risk-free series is empty"));
+        }
+        points.sort_by_key(|p| p.0);
+        points.dedup_by_key(|p| p.0);
+        Ok(RiskFreeRate { points, accrue_cash })
+    }
+
+    fn rate_on(&self, d: NaiveDate) -> f64 {
+        let k = self.points.partition_point(|p| p.0 <= d);
+        if k == 0 {
+            return self.points[0].1;
+        }
+        let (d0, r0) = self.points[k - 1];
+        match self.points.get(k) {
+            Some(&(d1, r1)) => {
+                let t = (d - d0).num_days() as f64 / (d1 - d0).num_days() as f64;
+                r0 + (r1 - r0) * t
+            }
+            None => r0,
+        }
+    }
+
+    /// Average annual rate over the bars dated `dates`, for excess returns over that period.
+    pub fn mean_rate(&self, dates: &[String]) -> f64 {
+        let rates: Vec<f64> = dates.iter().filter_map(|d| day(d)).map(|d| self.rate_on(d)).collect();
+        if rates.is_empty() { 0.0 } else { rates.iter().sum::<f64>() / rates.len() as f64 }
+    }
+
+    /// Growth factor of cash held from the session `from` to the session `to`, compounding
+    /// the rate in force on `from` over the calendar days between them.
+    pub fn growth(&self, from: &str, to: &str) -> f64 {
+        match (day(from), day(to)) {
+            (Some(a), Some(b)) if b > a => (1.0 + self.rate_on(a)).powf((b - a).num_days() as f64 / DAYS_PER_YEAR),
+            _ => 1.0,
+        }
+    }
+}
+
+fn read_csv(path: &str) -> PyResult<Vec<(String, f64)>> {
+    let text = fs::read_to_string(path).map_err(|e| PyIOError::new_err(format!("reading {}: {}", path, e)))?;
+    text.lines()
+        .skip(1)
+        .filter(|l| !l.trim().is_empty())
+        .enumerate()
+        .map(|(k, line)| {
+            let mut fields = line.split(',').map(|f| f.trim());
+            let date = fields.next().unwrap_or("");
+            let rate = fields.next().and_then(|r| r.parse::<f64>().ok())
+                .ok_or_else(|| PyValueError::new_err(format!("{} row {}: expected date,rate", path, k + 1)))?;
+            Ok((date.to_string(), rate))
+        })
+        .collect()
+}
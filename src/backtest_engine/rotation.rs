@@ -0,0 +1,318 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+use std::collections::{BTreeSet, HashMap};
+
+use super::{BacktestEngine, Bar, INITIAL_CAPITAL_PER_STOCK};
+use crate::stats::max_drawdown;
+
+/// Closes for every ticker on the union of all tickers' dates, sorted by date. A ticker's gaps
+/// carry its previous close forward; before its first bar and after its delisting it is NaN.
+struct AlignedUniverse {
+    tickers: Vec<String>,
+    dates: Vec<String>,
+    closes: Vec<Vec<f64>>,
+    /// Index of each ticker's first and last bar
+    first: Vec<usize>,
+    last: Vec<usize>,
+    /// Index of the bar each delisted ticker was delisted on
+    delisted_at: Vec<Option<usize>>,
+}
+
+impl AlignedUniverse {
+    /// Whether ticker `k` can be ranked and bought at bar `i`: it has a full history before
+    /// `i`, still has data and is not delisting on that bar.
+    fn rankable(&self, k: usize, i: usize, history_size: usize) -> bool {
+        self.first[k] + history_size <= i && i <= self.last[k] && self.delisted_at[k].is_none_or(|d| i < d)
+    }
+}
+
+fn align_union(mut series: Vec<(String, Vec<Bar>)>) -> AlignedUniverse {
+    series.sort_by(|a, b| a.0.cmp(&b.0));
+    let all_dates: BTreeSet<&str> = series.iter().flat_map(|(_, bars)| bars.iter().map(|b| b.date.as_str())).collect();
+    let dates: Vec<String> = all_dates.into_iter().map(str::to_string).collect();
+    let index: HashMap<&str, usize> = dates.iter().enumerate().map(|(i, d)| (d.as_str(), i)).collect();
+
+    let n = series.len();
+    let mut closes = Vec::with_capacity(n);
+    let (mut first, mut last, mut delisted_at) = (Vec::with_capacity(n), Vec::with_capacity(n), Vec::with_capacity(n));
+    for (_, bars) in &series {
+        let mut row = vec![f64::NAN; dates.len()];
+        for b in bars {
+            row[index[b.date.as_str()]] = b.close;
+        }
+        let start = bars.first().map_or(dates.len(), |b| index[b.date.as_str()]);
+        let end = bars.last().map_or(0, |b| index[b.date.as_str()]);
+        let delisted = bars.last().filter(|b| b.delisted).map(|_| end);
+        // Forward-fill gaps; a delisted ticker stays NaN after its last bar.
+        let fill_to = if delisted.is_some() { end + 1 } else { dates.len() };
+        for i in start + 1..fill_to {
+            if row[i].is_nan() { row[i] = row[i - 1]; }
+        }
+        closes.push(row);
+        first.push(start);
+        last.push(end);
+        delisted_at.push(delisted);
+    }
+    AlignedUniverse { tickers: series.into_iter().map(|(t, _)| t).collect(), dates, closes, first, last, delisted_at }
+}
+
+fn load_aligned_universe(engine: &BacktestEngine) -> AlignedUniverse {
+    let mut series: Vec<(String, Vec<Bar>)> = Vec::new();
+    for path in engine.data_files() {
+        let file_path = path.to_str().unwrap();
+        let ticker = path.file_stem().unwrap().to_str().unwrap().replace("_meso", "");
+        match engine.load_bars(file_path, &ticker) {
+            Ok(bars) if !bars.is_empty() => series.push((ticker, bars)),
+            Ok(_) => log::warn!("Skipping {} because it has no bars", file_path),
+            Err(e) => log::warn!("Skipping {} because of read error: {}", file_path, e),
+        }
+    }
+    align_union(series)
+}
+
+/// Interprets the strategy's `rank` output: either a list of tickers, best first, or a
+/// dict of ticker -> score where higher is better. Unknown tickers and NaN scores are dropped;
+/// anything else is `None`.
+fn parse_ranking(py: Python<'_>, obj: &PyObject, tickers: &[String]) -> Option<Vec<usize>> {
+    let index_of = |t: &str| tickers.iter().position(|x| x == t);
+
+    if let Ok(scores) = obj.extract::<HashMap<String, f64>>(py) {
+        let mut ranked: Vec<(usize, f64)> = scores.iter()
+            .filter(|(_, s)| !s.is_nan())
+            .filter_map(|(t, s)| index_of(t).map(|i| (i, *s)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+        return Some(ranked.into_iter().map(|(i, _)| i).collect());
+    }
+    if let Ok(list) = obj.extract::<Vec<String>>(py) {
+        return Some(list.iter().filter_map(|t| index_of(t)).collect());
+    }
+    None
+}
+
+/// Value per selected name when `value` is split equally and the rebalance pays `rate` on its
+/// traded notional: the selected names now hold `held` and `sold` is the value of the names
+/// dropped. Solves `n * t + rate * (sold + sum |t - held_k|) = value` by fixed-point
+/// iteration, which contracts by `rate` per step.
+fn equal_weight_target(value: f64, held: &[f64], sold: f64, rate: f64) -> f64 {
+    let n = held.len() as f64;
+    let mut target = value / n;
+    for _ in 0..50 {
+        let traded = sold + held.iter().map(|h| (target - h).abs()).sum::<f64>();
+        let next = ((value - rate * traded) / n).max(0.0);
+        if (next - target).abs() <= 1e-12 * value.abs().max(1.0) {
+            return next;
+        }
+        target = next;
+    }
+    target
+}
+
+pub(super) fn run_rotation(
+    engine: &BacktestEngine,
+    py: Python<'_>,
+    top_n: usize,
+    rebalance_every: usize,
+    capital: f64,
+) -> PyResult<PyObject> {
+    let history_size = engine.history_size;
+    let universe = load_aligned_universe(engine);
+    let n_tickers = universe.tickers.len();
+    if n_tickers == 0 || universe.dates.len() <= history_size + 1 {
+        return Err(PyValueError::new_err(format!(
+            "rotation needs more than {} dates, found {} across {} tickers",
+            history_size + 1, universe.dates.len(), n_tickers
+        )));
+    }
+
+    let mut cash = capital;
+    let mut shares: Vec<f64> = vec![0.0; n_tickers];
+    let mut rebalances = 0;
+    let mut turnover_count = 0;
+    let mut total_commission = 0.0;
+    let mut strategy_errors = 0;
+    let mut delisted: Vec<String> = Vec::new();
+
+    let n_out = universe.dates.len() - history_size;
+    let mut dates: Vec<String> = Vec::with_capacity(n_out);
+    let mut balance_history: Vec<f64> = Vec::with_capacity(n_out);
+    let mut rebalance_indices: Vec<usize> = Vec::new();
+    let mut holdings: Vec<Vec<String>> = Vec::new();
+
+    for i in history_size..universe.dates.len() {
+        let prices: Vec<f64> = universe.closes.iter().map(|c| c[i]).collect();
+        // Names without a price (unlisted or delisted) are never held, so they add nothing.
+        let worth = |shares: &[f64]| -> f64 {
+            shares.iter().zip(prices.iter()).filter(|(s, _)| **s != 0.0).map(|(s, p)| s * p).sum()
+        };
+
+        // A ticker's delisting bar sells its shares at the recovery value; it leaves the ranking.
+        for k in 0..n_tickers {
+            if universe.delisted_at[k] == Some(i) {
+                let recovery = engine.delistings.get(&universe.tickers[k]).map_or(0.0, |d| d.recovery);
+                if shares[k] != 0.0 {
+                    cash += shares[k] * prices[k] * recovery;
+                    shares[k] = 0.0;
+                    turnover_count += 1;
+                }
+                delisted.push(universe.tickers[k].clone());
+            }
+        }
+        let value = cash + worth(&shares);
+
+        if (i - history_size).is_multiple_of(rebalance_every) {
+            let py_histories = PyDict::new(py);
+            for (k, (t, closes)) in universe.tickers.iter().zip(universe.closes.iter()).enumerate() {
+                if universe.rankable(k, i, history_size) {
+                    py_histories.set_item(t, PyArray1::from_slice(py, &closes[i - history_size..i]))?;
+                }
+            }
+
+            // A raising or unreadable `rank` keeps the holdings and counts as a strategy error,
+            // unless `on_error` propagates the exception.
+            let ranking = match engine.strategy.call_method1(py, "rank", (py_histories,)) {
+                Ok(obj) => parse_ranking(py, &obj, &universe.tickers).unwrap_or_else(|| {
+                    log::debug!("strategy.rank at index {} did not return a list or dict of tickers", i);
+                    strategy_errors += 1;
+                    Vec::new()
+                }),
+                Err(e) => {
+                    log::error!("Error calling strategy.rank at index {}: {}", i, e);
+                    if engine.on_error.propagates() {
+                        return Err(e);
+                    }
+                    strategy_errors += 1;
+                    Vec::new()
+                }
+            };
+
+            // Only rotate when the strategy produced a ranking; otherwise keep current holdings.
+            if !ranking.is_empty() {
+                let selected: Vec<usize> = ranking.into_iter()
+                    .filter(|&k| universe.rankable(k, i, history_size) && prices[k] > 0.0)
+                    .take(top_n)
+                    .collect();
+                let held: Vec<f64> = selected.iter().map(|&k| shares[k] * prices[k]).collect();
+                let sold: f64 = (0..n_tickers)
+                    .filter(|k| !selected.contains(k) && shares[*k] != 0.0)
+                    .map(|k| shares[k] * prices[k])
+                    .sum();
+                let target_value = if selected.is_empty() { 0.0 } else {
+                    equal_weight_target(value, &held, sold, engine.commission_rate)
+                };
+
+                let mut new_shares = vec![0.0; n_tickers];
+                for &k in &selected {
+                    new_shares[k] = target_value / prices[k];
+                }
+                turnover_count += (0..n_tickers).filter(|&k| (shares[k] > 0.0) != (new_shares[k] > 0.0)).count();
+
+                // Commission on the notional each name trades, paid out of the rebalanced value.
+                let traded: f64 = (0..n_tickers)
+                    .filter(|&k| new_shares[k] != shares[k])
+                    .map(|k| (new_shares[k] - shares[k]).abs() * prices[k])
+                    .sum();
+                let commission = traded * engine.commission_rate;
+                total_commission += commission;
+                let invested = worth(&new_shares);
+
+                shares = new_shares;
+                cash = value - invested - commission;
+                rebalances += 1;
+                rebalance_indices.push(i - history_size);
+                holdings.push(selected.iter().map(|&k| universe.tickers[k].clone()).collect());
+            }
+        }
+
+        dates.push(universe.dates[i].clone());
+        balance_history.push(cash + worth(&shares));
+    }
+
+    let final_balance = *balance_history.last().unwrap_or(&capital);
+    let roi_pct = ((final_balance - capital) / capital) * 100.0;
+
+    let py_summary = PyDict::new(py);
+    py_summary.set_item("tickers", universe.tickers.len())?;
+    py_summary.set_item("top_n", top_n)?;
+    py_summary.set_item("rebalance_every", rebalance_every)?;
+    py_summary.set_item("rebalances", rebalances)?;
+    py_summary.set_item("position_changes", turnover_count)?;
+    py_summary.set_item("commission", total_commission)?;
+    py_summary.set_item("strategy_errors", strategy_errors)?;
+    py_summary.set_item("delisted_tickers", delisted.len())?;
+    py_summary.set_item("final_capital", final_balance)?;
+    py_summary.set_item("total_roi_pct", roi_pct)?;
+    py_summary.set_item("sharpe", engine.sharpe(&dates, &balance_history))?;
+    py_summary.set_item("max_drawdown_pct", max_drawdown(&balance_history) * 100.0)?;
+
+    let details = PyDict::new(py);
+    details.set_item("dates", dates)?;
+    details.set_item("balance_history", PyArray1::from_vec(py, balance_history))?;
+    details.set_item("rebalance_indices", PyArray1::from_vec(py, rebalance_indices))?;
+    details.set_item("holdings", holdings)?;
+    details.set_item("delisted", delisted)?;
+
+    let py_out = PyDict::new(py);
+    py_out.set_item("portfolio_summary", py_summary)?;
+    py_out.set_item("details", details)?;
+    Ok(py_out.to_object(py))
+}
+
+pub(super) fn default_capital(engine: &BacktestEngine) -> f64 {
+    INITIAL_CAPITAL_PER_STOCK * engine.data_files().len().max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bars(dates: &[&str], delisted: bool) -> Vec<Bar> {
+        let mut bars: Vec<Bar> = dates.iter().enumerate()
+            .map(|(k, d)| Bar::new(d.to_string(), 1.0, 1.0, 1.0, 10.0 + k as f64, 0.0))
+            .collect();
+        if let Some(last) = bars.last_mut() { last.delisted = delisted; }
+        bars
+    }
+
+    #[test]
+    fn union_alignment_fills_gaps_and_ends_delisted_tickers() {
+        let u = align_union(vec![
+            ("B".to_string(), bars(&["d1", "d2"], true)),
+            ("A".to_string(), bars(&["d1", "d3", "d4"], false)),
+        ]);
+        assert_eq!(u.tickers, ["A", "B"]);
+        assert_eq!(u.dates, ["d1", "d2", "d3", "d4"]);
+        assert_eq!(u.closes[0], [10.0, 10.0, 11.0, 12.0]);
+        assert_eq!(u.closes[1][..2], [10.0, 11.0]);
+        assert!(u.closes[1][2..].iter().all(|c| c.is_nan()));
+        assert_eq!((u.delisted_at[0], u.delisted_at[1]), (None, Some(1)));
+    }
+
+    #[test]
+    fn rankable_needs_history_and_a_listing() {
+        let u = align_union(vec![
+            ("A".to_string(), bars(&["d1", "d2", "d3", "d4"], false)),
+            ("B".to_string(), bars(&["d2", "d3"], true)),
+        ]);
+        assert!(!u.rankable(0, 1, 2) && u.rankable(0, 2, 2) && u.rankable(0, 3, 2));
+        // B starts at d2 and delists on d3, so it never has two bars of history while listed.
+        assert!(!u.rankable(1, 2, 1) && !u.rankable(1, 3, 1));
+        assert!(u.rankable(1, 1, 0));
+    }
+
+    #[test]
+    fn target_without_commission_is_an_equal_split() {
+        assert_eq!(equal_weight_target(900.0, &[0.0, 0.0, 0.0], 0.0, 0.0), 300.0);
+    }
+
+    #[test]
+    fn target_leaves_room_for_the_commission() {
+        let (value, held, sold, rate) = (1000.0, [400.0, 0.0], 600.0, 0.01);
+        let t = equal_weight_target(value, &held, sold, rate);
+        let traded = sold + held.iter().map(|h| (t - h).abs()).sum::<f64>();
+        assert!((2.0 * t + rate * traded - value).abs() < 1e-9);
+        assert!(t < 500.0);
+    }
+}
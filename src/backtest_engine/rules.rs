@@ -0,0 +1,141 @@
+use numpy::PyArray1;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
+
+/// Engine-level limits on when a buy signal may open a position. Zero means no limit.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct EntryRules {
+    /// Bars that must pass after an exit before the next entry
+    pub min_bars_between_trades: usize,
+    /// Bars that must pass after a losing exit before the next entry
+    pub reentry_after_loss_bars: usize,
+    /// Entries allowed per session date
+    pub max_trades_per_day: usize,
+}
+
+impl EntryRules {
+    pub fn from_map(fields: &HashMap<String, usize>) -> PyResult<Self> {
+        let mut rules = EntryRules::default();
+        for (key, &value) in fields {
+            match key.as_str() {
+                "min_bars_between_trades" => rules.min_bars_between_trades = value,
+                "reentry_after_loss_bars" => rules.reentry_after_loss_bars = value,
+                "max_trades_per_day" => rules.max_trades_per_day = value,
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "unknown entry rule '{}'; expected min_bars_between_trades, reentry_after_loss_bars or max_trades_per_day",
+                        other
+                    )))
+                }
+            }
+        }
+        Ok(rules)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.min_bars_between_trades > 0 || self.reentry_after_loss_bars > 0 || self.max_trades_per_day > 0
+    }
+
+    /// The first rule that forbids an entry now, given the bars since the last exit (if any),
+    /// whether that exit lost money and how many entries the session already had.
+    pub fn blocked_by(&self, bars_since_exit: Option<usize>, last_exit_loss: bool, entries_today: usize) -> Option<&'static str> {
+        if let Some(bars) = bars_since_exit {
+            if bars < self.min_bars_between_trades {
+                return Some("min_bars_between_trades");
+            }
+            if last_exit_loss && bars < self.reentry_after_loss_bars {
+                return Some("reentry_after_loss_bars");
+            }
+        }
+        if self.max_trades_per_day > 0 && entries_today >= self.max_trades_per_day {
+            return Some("max_trades_per_day");
+        }
+        None
+    }
+}
+
+/// Engine-level debouncing of strategy signals: a buy or sell the filters refuse acts as a hold.
+/// Zero means no filter.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct SignalFilters {
+    /// Bars in a row a signal must repeat before it acts
+    pub confirm_bars: usize,
+    /// Bars in a row a sell must repeat before it exits, if different from `confirm_bars`;
+    /// a longer exit confirmation keeps a position through brief reversals
+    pub exit_confirm_bars: Option<usize>,
+    /// Bars a position must be held before a sell may exit
+    pub min_hold_bars: usize,
+}
+
+impl SignalFilters {
+    pub fn from_map(fields: &HashMap<String, usize>) -> PyResult<Self> {
+        let mut filters = SignalFilters::default();
+        for (key, &value) in fields {
+            match key.as_str() {
+                "confirm_bars" => filters.confirm_bars = value,
+                "exit_confirm_bars" => filters.exit_confirm_bars = Some(value),
+                "min_hold_bars" => filters.min_hold_bars = value,
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "unknown signal filter '{}'; expected confirm_bars, exit_confirm_bars or min_hold_bars",
+                        other
+                    )))
+                }
+            }
+        }
+        Ok(filters)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.confirm_bars > 1 || self.exit_confirm_bars.is_some_and(|b| b > 1) || self.min_hold_bars > 0
+    }
+
+    /// The first filter that holds back a buy when flat, or a sell when `bars_held` into a
+    /// position, given the bars in a row (`streak`, counting this one) the signal repeated.
+    pub fn blocked_by(&self, signal: i32, in_position: bool, streak: usize, bars_held: usize) -> Option<&'static str> {
+        if signal == 1 && !in_position && streak < self.confirm_bars {
+            return Some("confirm_bars");
+        }
+        if signal == -1 && in_position {
+            if bars_held < self.min_hold_bars {
+                return Some("min_hold_bars");
+            }
+            match self.exit_confirm_bars {
+                Some(bars) if streak < bars => return Some("exit_confirm_bars"),
+                None if streak < self.confirm_bars => return Some("confirm_bars"),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// Signals the engine refused, with the rule or filter that refused them.
+#[derive(Default)]
+pub(super) struct Overrides {
+    index: Vec<usize>,
+    signal: Vec<i32>,
+    rule: Vec<&'static str>,
+}
+
+impl Overrides {
+    pub fn record(&mut self, index: usize, signal: i32, rule: &'static str) {
+        self.index.push(index);
+        self.signal.push(signal);
+        self.rule.push(rule);
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn into_py(self, py: Python<'_>) -> PyResult<&PyDict> {
+        let out = PyDict::new(py);
+        out.set_item("index", PyArray1::from_vec(py, self.index))?;
+        out.set_item("signal", PyArray1::from_vec(py, self.signal))?;
+        out.set_item("rule", self.rule)?;
+        Ok(out)
+    }
+}
@@ -0,0 +1,52 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+pub(super) const SHARPE_SAMPLING_NAMES: [&str; 2] = ["daily", "bar"];
+
+/// Which returns a Sharpe ratio is computed from. It is annualized with 252 periods a year,
+/// which only fits daily returns.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum SharpeSampling {
+    /// Intraday curves are sampled at each day's last bar; daily curves are used as they are
+    Daily,
+    /// Every bar's return, whatever the bar length
+    Bar,
+}
+
+impl SharpeSampling {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "daily" => Ok(SharpeSampling::Daily),
+            "bar" => Ok(SharpeSampling::Bar),
+            other => Err(PyValueError::new_err(format!(
+                "unknown sharpe sampling '{}', expected one of {}", other, SHARPE_SAMPLING_NAMES.join(", ")
+            ))),
+        }
+    }
+}
+
+/// Day (the date part of a timestamp) of each bar, or the whole string when it is too short to
+/// hold a date.
+fn day(date: &str) -> &str {
+    date.get(..10).unwrap_or(date)
+}
+
+/// The value at each day's last bar, dated by the day, when some day has more than one bar;
+/// `None` for curves that are already daily or coarser.
+pub(super) fn daily_closes(dates: &[String], values: &[f64]) -> Option<(Vec<String>, Vec<f64>)> {
+    if !dates.windows(2).any(|w| day(&w[0]) == day(&w[1])) {
+        return None;
+    }
+    let mut days: Vec<String> = Vec::new();
+    let mut closes: Vec<f64> = Vec::new();
+    for (date, &v) in dates.iter().zip(values) {
+        match (days.last(), closes.last_mut()) {
+            (Some(last), Some(close)) if last == day(date) => *close = v,
+            _ => {
+                days.push(day(date).to_string());
+                closes.push(v);
+            }
+        }
+    }
+    Some((days, closes))
+}
@@ -0,0 +1,118 @@
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+pub(super) const SCHEDULING_NAMES: [&str; 2] = ["serial", "pipelined"];
+
+/// How `run` spreads a ticker's work over threads. Strategy calls and result objects need the
+/// GIL, so they always stay on the calling thread; only the GIL-free stages move.
+pub(super) enum Scheduling {
+    /// Everything on the calling thread, one ticker after another
+    Serial,
+    /// Files are read and parsed on `io_threads` workers and pattern signals computed on
+    /// `compute_threads` workers ahead of the calling thread, which releases the GIL while it
+    /// waits for the next ticker
+    Pipelined { io_threads: usize, compute_threads: usize },
+}
+
+impl Scheduling {
+    pub fn parse(mode: &str, io_threads: Option<usize>, compute_threads: Option<usize>) -> PyResult<Self> {
+        match mode {
+            "serial" => Ok(Scheduling::Serial),
+            "pipelined" => {
+                let cores = thread::available_parallelism().map_or(2, |n| n.get());
+                let io_threads = io_threads.unwrap_or_else(|| cores.saturating_sub(1).clamp(1, 4));
+                let compute_threads = compute_threads.unwrap_or(1);
+                if io_threads == 0 || compute_threads == 0 {
+                    return Err(PyValueError::new_err("n_io_threads and n_compute_threads must be at least 1"));
+                }
+                Ok(Scheduling::Pipelined { io_threads, compute_threads })
+            }
+            other => Err(PyValueError::new_err(format!(
+                "unknown scheduling '{}', expected one of {}", other, SCHEDULING_NAMES.join(", ")
+            ))),
+        }
+    }
+
+    /// Runs `load` then `prepare` on every source and hands the results to `consume` as an
+    /// iterator in source order. `load` and `prepare` must not touch Python: in pipelined mode
+    /// they run on worker threads while `consume` holds the GIL on the calling thread.
+    pub fn run<S, L, P, R>(
+        &self,
+        py: Python<'_>,
+        sources: &[S],
+        load: impl Fn(&S) -> L + Sync,
+        prepare: impl Fn(L) -> P + Sync,
+        consume: impl FnOnce(&mut dyn Iterator<Item = PyResult<P>>) -> PyResult<R>,
+    ) -> PyResult<R>
+    where
+        S: Sync,
+        L: Send,
+        P: Send,
+    {
+        let (io_threads, compute_threads) = match *self {
+            Scheduling::Serial => return consume(&mut sources.iter().map(|s| Ok(prepare(load(s))))),
+            Scheduling::Pipelined { io_threads, compute_threads } => (io_threads, compute_threads),
+        };
+
+        // Bounded channels keep the workers a few tickers ahead without loading the universe.
+        let (loaded_tx, loaded_rx) = mpsc::sync_channel::<(usize, L)>(io_threads);
+        let (ready_tx, ready_rx) = mpsc::sync_channel::<(usize, P)>(compute_threads);
+        // Shared by the compute workers and dropped with the last of them, which unblocks
+        // loaders waiting on a full channel when the calling thread stops early.
+        let loaded_rx = Arc::new(Mutex::new(loaded_rx));
+        let next = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            let (next, load, prepare) = (&next, &load, &prepare);
+
+            for _ in 0..io_threads {
+                let tx = loaded_tx.clone();
+                scope.spawn(move || loop {
+                    let k = next.fetch_add(1, Ordering::Relaxed);
+                    if k >= sources.len() || tx.send((k, load(&sources[k]))).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(loaded_tx);
+            for _ in 0..compute_threads {
+                let tx = ready_tx.clone();
+                let loaded_rx = Arc::clone(&loaded_rx);
+                scope.spawn(move || loop {
+                    let received = loaded_rx.lock().unwrap().recv();
+                    let Ok((k, loaded)) = received else { break };
+                    if tx.send((k, prepare(loaded))).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(ready_tx);
+            drop(loaded_rx);
+
+            // Workers finish out of order; results are held back until their turn comes.
+            let mut rx = Some(ready_rx);
+            let mut early: HashMap<usize, P> = HashMap::new();
+            let mut in_order = (0..sources.len()).map(|k| loop {
+                if let Some(p) = early.remove(&k) {
+                    return Ok(p);
+                }
+                let waiting = rx.take().unwrap();
+                let (waiting, received) = py.allow_threads(move || {
+                    let received = waiting.recv();
+                    (waiting, received)
+                });
+                rx = Some(waiting);
+                match received {
+                    Ok((j, p)) if j == k => return Ok(p),
+                    Ok((j, p)) => { early.insert(j, p); }
+                    Err(_) => return Err(PyRuntimeError::new_err("a worker thread stopped before its ticker was ready")),
+                }
+            });
+            // Returning drops the receiver, so workers blocked on a full channel stop too.
+            consume(&mut in_order)
+        })
+    }
+}
@@ -0,0 +1,228 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyLong, PyTuple};
+use std::collections::HashMap;
+
+use super::optimize::{evaluate, search_result, Objective};
+use super::BacktestEngine;
+use crate::rng::SplitMix64;
+
+/// One searchable parameter. A list in the space is categorical, a `(low, high)` tuple is an
+/// inclusive integer range when both ends are ints and a float range otherwise.
+enum Dimension {
+    Categorical(Vec<PyObject>),
+    Int { low: i64, high: i64 },
+    Float { low: f64, high: f64 },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Gene {
+    Choice(usize),
+    Int(i64),
+    Float(f64),
+}
+
+impl Dimension {
+    fn parse(name: &str, spec: &PyAny) -> PyResult<Self> {
+        if let Ok(range) = spec.downcast::<PyTuple>() {
+            if range.len() != 2 {
+                return Err(PyValueError::new_err(format!("range for '{}' must be (low, high)", name)));
+            }
+            let (low, high) = (range.get_item(0)?, range.get_item(1)?);
+            let dim = if low.is_instance_of::<PyLong>() && high.is_instance_of::<PyLong>() {
+                Dimension::Int { low: low.extract()?, high: high.extract()? }
+            } else {
+                Dimension::Float { low: low.extract()?, high: high.extract()? }
+            };
+            let empty = match dim {
+                Dimension::Int { low, high } => low > high,
+                Dimension::Float { low, high } => low.is_nan() || high.is_nan() || low > high,
+                Dimension::Categorical(_) => false,
+            };
+            if empty {
+                return Err(PyValueError::new_err(format!("range for '{}' has low > high", name)));
+            }
+            return Ok(dim);
+        }
+        let choices: Vec<PyObject> = spec.iter()?.map(|v| v.map(Into::into)).collect::<PyResult<_>>()?;
+        if choices.is_empty() {
+            return Err(PyValueError::new_err(format!("no values to choose from for '{}'", name)));
+        }
+        Ok(Dimension::Categorical(choices))
+    }
+
+    fn sample(&self, rng: &mut SplitMix64) -> Gene {
+        match self {
+            Dimension::Categorical(c) => Gene::Choice(rng.below(c.len())),
+            Dimension::Int { low, high } => Gene::Int(low + rng.below((high - low + 1) as usize) as i64),
+            Dimension::Float { low, high } => Gene::Float(low + rng.next_f64() * (high - low)),
+        }
+    }
+
+    /// Categorical genes are resampled; numeric ones take a normal step of a tenth of the range.
+    fn mutate(&self, gene: Gene, rng: &mut SplitMix64) -> Gene {
+        let step = |rng: &mut SplitMix64, span: f64| {
+            // Box-Muller
+            let (u1, u2) = (rng.next_f64().max(f64::MIN_POSITIVE), rng.next_f64());
+            (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos() * span * 0.1
+        };
+        match (self, gene) {
+            (Dimension::Int { low, high }, Gene::Int(v)) => {
+                // Always move at least one step so narrow ranges still mutate.
+                let delta = match step(rng, (high - low) as f64).round() as i64 {
+                    0 if rng.next_f64() < 0.5 => -1,
+                    0 => 1,
+                    d => d,
+                };
+                Gene::Int((v + delta).clamp(*low, *high))
+            }
+            (Dimension::Float { low, high }, Gene::Float(v)) => Gene::Float((v + step(rng, high - low)).clamp(*low, *high)),
+            _ => self.sample(rng),
+        }
+    }
+
+    fn value(&self, py: Python<'_>, gene: Gene) -> PyObject {
+        match (self, gene) {
+            (Dimension::Categorical(c), Gene::Choice(k)) => c[k].clone_ref(py),
+            (_, Gene::Int(v)) => v.into_py(py),
+            (_, Gene::Float(v)) => v.into_py(py),
+            (_, Gene::Choice(k)) => k.into_py(py),
+        }
+    }
+}
+
+/// Knobs of `search`; see `BacktestEngine::search`.
+pub(super) struct SearchOptions {
+    pub n_trials: usize,
+    pub population: usize,
+    pub mutation_rate: f64,
+    pub patience: Option<usize>,
+    pub seed: u64,
+}
+
+/// Evaluates candidates, remembering scores so repeated candidates aren't re-run, and keeps
+/// every distinct trial for the result.
+struct Trials<'a, 'py> {
+    engine: &'a BacktestEngine,
+    py: Python<'py>,
+    factory: &'a PyObject,
+    names: &'a [String],
+    dims: &'a [Dimension],
+    objective: Objective,
+    seen: HashMap<String, f64>,
+    params: &'py PyList,
+    scores: Vec<f64>,
+}
+
+impl Trials<'_, '_> {
+    fn score(&mut self, genes: &[Gene]) -> PyResult<f64> {
+        let key = genes.iter().map(|g| match g {
+            Gene::Choice(k) => format!("c{}", k),
+            Gene::Int(v) => format!("i{}", v),
+            Gene::Float(v) => format!("f{:x}", v.to_bits()),
+        }).collect::<Vec<_>>().join(",");
+        if let Some(&score) = self.seen.get(&key) {
+            return Ok(score);
+        }
+        let params = PyDict::new(self.py);
+        for ((name, dim), &gene) in self.names.iter().zip(self.dims).zip(genes) {
+            params.set_item(name, dim.value(self.py, gene))?;
+        }
+        let score = evaluate(self.engine, self.py, self.factory, params, self.objective, None)?.score;
+        self.seen.insert(key, score);
+        self.params.append(params)?;
+        self.scores.push(score);
+        Ok(score)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn search(
+    engine: &BacktestEngine,
+    py: Python<'_>,
+    factory: PyObject,
+    space: &PyDict,
+    method: &str,
+    objective: Objective,
+    maximize: bool,
+    options: SearchOptions,
+) -> PyResult<PyObject> {
+    let names: Vec<String> = space.keys().extract()?;
+    let dims: Vec<Dimension> = space.iter()
+        .map(|(name, spec)| Dimension::parse(&name.extract::<String>()?, spec))
+        .collect::<PyResult<_>>()?;
+    if dims.is_empty() {
+        return Err(PyValueError::new_err("space must contain at least one parameter"));
+    }
+    let mut rng = SplitMix64::new(options.seed);
+    let mut trials = Trials {
+        engine, py, factory: &factory, names: &names, dims: &dims, objective,
+        seen: HashMap::new(), params: PyList::empty(py), scores: Vec::new(),
+    };
+    // NaN scores never count as an improvement.
+    let improves = |score: f64, best: Option<f64>| !score.is_nan() && best.is_none_or(|b| if maximize { score > b } else { score < b });
+    let mut best: Option<f64> = None;
+    let mut stale = 0;
+    let mut stopped_early = false;
+    let mut best_per_generation = Vec::new();
+
+    match method {
+        "random" => {
+            for _ in 0..options.n_trials {
+                let genes: Vec<Gene> = dims.iter().map(|d| d.sample(&mut rng)).collect();
+                let score = trials.score(&genes)?;
+                if improves(score, best) { best = Some(score); stale = 0; } else { stale += 1; }
+                if options.patience.is_some_and(|p| stale >= p) {
+                    stopped_early = true;
+                    break;
+                }
+            }
+        }
+        "genetic" => {
+            let size = options.population.max(2);
+            let mut population: Vec<Vec<Gene>> = (0..size)
+                .map(|_| dims.iter().map(|d| d.sample(&mut rng)).collect())
+                .collect();
+            let generations = options.n_trials.div_ceil(size).max(1);
+            for _ in 0..generations {
+                let fitness: Vec<f64> = population.iter().map(|g| trials.score(g)).collect::<PyResult<_>>()?;
+                // Rank best first; NaN scores sink to the end.
+                let mut order: Vec<usize> = (0..size).collect();
+                let key = |k: &usize| if fitness[*k].is_nan() { f64::NEG_INFINITY } else if maximize { fitness[*k] } else { -fitness[*k] };
+                order.sort_by(|a, b| key(b).total_cmp(&key(a)));
+                let generation_best = fitness[order[0]];
+                best_per_generation.push(generation_best);
+                if improves(generation_best, best) { best = Some(generation_best); stale = 0; } else { stale += 1; }
+                if options.patience.is_some_and(|p| stale >= p) {
+                    stopped_early = true;
+                    break;
+                }
+
+                // Two elites survive; the rest are children of tournament-selected parents with
+                // uniform crossover and per-gene mutation.
+                let mut next: Vec<Vec<Gene>> = order.iter().take(2).map(|&k| population[k].clone()).collect();
+                let tournament = |rng: &mut SplitMix64| {
+                    (0..3).map(|_| rng.below(size)).min_by_key(|k| order.iter().position(|o| o == k)).unwrap()
+                };
+                while next.len() < size {
+                    let (a, b) = (tournament(&mut rng), tournament(&mut rng));
+                    let child = dims.iter().enumerate().map(|(d, dim)| {
+                        let gene = if rng.next_f64() < 0.5 { population[a][d] } else { population[b][d] };
+                        if rng.next_f64() < options.mutation_rate { dim.mutate(gene, &mut rng) } else { gene }
+                    }).collect();
+                    next.push(child);
+                }
+                population = next;
+            }
+        }
+        other => return Err(PyValueError::new_err(format!("method must be 'random' or 'genetic', got '{}'", other))),
+    }
+
+    let out = search_result(py, trials.params, &trials.scores, objective, maximize)?;
+    out.set_item("method", method)?;
+    out.set_item("stopped_early", stopped_early)?;
+    if method == "genetic" {
+        out.set_item("best_per_generation", best_per_generation)?;
+    }
+    Ok(out.into())
+}
@@ -0,0 +1,134 @@
+use chrono::NaiveDate;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use super::state::TickerState;
+use super::{BacktestEngine, Bar, Signal, TickerRun, INITIAL_CAPITAL_PER_STOCK};
+use crate::rng::SplitMix64;
+use crate::synthetic::{gbm, weekdays};
+
+/// Relative tolerance of the accounting identities.
+const TOLERANCE: f64 = 1e-9;
+
+/// Outcome of one invariant: its name and, when violated, what went wrong.
+struct Check {
+    name: &'static str,
+    violation: Option<String>,
+}
+
+fn close_enough(a: f64, b: f64) -> bool {
+    (a - b).abs() <= TOLERANCE * a.abs().max(b.abs()).max(1.0)
+}
+
+/// First bar where `lhs` and `rhs` differ beyond the tolerance, as a violation message.
+fn first_mismatch(what: &str, lhs: &[f64], rhs: &[f64]) -> Option<String> {
+    if lhs.len() != rhs.len() {
+        return Some(format!("{}: {} values against {}", what, lhs.len(), rhs.len()));
+    }
+    lhs.iter().zip(rhs).position(|(a, b)| !close_enough(*a, *b))
+        .map(|t| format!("{} differ at bar {}: {} vs {}", what, t, lhs[t], rhs[t]))
+}
+
+fn series(run: &TickerRun<'_>, key: &str) -> PyResult<Vec<f64>> {
+    run.detail.get_item(key).map_or(Ok(Vec::new()), |v| v.extract())
+}
+
+/// Runs `engine` over `bars` with a fixed signal per bar.
+fn run_signals<'py>(engine: &BacktestEngine, py: Python<'py>, bars: &[Bar], signals: &[i32]) -> PyResult<TickerRun<'py>> {
+    let st = TickerState::new(INITIAL_CAPITAL_PER_STOCK, INITIAL_CAPITAL_PER_STOCK / bars[0].close);
+    engine.simulate_ticker(py, "SELF_CHECK", bars, 0, st, false, |i, _, _| Ok(Some(Signal::full(signals[i]))))
+}
+
+/// Runs the engine over seeded GBM bars with strategies whose outcome is known and checks
+/// the accounting. Identities that hold under any configuration use `engine` itself; exact
+/// baselines use a default engine (no commission, close fills, full sizing).
+pub(super) fn self_check(engine: &BacktestEngine, py: Python<'_>, n_bars: usize, seed: u64) -> PyResult<PyObject> {
+    let prices = gbm(n_bars, 100.0, 0.08, 0.3, seed);
+    let dates = weekdays(NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(), n_bars);
+    let bars: Vec<Bar> = (0..n_bars)
+        .map(|t| Bar::new(dates[t].to_string(), prices.open[t], prices.high[t], prices.low[t], prices.close[t], prices.volume[t]))
+        .collect();
+    let baseline = BacktestEngine::builder()
+        .strategy(py.None())
+        .history_size(engine.history_size)
+        .data_folder(engine.data_folder.clone())
+        .build()?;
+
+    let mut rng = SplitMix64::new(seed);
+    let random: Vec<i32> = (0..n_bars)
+        .map(|_| match rng.below(10) { 0 => 1, 1 => -1, _ => 0 })
+        .collect();
+    let flat = vec![0; n_bars];
+    let mut hold = vec![0; n_bars];
+    hold[0] = 1;
+    let mut checks = Vec::new();
+
+    // No signals: nothing trades and the capital is untouched.
+    let run = run_signals(engine, py, &bars, &flat)?;
+    checks.push(Check {
+        name: "zero_signal_keeps_capital",
+        violation: if run.metric.trades != 0 || !close_enough(run.metric.final_balance, INITIAL_CAPITAL_PER_STOCK) {
+            Some(format!("{} trades, final balance {}", run.metric.trades, run.metric.final_balance))
+        } else { None },
+    });
+
+    // Buying everything on the first bar is the buy-and-hold baseline, bar for bar.
+    let run = run_signals(&baseline, py, &bars, &hold)?;
+    let buy_and_hold: Vec<f64> = bars.iter().map(|b| INITIAL_CAPITAL_PER_STOCK / bars[0].close * b.close).collect();
+    checks.push(Check {
+        name: "buy_and_hold_matches_baseline",
+        violation: first_mismatch("equity and buy-and-hold", &series(&run, "balance_history")?, &buy_and_hold)
+            .or_else(|| (!close_enough(run.metric.roi_pct, run.metric.buy_and_hold_pct)).then(|| format!(
+                "roi_pct {} vs buy_and_hold_pct {}", run.metric.roi_pct, run.metric.buy_and_hold_pct
+            ))),
+    });
+
+    // Random trading: equity splits into cash and position, and its change from the initial
+    // capital is exactly realized plus unrealized PnL.
+    let run = run_signals(engine, py, &bars, &random)?;
+    let equity = series(&run, "balance_history")?;
+    let split: Vec<f64> = series(&run, "cash")?.iter().zip(series(&run, "invested")?).map(|(c, v)| c + v).collect();
+    checks.push(Check { name: "equity_is_cash_plus_invested", violation: first_mismatch("equity and cash + invested", &equity, &split) });
+    let pnl: Vec<f64> = series(&run, "realized_pnl")?.iter().zip(series(&run, "unrealized_pnl")?)
+        .map(|(r, u)| INITIAL_CAPITAL_PER_STOCK + r + u)
+        .collect();
+    checks.push(Check { name: "equity_is_capital_plus_pnl", violation: first_mismatch("equity and capital + PnL", &equity, &pnl) });
+    checks.push(Check {
+        name: "equity_positive_and_finite",
+        violation: equity.iter().position(|v| !v.is_finite() || *v <= 0.0).map(|t| format!("equity is {} at bar {}", equity[t], t)),
+    });
+
+    // Commissions can only cost money on the same trades.
+    let free = run_signals(&baseline, py, &bars, &random)?;
+    let charged = BacktestEngine::builder()
+        .strategy(py.None())
+        .history_size(engine.history_size)
+        .data_folder(engine.data_folder.clone())
+        .commission_bps(10.0)
+        .build()?;
+    let charged = run_signals(&charged, py, &bars, &random)?;
+    checks.push(Check {
+        name: "commission_never_helps",
+        violation: (charged.metric.final_balance > free.metric.final_balance * (1.0 + TOLERANCE)).then(|| format!(
+            "final balance {} with commission vs {} without", charged.metric.final_balance, free.metric.final_balance
+        )),
+    });
+
+    let out = PyDict::new(py);
+    let list = PyList::empty(py);
+    let mut passed = true;
+    for check in checks {
+        let item = PyDict::new(py);
+        item.set_item("name", check.name)?;
+        item.set_item("passed", check.violation.is_none())?;
+        if let Some(v) = &check.violation {
+            log::error!("self_check {} failed: {}", check.name, v);
+            passed = false;
+        }
+        item.set_item("violation", check.violation)?;
+        list.append(item)?;
+    }
+    out.set_item("passed", passed)?;
+    out.set_item("checks", list)?;
+    Ok(out.to_object(py))
+}
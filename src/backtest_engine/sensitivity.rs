@@ -0,0 +1,131 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::time::Instant;
+
+use super::state::TickerState;
+use super::{combine_equity_curves, validation, BacktestEngine, Bar, Signal, INITIAL_CAPITAL_PER_STOCK};
+use crate::stats::max_drawdown;
+
+/// One ticker's bars and the signals the strategy gave for bars `history_size..`.
+struct Recorded {
+    name: String,
+    bars: Vec<Bar>,
+    signals: Vec<Option<Signal>>,
+}
+
+/// Asks the strategy for every ticker's signals once, through a run at the engine's own
+/// settings.
+fn record(engine: &BacktestEngine, py: Python<'_>) -> PyResult<Vec<Recorded>> {
+    engine.strategy_scope.check(py, &engine.strategy)?;
+    let start = engine.history_size;
+    let mut out = Vec::new();
+    for path in engine.data_files() {
+        let file_path = path.to_str().unwrap();
+        let name = path.file_stem().unwrap().to_str().unwrap().replace("_meso", "");
+        let bars = match engine.load_bars(file_path, &name) {
+            Ok(bars) => bars,
+            Err(e) => {
+                log::warn!("Skipping {} because of read error: {}", file_path, e);
+                continue;
+            }
+        };
+        if let Some(reason) = engine.nan_policy.screen(&name, || validation::check_bars(&bars))? {
+            log::warn!("Skipping {}: {}", file_path, reason);
+            continue;
+        }
+        if bars.len() <= start + 1 {
+            continue;
+        }
+        let strategy = &engine.strategy_scope.instance(py, &engine.strategy, &name)?;
+        let subscribed = BacktestEngine::subscribed_patterns(py, strategy);
+        let pattern_signals = BacktestEngine::pattern_signals(&subscribed, &bars);
+        let signals = if strategy.as_ref(py).hasattr("step_batch")? {
+            engine.batch_signals(py, strategy, &name, &bars, start, &pattern_signals)?
+        } else {
+            let mut signals = Vec::with_capacity(bars.len() - start);
+            let st = TickerState::new(INITIAL_CAPITAL_PER_STOCK, INITIAL_CAPITAL_PER_STOCK / bars[start].close);
+            engine.simulate_ticker(py, &name, &bars, start, st, false, |i, history, position| {
+                let signal = engine.step_signal(py, strategy, &name, &bars, &pattern_signals, i, history, position)?;
+                signals.push(signal);
+                Ok(signal)
+            })?;
+            signals
+        };
+        out.push(Recorded { name, bars, signals });
+    }
+    Ok(out)
+}
+
+/// Replays the recorded signals at each cost level in turn; `commission_rate` holds each level
+/// while it is simulated.
+fn sweep(engine: &mut BacktestEngine, py: Python<'_>, recorded: &[Recorded], costs_bps: &[f64]) -> PyResult<PyObject> {
+    let start = engine.history_size;
+    let summary = PyDict::new(py);
+    let (mut roi, mut sharpe, mut average_sharpe, mut drawdown, mut trades) =
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let mut per_ticker: Vec<(Vec<f64>, Vec<f64>)> = vec![(Vec::new(), Vec::new()); recorded.len()];
+
+    for &cost in costs_bps {
+        engine.commission_rate = cost / 10_000.0;
+        let mut curves = Vec::with_capacity(recorded.len());
+        let (mut ticker_sharpes, mut level_trades) = (0.0, 0);
+        for (k, t) in recorded.iter().enumerate() {
+            let st = TickerState::new(INITIAL_CAPITAL_PER_STOCK, INITIAL_CAPITAL_PER_STOCK / t.bars[start].close);
+            let run = engine.simulate_ticker(py, &t.name, &t.bars, start, st, false, |i, _, _| Ok(t.signals[i - start]))?;
+            ticker_sharpes += run.metric.sharpe;
+            level_trades += run.metric.trades;
+            per_ticker[k].0.push(run.metric.roi_pct);
+            per_ticker[k].1.push(run.metric.sharpe);
+            curves.push(run.equity_curve);
+        }
+        let (dates, equity) = combine_equity_curves(&curves);
+        let base = INITIAL_CAPITAL_PER_STOCK * curves.len() as f64;
+        roi.push(equity.last().map_or(0.0, |last| (last / base - 1.0) * 100.0));
+        sharpe.push(engine.sharpe(&dates, &equity));
+        average_sharpe.push(ticker_sharpes / recorded.len() as f64);
+        drawdown.push(max_drawdown(&equity) * 100.0);
+        trades.push(level_trades);
+    }
+
+    summary.set_item("cost_bps", costs_bps.to_vec())?;
+    summary.set_item("total_roi_pct", roi)?;
+    summary.set_item("portfolio_sharpe", sharpe)?;
+    summary.set_item("average_sharpe", average_sharpe)?;
+    summary.set_item("max_drawdown_pct", drawdown)?;
+    summary.set_item("total_trades", trades)?;
+    let tickers = PyDict::new(py);
+    for (t, (roi_pct, sharpe)) in recorded.iter().zip(per_ticker) {
+        let curves = PyDict::new(py);
+        curves.set_item("roi_pct", roi_pct)?;
+        curves.set_item("sharpe", sharpe)?;
+        tickers.set_item(&t.name, curves)?;
+    }
+    summary.set_item("tickers", tickers)?;
+    Ok(summary.to_object(py))
+}
+
+/// Records the strategy's signals once and re-prices them at every cost level, restoring the
+/// engine's own commission afterwards.
+pub(super) fn run_sensitivity(engine: &mut BacktestEngine, py: Python<'_>, costs_bps: &[f64]) -> PyResult<PyObject> {
+    if costs_bps.is_empty() {
+        return Err(PyValueError::new_err("costs is empty"));
+    }
+    if let Some(c) = costs_bps.iter().find(|c| c.is_nan() || **c < 0.0) {
+        return Err(PyValueError::new_err(format!("costs must be >= 0 bps, got {}", c)));
+    }
+    let started = Instant::now();
+    let recorded = record(engine, py)?;
+    if recorded.is_empty() {
+        return Err(PyValueError::new_err(format!(
+            "no data file matching '{}' has more than history_size + 1 = {} bars",
+            BacktestEngine::data_pattern(&engine.data_folder), engine.history_size + 1
+        )));
+    }
+    log::info!("run_sensitivity: recorded signals of {} tickers in {:?}", recorded.len(), started.elapsed());
+
+    let own_rate = engine.commission_rate;
+    let result = sweep(engine, py, &recorded, costs_bps);
+    engine.commission_rate = own_rate;
+    result
+}
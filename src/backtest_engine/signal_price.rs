@@ -0,0 +1,47 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use super::Bar;
+
+pub(super) const SIGNAL_PRICE_NAMES: [&str; 6] = ["close", "adj_close", "open", "high", "low", "typical"];
+
+/// Which price of each bar goes into the history strategies decide from. Fills are priced
+/// separately by the fill model, so signals and executions can use different series.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum SignalPrice {
+    Close,
+    /// The data file's adjusted close, or the close when the file has none
+    AdjClose,
+    Open,
+    High,
+    Low,
+    /// (high + low + close) / 3
+    Typical,
+}
+
+impl SignalPrice {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "close" => Ok(SignalPrice::Close),
+            "adj_close" => Ok(SignalPrice::AdjClose),
+            "open" => Ok(SignalPrice::Open),
+            "high" => Ok(SignalPrice::High),
+            "low" => Ok(SignalPrice::Low),
+            "typical" => Ok(SignalPrice::Typical),
+            other => Err(PyValueError::new_err(format!(
+                "unknown signal price '{}', expected one of {}", other, SIGNAL_PRICE_NAMES.join(", ")
+            ))),
+        }
+    }
+
+    pub fn of(self, bar: &Bar) -> f64 {
+        match self {
+            SignalPrice::Close => bar.close,
+            SignalPrice::AdjClose => bar.adj_close,
+            SignalPrice::Open => bar.open,
+            SignalPrice::High => bar.high,
+            SignalPrice::Low => bar.low,
+            SignalPrice::Typical => (bar.high + bar.low + bar.close) / 3.0,
+        }
+    }
+}
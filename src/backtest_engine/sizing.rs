@@ -0,0 +1,152 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+use super::Bar;
+use crate::stats::{std_sample, TRADING_DAYS_PER_YEAR};
+
+pub(super) const SIZER_NAMES: [&str; 5] = ["full", "fixed_fraction", "kelly", "vol_target", "atr_target"];
+
+/// Closed-trade record a sizer can learn from: round trips won and lost so far and the sum of
+/// their returns (losses as positive numbers).
+pub(super) struct TradeRecord {
+    pub wins: i32,
+    pub losses: i32,
+    pub win_return_sum: f64,
+    pub loss_return_sum: f64,
+}
+
+/// Share of the available balance an entry at bar `i` invests, in [0, 1]. Only bars before `i`
+/// are looked at, so sizing never uses prices the strategy hasn't seen.
+pub(super) trait PositionSizer: Send + Sync {
+    fn fraction(&self, bars: &[Bar], i: usize, record: &TradeRecord) -> f64;
+}
+
+/// Invest the whole balance (the historical behaviour).
+struct FullSizer;
+
+impl PositionSizer for FullSizer {
+    fn fraction(&self, _bars: &[Bar], _i: usize, _record: &TradeRecord) -> f64 { 1.0 }
+}
+
+/// Invest a fixed share of the balance.
+struct FixedFraction {
+    fraction: f64,
+}
+
+impl PositionSizer for FixedFraction {
+    fn fraction(&self, _bars: &[Bar], _i: usize, _record: &TradeRecord) -> f64 { self.fraction }
+}
+
+/// Kelly fraction `W - (1 - W) / R` from the running win rate `W` and payoff ratio `R`
+/// (average win over average loss), scaled by `scale`. Until `min_trades` round trips have
+/// both wins and losses to estimate from, `fallback` is used.
+struct Kelly {
+    scale: f64,
+    min_trades: i32,
+    fallback: f64,
+}
+
+impl PositionSizer for Kelly {
+    fn fraction(&self, _bars: &[Bar], _i: usize, record: &TradeRecord) -> f64 {
+        let decided = record.wins + record.losses;
+        if decided < self.min_trades || record.wins == 0 || record.losses == 0 {
+            return self.fallback;
+        }
+        let win_rate = record.wins as f64 / decided as f64;
+        let avg_win = record.win_return_sum / record.wins as f64;
+        let avg_loss = record.loss_return_sum / record.losses as f64;
+        if avg_loss <= 0.0 {
+            return self.fallback;
+        }
+        let kelly = win_rate - (1.0 - win_rate) / (avg_win / avg_loss);
+        (kelly * self.scale).clamp(0.0, 1.0)
+    }
+}
+
+enum VolMeasure {
+    /// Sample std of close-to-close returns
+    ReturnStd,
+    /// Mean true range relative to the last close
+    Atr,
+}
+
+/// Scale the position so its annualized volatility matches `target`, measured over the
+/// `window` bars before the entry. Capped at the full balance.
+struct VolTarget {
+    target: f64,
+    window: usize,
+    measure: VolMeasure,
+}
+
+impl PositionSizer for VolTarget {
+    fn fraction(&self, bars: &[Bar], i: usize, _record: &TradeRecord) -> f64 {
+        let recent = &bars[i.saturating_sub(self.window + 1)..i];
+        if recent.len() < 3 {
+            return 1.0;
+        }
+        let per_bar = match self.measure {
+            VolMeasure::ReturnStd => {
+                let returns: Vec<f64> = recent.windows(2)
+                    .filter(|w| w[0].close > 0.0)
+                    .map(|w| w[1].close / w[0].close - 1.0)
+                    .collect();
+                std_sample(&returns)
+            }
+            VolMeasure::Atr => {
+                let true_range = |w: &[Bar]| (w[1].high - w[1].low)
+                    .max((w[1].high - w[0].close).abs())
+                    .max((w[1].low - w[0].close).abs());
+                let atr = recent.windows(2).map(true_range).sum::<f64>() / (recent.len() - 1) as f64;
+                let last = recent[recent.len() - 1].close;
+                if last > 0.0 { atr / last } else { 0.0 }
+            }
+        };
+        let annual = per_bar * TRADING_DAYS_PER_YEAR.sqrt();
+        if annual > f64::EPSILON { (self.target / annual).min(1.0) } else { 1.0 }
+    }
+}
+
+/// Builds the sizer `name` from `params`; unknown or out-of-range parameters are errors.
+pub(super) fn position_sizer(name: &str, params: &HashMap<String, f64>) -> PyResult<Box<dyn PositionSizer>> {
+    let allowed: &[&str] = match name {
+        "full" => &[],
+        "fixed_fraction" => &["fraction"],
+        "kelly" => &["scale", "min_trades", "fraction"],
+        "vol_target" | "atr_target" => &["target_vol", "window"],
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "position_sizer must be one of {:?}, got '{}'", SIZER_NAMES, other
+            )))
+        }
+    };
+    if let Some(key) = params.keys().find(|k| !allowed.contains(&k.as_str())) {
+        return Err(PyValueError::new_err(format!(
+            "unknown parameter '{}' for position_sizer '{}'; expected one of {:?}", key, name, allowed
+        )));
+    }
+    let param = |key: &str, default: f64| params.get(key).copied().unwrap_or(default);
+    let fraction = param("fraction", 1.0);
+    if !(0.0..=1.0).contains(&fraction) {
+        return Err(PyValueError::new_err(format!("sizer fraction must be in [0, 1], got {}", fraction)));
+    }
+    let window = param("window", 20.0);
+    if window < 2.0 {
+        return Err(PyValueError::new_err(format!("sizer window must be at least 2 bars, got {}", window)));
+    }
+
+    Ok(match name {
+        "full" => Box::new(FullSizer),
+        "fixed_fraction" => Box::new(FixedFraction { fraction }),
+        "kelly" => Box::new(Kelly {
+            scale: param("scale", 1.0),
+            min_trades: param("min_trades", 10.0) as i32,
+            fallback: fraction,
+        }),
+        _ => Box::new(VolTarget {
+            target: param("target_vol", 0.15),
+            window: window as usize,
+            measure: if name == "atr_target" { VolMeasure::Atr } else { VolMeasure::ReturnStd },
+        }),
+    })
+}
@@ -0,0 +1,184 @@
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::lots::TaxLots;
+
+const STATE_VERSION: u32 = 1;
+
+/// Position and accounting state of one ticker, enough to continue its simulation from the
+/// bar after `last_date`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct TickerState {
+    pub last_date: String,
+    pub balance: f64,
+    pub shares: f64,
+    pub in_position: bool,
+    pub entry_date: String,
+    pub entry_price: f64,
+    pub entry_cash: f64,
+    pub entry_commission: f64,
+    /// Uninvested cash while in a position, left over from lot rounding
+    #[serde(default)]
+    pub cash: f64,
+    pub lowest_since_entry: f64,
+    pub highest_since_entry: f64,
+    pub trades: i32,
+    pub wins: i32,
+    pub gross_wins: i32,
+    pub net_wins: i32,
+    pub breakevens: i32,
+    /// Buy-and-hold baseline shares, fixed at the first simulated bar.
+    pub bh_shares: f64,
+    /// Open FIFO tax lots and lifetime realized PnL
+    #[serde(default)]
+    pub lots: TaxLots,
+    #[serde(default)]
+    pub realized_pnl: f64,
+    /// Entry cost released and PnL booked by earlier partial sells of the open position
+    #[serde(default)]
+    pub scaled_out_cost: f64,
+    #[serde(default)]
+    pub scaled_out_gross_pnl: f64,
+    #[serde(default)]
+    pub scaled_out_pnl: f64,
+    /// Last round-trip exit and entries in the current session, for the entry rules
+    #[serde(default)]
+    pub last_exit_date: String,
+    #[serde(default)]
+    pub last_exit_loss: bool,
+    #[serde(default)]
+    pub entries_session: String,
+    #[serde(default)]
+    pub entries_today: usize,
+    /// Summed returns of winning and (as positive numbers) losing round trips, for Kelly sizing
+    #[serde(default)]
+    pub win_return_sum: f64,
+    #[serde(default)]
+    pub loss_return_sum: f64,
+    /// The last raw signal and how many bars in a row the strategy has emitted it, for the
+    /// signal filters
+    #[serde(default)]
+    pub streak_signal: i32,
+    #[serde(default)]
+    pub streak_bars: usize,
+    /// Trailing window of the equity overlay's shadow curve and its shadow position
+    #[serde(default)]
+    pub overlay_equity: Vec<f64>,
+    #[serde(default)]
+    pub overlay_in_position: bool,
+    /// Shares still to buy or sell after the volume limit cut earlier orders
+    #[serde(default)]
+    pub pending_buy: f64,
+    #[serde(default)]
+    pub pending_sell: f64,
+}
+
+impl TickerState {
+    pub fn new(initial_capital: f64, bh_shares: f64) -> Self {
+        TickerState {
+            last_date: String::new(),
+            balance: initial_capital,
+            shares: 0.0,
+            in_position: false,
+            entry_date: String::new(),
+            entry_price: 0.0,
+            entry_cash: 0.0,
+            entry_commission: 0.0,
+            cash: 0.0,
+            lowest_since_entry: 0.0,
+            highest_since_entry: 0.0,
+            trades: 0,
+            wins: 0,
+            gross_wins: 0,
+            net_wins: 0,
+            breakevens: 0,
+            bh_shares,
+            lots: TaxLots::default(),
+            realized_pnl: 0.0,
+            scaled_out_cost: 0.0,
+            scaled_out_gross_pnl: 0.0,
+            scaled_out_pnl: 0.0,
+            last_exit_date: String::new(),
+            last_exit_loss: false,
+            entries_session: String::new(),
+            entries_today: 0,
+            win_return_sum: 0.0,
+            loss_return_sum: 0.0,
+            streak_signal: 0,
+            streak_bars: 0,
+            overlay_equity: Vec::new(),
+            overlay_in_position: false,
+            pending_buy: 0.0,
+            pending_sell: 0.0,
+        }
+    }
+}
+
+/// Serialized engine state written by `run(save_state=...)` and read by `run(resume_state=...)`;
+/// also carried as `results["state"]` for `update`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(super) struct EngineState {
+    pub version: u32,
+    pub history_size: usize,
+    pub tickers: BTreeMap<String, TickerState>,
+    /// JSON text of whatever `strategy.get_state()` returned, if the strategy implements it.
+    pub strategy_state: Option<String>,
+}
+
+impl EngineState {
+    pub fn new(history_size: usize) -> Self {
+        EngineState { version: STATE_VERSION, history_size, ..Default::default() }
+    }
+
+    pub fn load(path: &str) -> PyResult<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| PyIOError::new_err(format!("failed to read engine state {}: {}", path, e)))?;
+        Self::from_json(&text)
+    }
+
+    pub fn save(&self, path: &str) -> PyResult<()> {
+        std::fs::write(path, self.to_json()?)
+            .map_err(|e| PyIOError::new_err(format!("failed to write engine state {}: {}", path, e)))
+    }
+
+    pub fn from_json(text: &str) -> PyResult<Self> {
+        let state: EngineState = serde_json::from_str(text)
+            .map_err(|e| PyValueError::new_err(format!("invalid engine state: {}", e)))?;
+        if state.version != STATE_VERSION {
+            return Err(PyValueError::new_err(format!(
+                "engine state has version {}, expected {}", state.version, STATE_VERSION
+            )));
+        }
+        Ok(state)
+    }
+
+    pub fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| PyValueError::new_err(format!("failed to serialize engine state: {}", e)))
+    }
+
+    /// Captures `strategy.get_state()` as JSON text, if the strategy implements it.
+    pub fn capture_strategy(&mut self, py: Python<'_>, strategy: &PyObject) -> PyResult<()> {
+        if !strategy.as_ref(py).hasattr("get_state")? {
+            return Ok(());
+        }
+        let state = strategy.call_method0(py, "get_state")?;
+        let text: String = py.import("json")?.call_method1("dumps", (state,))?.extract()?;
+        self.strategy_state = Some(text);
+        Ok(())
+    }
+
+    /// Hands a previously captured state back through `strategy.set_state(state)`.
+    pub fn restore_strategy(&self, py: Python<'_>, strategy: &PyObject) -> PyResult<()> {
+        let Some(text) = &self.strategy_state else { return Ok(()) };
+        if !strategy.as_ref(py).hasattr("set_state")? {
+            log::warn!("Saved state contains strategy state but the strategy has no set_state method");
+            return Ok(());
+        }
+        let state = py.import("json")?.call_method1("loads", (text,))?;
+        strategy.call_method1(py, "set_state", (state,))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+
+pub(super) const STRATEGY_SCOPE_NAMES: [&str; 3] = ["shared", "reset", "factory"];
+
+/// How the engine's strategy object relates to the tickers it trades.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum StrategyScope {
+    /// One object sees every ticker in turn, keeping whatever state it builds up
+    Shared,
+    /// One object, whose `reset(ticker)` is called before each ticker starts
+    Reset,
+    /// The strategy is a callable; `strategy(ticker)` builds a fresh object for each ticker
+    Factory,
+}
+
+impl StrategyScope {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "shared" => Ok(StrategyScope::Shared),
+            "reset" => Ok(StrategyScope::Reset),
+            "factory" => Ok(StrategyScope::Factory),
+            other => Err(PyValueError::new_err(format!(
+                "unknown strategy scope '{}', expected one of {}", other, STRATEGY_SCOPE_NAMES.join(", ")
+            ))),
+        }
+    }
+
+    /// Checks that `strategy` can be used in this scope.
+    pub fn check(self, py: Python<'_>, strategy: &PyObject) -> PyResult<()> {
+        let strategy = strategy.as_ref(py);
+        match self {
+            StrategyScope::Shared => Ok(()),
+            StrategyScope::Reset if strategy.hasattr("reset")? => Ok(()),
+            StrategyScope::Reset => Err(PyTypeError::new_err("strategy scope 'reset' needs a strategy with a reset(ticker) method")),
+            StrategyScope::Factory if strategy.is_callable() => Ok(()),
+            StrategyScope::Factory => Err(PyTypeError::new_err("strategy scope 'factory' needs a callable strategy(ticker)")),
+        }
+    }
+
+    /// Errors under "factory" for runs that trade one strategy object across tickers.
+    pub fn require_instance(self, method: &str) -> PyResult<()> {
+        if self == StrategyScope::Factory {
+            return Err(PyValueError::new_err(format!(
+                "{} trades one strategy across tickers and does not support strategy scope 'factory'", method
+            )));
+        }
+        Ok(())
+    }
+
+    /// The object that trades `ticker` from its first bar: the shared strategy (after its
+    /// `reset(ticker)` under "reset") or a new one from the factory.
+    pub fn instance(self, py: Python<'_>, strategy: &PyObject, ticker: &str) -> PyResult<PyObject> {
+        match self {
+            StrategyScope::Shared => Ok(strategy.clone_ref(py)),
+            StrategyScope::Reset => {
+                strategy.call_method1(py, "reset", (ticker,))?;
+                Ok(strategy.clone_ref(py))
+            }
+            StrategyScope::Factory => {
+                let instance = strategy.call1(py, (ticker,))?;
+                let built = instance.as_ref(py);
+                if !built.hasattr("step")? && !built.hasattr("step_batch")? {
+                    return Err(PyTypeError::new_err(format!(
+                        "strategy factory returned {} for {}, which has neither step nor step_batch",
+                        built.get_type().name()?, ticker
+                    )));
+                }
+                Ok(instance)
+            }
+        }
+    }
+
+    /// Whether `get_state` / `set_state` of the engine's strategy object carry the strategy's
+    /// state; under "factory" it lives in the per-ticker objects and is not saved.
+    pub fn saves_state(self) -> bool {
+        self != StrategyScope::Factory
+    }
+}
@@ -0,0 +1,100 @@
+use arrow_array::{ArrayRef, Float64Array, Int32Array, RecordBatch, StringArray};
+use arrow_ipc::writer::{FileWriter, StreamWriter};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Per-ticker results written as soon as each ticker finishes, for readers in other processes.
+pub(super) enum ResultStream {
+    /// One Arrow IPC stream, one record batch per ticker, flushed after every batch.
+    Stream(StreamWriter<BufWriter<File>>),
+    /// One Arrow IPC (Feather v2) file per ticker, renamed into place once complete.
+    Directory(PathBuf),
+}
+
+fn schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("ticker", DataType::Utf8, false),
+        Field::new("date", DataType::Utf8, false),
+        Field::new("close", DataType::Float64, false),
+        Field::new("signal", DataType::Int32, false),
+        Field::new("equity", DataType::Float64, false),
+        Field::new("realized_pnl", DataType::Float64, false),
+        Field::new("unrealized_pnl", DataType::Float64, false),
+        Field::new("position_size", DataType::Float64, false),
+        Field::new("cash", DataType::Float64, false),
+        Field::new("invested", DataType::Float64, false),
+    ]))
+}
+
+fn arrow_err(path: &Path, e: ArrowError) -> PyErr {
+    PyIOError::new_err(format!("writing {}: {}", path.display(), e))
+}
+
+impl ResultStream {
+    /// A path ending in a separator or naming an existing directory gets a file per ticker;
+    /// anything else becomes a single stream file.
+    pub fn open(path: &str) -> PyResult<Self> {
+        let p = Path::new(path);
+        if path.ends_with('/') || path.ends_with(std::path::MAIN_SEPARATOR) || p.is_dir() {
+            fs::create_dir_all(p).map_err(|e| PyIOError::new_err(format!("creating {}: {}", path, e)))?;
+            return Ok(ResultStream::Directory(p.to_path_buf()));
+        }
+        let file = File::create(p).map_err(|e| PyIOError::new_err(format!("creating {}: {}", path, e)))?;
+        let writer = StreamWriter::try_new(BufWriter::new(file), &schema()).map_err(|e| arrow_err(p, e))?;
+        Ok(ResultStream::Stream(writer))
+    }
+
+    pub fn write(&mut self, ticker: &str, detail: &PyDict) -> PyResult<()> {
+        let column = |key: &str| detail.get_item(key)
+            .ok_or_else(|| PyValueError::new_err(format!("details for {} have no '{}'", ticker, key)));
+        let dates: Vec<String> = column("dates")?.extract()?;
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(vec![ticker; dates.len()])),
+            Arc::new(StringArray::from(dates)),
+            Arc::new(Float64Array::from(column("closes")?.extract::<Vec<f64>>()?)),
+            Arc::new(Int32Array::from(column("signals")?.extract::<Vec<i32>>()?)),
+            Arc::new(Float64Array::from(column("balance_history")?.extract::<Vec<f64>>()?)),
+            Arc::new(Float64Array::from(column("realized_pnl")?.extract::<Vec<f64>>()?)),
+            Arc::new(Float64Array::from(column("unrealized_pnl")?.extract::<Vec<f64>>()?)),
+            Arc::new(Float64Array::from(column("position_size")?.extract::<Vec<f64>>()?)),
+            Arc::new(Float64Array::from(column("cash")?.extract::<Vec<f64>>()?)),
+            Arc::new(Float64Array::from(column("invested")?.extract::<Vec<f64>>()?)),
+        ];
+
+        match self {
+            ResultStream::Stream(writer) => {
+                let path = Path::new("result stream");
+                let batch = RecordBatch::try_new(schema(), columns).map_err(|e| arrow_err(path, e))?;
+                writer.write(&batch).map_err(|e| arrow_err(path, e))?;
+                writer.flush().map_err(|e| arrow_err(path, e))?;
+            }
+            ResultStream::Directory(dir) => {
+                // Written under a temporary name so readers never open a half-written file.
+                let path = dir.join(format!("{}.arrow", ticker));
+                let tmp = dir.join(format!(".{}.arrow.tmp", ticker));
+                let batch = RecordBatch::try_new(schema(), columns).map_err(|e| arrow_err(&path, e))?;
+                let file = File::create(&tmp).map_err(|e| PyIOError::new_err(format!("creating {}: {}", tmp.display(), e)))?;
+                let mut writer = FileWriter::try_new(BufWriter::new(file), &schema()).map_err(|e| arrow_err(&tmp, e))?;
+                writer.write(&batch).map_err(|e| arrow_err(&tmp, e))?;
+                writer.finish().map_err(|e| arrow_err(&tmp, e))?;
+                drop(writer);
+                fs::rename(&tmp, &path).map_err(|e| PyIOError::new_err(format!("renaming {}: {}", tmp.display(), e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the end-of-stream marker; per-ticker files are already complete.
+    pub fn finish(self) -> PyResult<()> {
+        if let ResultStream::Stream(mut writer) = self {
+            writer.finish().map_err(|e| arrow_err(Path::new("result stream"), e))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// Exchange trading constraints for one symbol. Zero means unconstrained.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct SymbolSpec {
+    /// Quantities are whole multiples of this
+    pub lot_size: f64,
+    /// Fill prices are multiples of this, rounded against the trader
+    pub tick_size: f64,
+    /// Smallest order value accepted
+    pub min_notional: f64,
+}
+
+impl SymbolSpec {
+    pub fn from_map(ticker: &str, fields: &HashMap<String, f64>) -> PyResult<Self> {
+        let mut spec = SymbolSpec::default();
+        for (key, &value) in fields {
+            if !value.is_finite() || value < 0.0 {
+                return Err(PyValueError::new_err(format!("{} for {} must be finite and >= 0, got {}", key, ticker, value)));
+            }
+            match key.as_str() {
+                "lot_size" => spec.lot_size = value,
+                "tick_size" => spec.tick_size = value,
+                "min_notional" => spec.min_notional = value,
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "unknown symbol constraint '{}' for {}; expected lot_size, tick_size or min_notional", other, ticker
+                    )))
+                }
+            }
+        }
+        Ok(spec)
+    }
+
+    /// Prices already on a tick keep it, within the same epsilon as `round_quantity`.
+    pub fn round_buy_price(&self, price: f64) -> f64 {
+        if self.tick_size > 0.0 { (price / self.tick_size - 1e-9).ceil() * self.tick_size } else { price }
+    }
+
+    pub fn round_sell_price(&self, price: f64) -> f64 {
+        if self.tick_size > 0.0 { (price / self.tick_size + 1e-9).floor() * self.tick_size } else { price }
+    }
+
+    /// The spec with whole-share lots where it sets no lot size of its own.
+    pub fn whole_shares(self) -> Self {
+        if self.lot_size == 0.0 { SymbolSpec { lot_size: 1.0, ..self } } else { self }
+    }
+
+    /// Largest whole number of lots not exceeding `quantity`.
+    pub fn round_quantity(&self, quantity: f64) -> f64 {
+        // The epsilon keeps exact multiples from flooring one lot short after division.
+        if self.lot_size > 0.0 { (quantity / self.lot_size + 1e-9).floor() * self.lot_size } else { quantity }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(lot_size: f64, tick_size: f64) -> SymbolSpec {
+        SymbolSpec { lot_size, tick_size, min_notional: 0.0 }
+    }
+
+    #[test]
+    fn quantities_round_down_to_whole_lots() {
+        assert_eq!(spec(100.0, 0.0).round_quantity(250.0), 200.0);
+        assert_eq!(spec(100.0, 0.0).round_quantity(99.9), 0.0);
+        assert_eq!(spec(0.0, 0.0).round_quantity(12.345), 12.345);
+    }
+
+    #[test]
+    fn exact_multiples_keep_their_last_lot() {
+        assert!((spec(0.1, 0.0).round_quantity(0.3) - 0.3).abs() < 1e-12);
+        assert_eq!(spec(1.0, 0.0).round_quantity(7.0), 7.0);
+    }
+
+    #[test]
+    fn whole_shares_floor_fractions_but_keep_lot_sizes() {
+        assert_eq!(spec(0.0, 0.0).whole_shares().round_quantity(12.7), 12.0);
+        assert_eq!(spec(0.0, 0.0).whole_shares().round_quantity(0.4), 0.0);
+        assert_eq!(spec(100.0, 0.0).whole_shares().round_quantity(250.0), 200.0);
+        assert_eq!(spec(0.0, 0.05).whole_shares().tick_size, 0.05);
+    }
+
+    #[test]
+    fn prices_round_against_the_trader() {
+        let s = spec(0.0, 0.05);
+        assert!((s.round_buy_price(10.01) - 10.05).abs() < 1e-12);
+        assert!((s.round_sell_price(10.04) - 10.0).abs() < 1e-12);
+        assert_eq!(spec(0.0, 0.0).round_buy_price(10.01), 10.01);
+    }
+
+    #[test]
+    fn prices_on_a_tick_keep_it() {
+        assert!((spec(0.0, 0.1).round_sell_price(2.3) - 2.3).abs() < 1e-12);
+        assert!((spec(0.0, 0.1).round_buy_price(2.3) - 2.3).abs() < 1e-12);
+        assert!((spec(0.0, 0.05).round_sell_price(4.35) - 4.35).abs() < 1e-12);
+        assert!((spec(0.0, 0.05).round_buy_price(4.35) - 4.35).abs() < 1e-12);
+        assert!((spec(0.0, 0.01).round_buy_price(0.07) - 0.07).abs() < 1e-12);
+    }
+
+    #[test]
+    fn from_map_rejects_unknown_and_negative_fields() {
+        let fields = |k: &str, v: f64| HashMap::from([(k.to_string(), v)]);
+        assert_eq!(SymbolSpec::from_map("X", &fields("lot_size", 10.0)).unwrap().lot_size, 10.0);
+        assert!(SymbolSpec::from_map("X", &fields("lot", 10.0)).is_err());
+        assert!(SymbolSpec::from_map("X", &fields("tick_size", -1.0)).is_err());
+        assert!(SymbolSpec::from_map("X", &fields("min_notional", f64::NAN)).is_err());
+        assert!(SymbolSpec::from_map("X", &fields("tick_size", f64::INFINITY)).is_err());
+        assert!(SymbolSpec::from_map("X", &fields("lot_size", f64::INFINITY)).is_err());
+    }
+}
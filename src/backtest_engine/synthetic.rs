@@ -0,0 +1,70 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use super::{BacktestEngine, Bar};
+
+/// An instrument priced as a fixed linear combination of tickers, e.g. 2×A − 1×B, and traded
+/// as one unit.
+pub(super) struct Synthetic {
+    legs: Vec<(String, f64)>,
+}
+
+impl Synthetic {
+    pub fn new(name: &str, weights: HashMap<String, f64>) -> PyResult<Self> {
+        let mut legs: Vec<(String, f64)> = weights.into_iter().filter(|(_, w)| *w != 0.0).collect();
+        if legs.is_empty() {
+            return Err(PyValueError::new_err(format!("synthetic '{}' needs at least one leg with a non-zero weight", name)));
+        }
+        if let Some((leg, w)) = legs.iter().find(|(_, w)| !w.is_finite()) {
+            return Err(PyValueError::new_err(format!("synthetic '{}' has weight {} for '{}'", name, w, leg)));
+        }
+        legs.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(Synthetic { legs })
+    }
+
+    /// Bars on the dates every leg trades, in the first leg's order. Short legs swap high and
+    /// low, so the combined high and low bound the combined close. Fails when a leg file can't
+    /// be read or the combined price is not positive on some bar, since the engine's returns
+    /// and share counts need a positive price. The volume is the number of synthetic units the
+    /// thinnest leg's volume would cover.
+    pub fn bars(&self, engine: &BacktestEngine, data_folder: &str) -> Result<Vec<Bar>, Error> {
+        let mut leg_bars: Vec<HashMap<String, Bar>> = Vec::with_capacity(self.legs.len());
+        let mut dates: Vec<(String, String)> = Vec::new();
+        for (k, (leg, _)) in self.legs.iter().enumerate() {
+            let path = Path::new(data_folder).join(format!("{}_meso.csv", leg));
+            let bars = engine.load_bars(path.to_str().unwrap(), leg)
+                .map_err(|e| Error::new(e.kind(), format!("leg {}: {}", leg, e)))?;
+            if k == 0 {
+                dates = bars.iter().map(|b| (b.date.clone(), b.session.clone())).collect();
+            }
+            leg_bars.push(bars.into_iter().map(|b| (b.date.clone(), b)).collect());
+        }
+
+        let mut out = Vec::with_capacity(dates.len());
+        'dates: for (date, session) in dates {
+            let (mut open, mut high, mut low, mut close, mut adj_close, mut volume) = (0.0, 0.0, 0.0, 0.0, 0.0, f64::INFINITY);
+            for ((_, weight), bars) in self.legs.iter().zip(&leg_bars) {
+                let Some(bar) = bars.get(&date) else { continue 'dates };
+                open += weight * bar.open;
+                close += weight * bar.close;
+                adj_close += weight * bar.adj_close;
+                volume = volume.min(bar.volume / weight.abs());
+                if *weight > 0.0 {
+                    high += weight * bar.high;
+                    low += weight * bar.low;
+                } else {
+                    high += weight * bar.low;
+                    low += weight * bar.high;
+                }
+            }
+            if close <= 0.0 {
+                return Err(Error::new(ErrorKind::InvalidData, format!("combined close is {} on {}", close, date)));
+            }
+            out.push(Bar { date, session, open, high, low, close, volume, adj_close, delisted: false });
+        }
+        Ok(out)
+    }
+}
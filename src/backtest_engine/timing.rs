@@ -0,0 +1,52 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::time::Duration;
+
+/// Where one ticker's run spent its time.
+#[derive(Debug, Clone, Default)]
+pub(super) struct TickerTiming {
+    /// Reading and parsing the data file
+    pub load: Duration,
+    /// Inside `strategy.step` / `step_batch`, including argument conversion
+    pub strategy: Duration,
+    /// The engine's own bar loop: fills, accounting and bookkeeping
+    pub fills: Duration,
+    /// Metrics and the result arrays
+    pub metrics: Duration,
+    /// Calls into the Python strategy
+    pub ffi_calls: usize,
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+impl TickerTiming {
+    pub fn to_py<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let d = PyDict::new(py);
+        d.set_item("load_ms", ms(self.load))?;
+        d.set_item("strategy_ms", ms(self.strategy))?;
+        d.set_item("engine_ms", ms(self.fills))?;
+        d.set_item("metrics_ms", ms(self.metrics))?;
+        d.set_item("ffi_calls", self.ffi_calls)?;
+        Ok(d)
+    }
+}
+
+/// Totals over all tickers, plus the share of the measured time spent in the strategy and
+/// the mean time per strategy call.
+pub(super) fn profile_to_py<'py>(py: Python<'py>, timings: &[(String, TickerTiming)]) -> PyResult<&'py PyDict> {
+    let mut total = TickerTiming::default();
+    for (_, t) in timings {
+        total.load += t.load;
+        total.strategy += t.strategy;
+        total.fills += t.fills;
+        total.metrics += t.metrics;
+        total.ffi_calls += t.ffi_calls;
+    }
+    let measured = total.load + total.strategy + total.fills + total.metrics;
+    let d = total.to_py(py)?;
+    d.set_item("strategy_share_pct", if measured > Duration::ZERO { ms(total.strategy) / ms(measured) * 100.0 } else { 0.0 })?;
+    d.set_item("ms_per_ffi_call", if total.ffi_calls > 0 { ms(total.strategy) / total.ffi_calls as f64 } else { 0.0 })?;
+    Ok(d)
+}
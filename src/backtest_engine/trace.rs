@@ -0,0 +1,94 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::PyArray1;
+
+/// What the engine did with a bar's signal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum BarAction {
+    Hold,
+    Buy,
+    Sell,
+    /// A sell that closed only part of the position.
+    ScaleOut,
+    /// A buy that added to an open position.
+    ScaleIn,
+    /// A signal refused by the engine's entry rules or signal filters.
+    Overridden,
+    /// A non-zero signal that does not apply in the current state (buy while long, sell while flat).
+    Ignored,
+    /// `strategy.step` raised or returned something that is not a valid signal; treated as 0.
+    StrategyError,
+}
+
+impl BarAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BarAction::Hold => "hold",
+            BarAction::Buy => "buy",
+            BarAction::Sell => "sell",
+            BarAction::ScaleOut => "scale_out",
+            BarAction::ScaleIn => "scale_in",
+            BarAction::Overridden => "overridden",
+            BarAction::Ignored => "ignored",
+            BarAction::StrategyError => "strategy_error",
+        }
+    }
+}
+
+/// Column-oriented per-bar debug trace, recorded only when `run(trace=True)`.
+#[derive(Default)]
+pub(super) struct BarTrace {
+    history_last: Vec<f64>,
+    history_mean: Vec<f64>,
+    history_min: Vec<f64>,
+    history_max: Vec<f64>,
+    signal: Vec<i32>,
+    action: Vec<BarAction>,
+    position_before: Vec<i32>,
+    position_after: Vec<i32>,
+    value_before: Vec<f64>,
+    value_after: Vec<f64>,
+}
+
+impl BarTrace {
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn record(
+        &mut self,
+        history: &[f64],
+        signal: i32,
+        action: BarAction,
+        position_before: i32,
+        position_after: i32,
+        value_before: f64,
+        value_after: f64,
+    ) {
+        let n = history.len().max(1) as f64;
+        self.history_last.push(history.last().copied().unwrap_or(f64::NAN));
+        self.history_mean.push(history.iter().sum::<f64>() / n);
+        self.history_min.push(history.iter().cloned().fold(f64::INFINITY, f64::min));
+        self.history_max.push(history.iter().cloned().fold(f64::NEG_INFINITY, f64::max));
+        self.signal.push(signal);
+        self.action.push(action);
+        self.position_before.push(position_before);
+        self.position_after.push(position_after);
+        self.value_before.push(value_before);
+        self.value_after.push(value_after);
+    }
+
+    pub(super) fn into_py(self, py: Python<'_>) -> PyResult<&PyDict> {
+        let value_change: Vec<f64> = self.value_after.iter().zip(self.value_before.iter()).map(|(a, b)| a - b).collect();
+        let out = PyDict::new(py);
+        out.set_item("history_last", PyArray1::from_vec(py, self.history_last))?;
+        out.set_item("history_mean", PyArray1::from_vec(py, self.history_mean))?;
+        out.set_item("history_min", PyArray1::from_vec(py, self.history_min))?;
+        out.set_item("history_max", PyArray1::from_vec(py, self.history_max))?;
+        out.set_item("signal", PyArray1::from_vec(py, self.signal))?;
+        out.set_item("action", self.action.iter().map(|a| a.as_str()).collect::<Vec<_>>())?;
+        out.set_item("position_before", PyArray1::from_vec(py, self.position_before))?;
+        out.set_item("position_after", PyArray1::from_vec(py, self.position_after))?;
+        out.set_item("value_before", PyArray1::from_vec(py, self.value_before))?;
+        out.set_item("value_after", PyArray1::from_vec(py, self.value_after))?;
+        out.set_item("value_change", PyArray1::from_vec(py, value_change))?;
+        Ok(out)
+    }
+}
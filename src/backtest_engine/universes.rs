@@ -0,0 +1,108 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::Path;
+
+use super::state::EngineState;
+use super::{assemble, BacktestEngine, DetailSinks, FileCounts, RunOutput};
+
+/// `portfolio_summary` entries lined up side by side in the comparison table.
+const COMPARED_KEYS: [&str; 8] = [
+    "stocks_processed",
+    "total_roi_pct",
+    "portfolio_sharpe",
+    "average_sharpe",
+    "win_rate_pct",
+    "total_trades",
+    "average_alpha_pct",
+    "average_gross_exposure_pct",
+];
+
+/// (name, folder) pairs from a dict of name -> folder, or from a list of folders named after
+/// their last path component.
+fn parse(universes: &PyAny) -> PyResult<Vec<(String, String)>> {
+    if let Ok(map) = universes.downcast::<PyDict>() {
+        return map.iter().map(|(name, folder)| Ok((name.extract()?, folder.extract()?))).collect();
+    }
+    let folders: Vec<String> = universes.extract()
+        .map_err(|_| PyValueError::new_err("universes must be a list of data folders or a dict of name -> folder"))?;
+    let mut out: Vec<(String, String)> = Vec::with_capacity(folders.len());
+    for folder in folders {
+        let name = Path::new(folder.trim_end_matches('/'))
+            .file_name()
+            .map_or_else(|| folder.clone(), |n| n.to_string_lossy().into_owned());
+        if out.iter().any(|(n, _)| *n == name) {
+            return Err(PyValueError::new_err(format!(
+                "two folders are named '{}'; pass a dict of name -> folder instead", name
+            )));
+        }
+        out.push((name, folder));
+    }
+    Ok(out)
+}
+
+impl FileCounts {
+    fn add(&mut self, other: &FileCounts) {
+        self.skipped_read_error += other.skipped_read_error;
+        self.skipped_too_short += other.skipped_too_short;
+        self.strategy_errors += other.strategy_errors;
+        self.up_to_date += other.up_to_date;
+    }
+}
+
+/// Runs the strategy over each universe's data folder, in the given order, and assembles a
+/// `run`-style result per universe plus one over every universe's tickers together.
+pub(super) fn run_universes(engine: &BacktestEngine, py: Python<'_>, universes: &PyAny, trace: bool) -> PyResult<PyObject> {
+    let universes = parse(universes)?;
+    if universes.is_empty() {
+        return Err(PyValueError::new_err("universes is empty"));
+    }
+
+    let per_universe = PyDict::new(py);
+    let mut combined = RunOutput {
+        details: PyDict::new(py),
+        metrics: Vec::new(),
+        equity_curves: Vec::new(),
+        position_curves: Vec::new(),
+        files: FileCounts::default(),
+        warnings: Vec::new(),
+        state: EngineState::new(engine.history_size),
+        spilled: None,
+        timings: Vec::new(),
+        invalid: Vec::new(),
+        errors: Vec::new(),
+    };
+    for (name, folder) in &universes {
+        let out = engine.simulate(py, &engine.strategy, folder, trace, &EngineState::new(engine.history_size), DetailSinks::default())?;
+        combined.metrics.extend(out.metrics.iter().cloned());
+        combined.equity_curves.extend(out.equity_curves.iter().cloned());
+        combined.position_curves.extend(out.position_curves.iter().cloned());
+        combined.files.add(&out.files);
+        combined.warnings.extend(out.warnings.iter().map(|w| format!("{}: {}", name, w)));
+        combined.invalid.extend(out.invalid.iter().map(|(ticker, reason)| (format!("{}/{}", name, ticker), reason.clone())));
+        combined.errors.extend(out.errors.iter().map(|(ticker, e)| (format!("{}/{}", name, ticker), e.clone())));
+        combined.timings.extend(out.timings.iter().map(|(ticker, t)| (format!("{}/{}", name, ticker), t.clone())));
+        per_universe.set_item(name, assemble(engine, py, out)?)?;
+    }
+
+    // Details and state stay with their universe, where ticker names cannot collide.
+    let combined = assemble(engine, py, combined)?;
+    let combined: &PyDict = combined.as_ref(py).downcast()?;
+    combined.del_item("details")?;
+    combined.del_item("state")?;
+
+    let comparison = PyDict::new(py);
+    comparison.set_item("universe", universes.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>())?;
+    for key in COMPARED_KEYS {
+        let column = universes.iter()
+            .map(|(name, _)| per_universe.get_item(name).unwrap().get_item("portfolio_summary")?.get_item(key)?.extract())
+            .collect::<PyResult<Vec<f64>>>()?;
+        comparison.set_item(key, column)?;
+    }
+
+    let out = PyDict::new(py);
+    out.set_item("universes", per_universe)?;
+    out.set_item("combined", combined)?;
+    out.set_item("comparison", comparison)?;
+    Ok(out.to_object(py))
+}
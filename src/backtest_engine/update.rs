@@ -0,0 +1,284 @@
+use numpy::PyArray1;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use super::state::EngineState;
+use super::regimes::segment_metrics_to_py;
+use super::{
+    assemble, drawdowns_to_py, pct_changes, return_stats_to_py, BacktestEngine, DetailSinks,
+    RunOutput, StockMetric,
+};
+use crate::stats::{max_drawdown, mean, underwater_curve, TRADING_DAYS_PER_YEAR};
+
+/// Per-bar arrays in a ticker's details that are appended as-is.
+const SERIES_KEYS: [&str; 5] = ["closes", "signals", "balance_history", "realized_pnl", "unrealized_pnl"];
+/// Bar positions in a ticker's details that are shifted by the length of the earlier result.
+/// Missing keys (results from before the key existed) are read as empty.
+const INDEX_KEYS: [&str; 6] = [
+    "buy_indices", "sell_win_indices", "sell_loss_indices", "sell_breakeven_indices", "scale_out_indices",
+    "scale_in_indices",
+];
+/// Per-bar series present only with some engine options, or missing from older results.
+const OPTIONAL_SERIES_KEYS: [&str; 5] = ["sessions", "regimes", "position_size", "cash", "invested"];
+/// Optional dicts of per-bar columns, appended when both results carry them.
+const COLUMN_KEYS: [&str; 3] = ["trace", "patterns", "equity_overlay"];
+/// Dicts of per-event columns whose `index` column holds bar positions.
+const INDEXED_COLUMN_KEYS: [&str; 2] = ["overridden_signals", "orders"];
+
+pub(super) fn update(
+    engine: &BacktestEngine,
+    py: Python<'_>,
+    results: &PyDict,
+    data_folder: &str,
+    trace_enabled: bool,
+) -> PyResult<PyObject> {
+    let state_json: String = item(results, "state")?.extract()?;
+    let state = EngineState::from_json(&state_json)?;
+    engine.check_state(&state)?;
+    if engine.strategy_scope.saves_state() {
+        state.restore_strategy(py, &engine.strategy)?;
+    }
+
+    // Appending needs every bar of every ticker, which a detail budget may have given up.
+    let budget_error = || PyValueError::new_err("results with downsampled, filtered or spilled details cannot be updated");
+    let old_details: &PyDict = item(results, "details")?.downcast().map_err(|_| budget_error())?;
+    let old_metrics = item(results, "metrics")?
+        .downcast::<PyList>()?
+        .iter()
+        .map(|m| StockMetric::from_py(m.downcast()?))
+        .collect::<PyResult<Vec<_>>>()?;
+    for metric in &old_metrics {
+        match old_details.get_item(metric.ticker.as_str()) {
+            Some(detail) if !detail.downcast::<PyDict>()?.contains("bar_index")? => {}
+            _ => return Err(budget_error()),
+        }
+    }
+
+    let mut out = engine.simulate(py, &engine.strategy, data_folder, trace_enabled, &state, DetailSinks::default())?;
+    if engine.strategy_scope.saves_state() {
+        out.state.capture_strategy(py, &engine.strategy)?;
+    }
+
+    let details = PyDict::new(py);
+    let mut metrics = Vec::with_capacity(old_metrics.len() + out.metrics.len());
+    let mut equity_curves = Vec::with_capacity(old_metrics.len() + out.metrics.len());
+    let mut position_curves = Vec::with_capacity(old_metrics.len() + out.metrics.len());
+
+    // Tickers without new bars keep their earlier result; the rest are merged in place.
+    for old_metric in &old_metrics {
+        let old_detail: &PyDict = item(old_details, &old_metric.ticker)?.downcast()?;
+        let new_pos = out.metrics.iter().position(|m| m.ticker == old_metric.ticker);
+        let (detail, metric, curve) = match new_pos {
+            Some(k) => {
+                let new_detail: &PyDict = item(out.details, &old_metric.ticker)?.downcast()?;
+                merge_ticker(engine, py, old_detail, old_metric, new_detail, &out.metrics[k])?
+            }
+            None => (old_detail, old_metric.clone(), equity_curve(old_detail)?),
+        };
+        details.set_item(&metric.ticker, detail)?;
+        position_curves.push(position_curve(detail, curve.0.len())?);
+        metrics.push(metric);
+        equity_curves.push(curve);
+    }
+
+    // Tickers that first appear in the new folder
+    for ((metric, curve), positions) in out.metrics.iter().zip(&out.equity_curves).zip(&out.position_curves) {
+        if old_metrics.iter().any(|m| m.ticker == metric.ticker) {
+            continue;
+        }
+        details.set_item(&metric.ticker, item(out.details, &metric.ticker)?)?;
+        metrics.push(metric.clone());
+        equity_curves.push(curve.clone());
+        position_curves.push(positions.clone());
+    }
+
+    out = RunOutput { details, metrics, equity_curves, position_curves, ..out };
+    assemble(engine, py, out)
+}
+
+fn item<'py>(dict: &'py PyDict, key: &str) -> PyResult<&'py PyAny> {
+    dict.get_item(key)
+        .ok_or_else(|| PyValueError::new_err(format!("results has no '{}'; pass a dict returned by run() or update()", key)))
+}
+
+/// Dates and equity of one ticker.
+type EquityCurve = (Vec<String>, Vec<f64>);
+
+fn equity_curve(detail: &PyDict) -> PyResult<EquityCurve> {
+    Ok((item(detail, "dates")?.extract()?, item(detail, "balance_history")?.extract()?))
+}
+
+/// Position values of one ticker; results from before `invested` existed count as flat.
+fn position_curve(detail: &PyDict, n_bars: usize) -> PyResult<Vec<f64>> {
+    Ok(detail.get_item("invested").map(|v| v.extract()).transpose()?.unwrap_or_else(|| vec![0.0; n_bars]))
+}
+
+/// Concatenates two lists or two numpy arrays.
+fn concat<'py>(py: Python<'py>, a: &'py PyAny, b: &'py PyAny) -> PyResult<&'py PyAny> {
+    if a.downcast::<PyList>().is_ok() {
+        a.call_method1("__add__", (b,))
+    } else {
+        py.import("numpy")?.getattr("concatenate")?.call1(((a, b),))
+    }
+}
+
+/// Appends the columns present in both dicts.
+fn concat_columns<'py>(py: Python<'py>, a: &'py PyDict, b: &'py PyDict) -> PyResult<&'py PyDict> {
+    let out = PyDict::new(py);
+    for (key, new_values) in b.iter() {
+        if let Some(old_values) = a.get_item(key) {
+            out.set_item(key, concat(py, old_values, new_values)?)?;
+        }
+    }
+    Ok(out)
+}
+
+/// Round trips in a trade ledger; rows of partial exits don't count.
+fn closed_trips(trades: &PyDict) -> PyResult<usize> {
+    match trades.get_item("closes_position") {
+        Some(flags) => Ok(flags.extract::<Vec<bool>>()?.into_iter().filter(|&c| c).count()),
+        None => item(trades, "exit_index")?.len(),
+    }
+}
+
+fn merge_ticker<'py>(
+    engine: &BacktestEngine,
+    py: Python<'py>,
+    old: &'py PyDict,
+    old_metric: &StockMetric,
+    new: &'py PyDict,
+    new_metric: &StockMetric,
+) -> PyResult<(&'py PyDict, StockMetric, EquityCurve)> {
+    let merged = PyDict::new(py);
+    let (old_dates, old_equity) = equity_curve(old)?;
+    let (new_dates, new_equity) = equity_curve(new)?;
+    let offset = old_dates.len();
+    let dates = [old_dates, new_dates].concat();
+    let equity = [old_equity.clone(), new_equity.clone()].concat();
+
+    merged.set_item("dates", &dates)?;
+    for key in SERIES_KEYS {
+        merged.set_item(key, concat(py, item(old, key)?, item(new, key)?)?)?;
+    }
+    for key in INDEX_KEYS {
+        let mut indices: Vec<usize> = old.get_item(key).map(|v| v.extract()).transpose()?.unwrap_or_default();
+        let new_indices: Vec<usize> = new.get_item(key).map(|v| v.extract()).transpose()?.unwrap_or_default();
+        indices.extend(new_indices.into_iter().map(|i| i + offset));
+        merged.set_item(key, PyArray1::from_vec(py, indices))?;
+    }
+    for key in OPTIONAL_SERIES_KEYS {
+        match (old.get_item(key), new.get_item(key)) {
+            (Some(a), Some(b)) => merged.set_item(key, concat(py, a, b)?)?,
+            (None, None) => {}
+            _ => log::warn!("Dropping '{}' for {}: only one of the merged results has it", key, new_metric.ticker),
+        }
+    }
+    for key in COLUMN_KEYS {
+        match (old.get_item(key), new.get_item(key)) {
+            (Some(a), Some(b)) => merged.set_item(key, concat_columns(py, a.downcast()?, b.downcast()?)?)?,
+            (None, None) => {}
+            _ => log::warn!("Dropping '{}' for {}: only one of the merged results has it", key, new_metric.ticker),
+        }
+    }
+
+    // Refused signals and orders are appended with their bar positions shifted like the index arrays.
+    for key in INDEXED_COLUMN_KEYS {
+        match (old.get_item(key), new.get_item(key)) {
+            (Some(a), Some(b)) => {
+                let columns = concat_columns(py, a.downcast()?, b.downcast()?)?;
+                let mut index: Vec<usize> = item(a.downcast()?, "index")?.extract()?;
+                let new_index: Vec<usize> = item(b.downcast()?, "index")?.extract()?;
+                index.extend(new_index.into_iter().map(|i| i + offset));
+                columns.set_item("index", PyArray1::from_vec(py, index))?;
+                merged.set_item(key, columns)?;
+            }
+            (None, None) => {}
+            _ => log::warn!("Dropping '{}' for {}: only one of the merged results has it", key, new_metric.ticker),
+        }
+    }
+
+    let old_trades: &PyDict = item(old, "trades")?.downcast()?;
+    let new_trades: &PyDict = item(new, "trades")?.downcast()?;
+    let old_closed = closed_trips(old_trades)?;
+    let new_closed = closed_trips(new_trades)?;
+    let trades = concat_columns(py, old_trades, new_trades)?;
+
+    // A trade opened before the update has entry index 0 in the new result; locate its entry
+    // bar in the combined dates instead.
+    let mut entry_index: Vec<usize> = item(old_trades, "entry_index")?.extract()?;
+    let new_entries: Vec<usize> = item(new_trades, "entry_index")?.extract()?;
+    let new_entry_dates: Vec<String> = item(new_trades, "entry_date")?.extract()?;
+    for (i, date) in new_entries.into_iter().zip(&new_entry_dates) {
+        let shifted = if *date < dates[offset] {
+            dates.iter().position(|d| d == date).unwrap_or(offset)
+        } else { i + offset };
+        entry_index.push(shifted);
+    }
+    let mut exit_index: Vec<usize> = item(old_trades, "exit_index")?.extract()?;
+    let new_exits: Vec<usize> = item(new_trades, "exit_index")?.extract()?;
+    exit_index.extend(new_exits.into_iter().map(|i| i + offset));
+    let trades_entry_index = entry_index.clone();
+    trades.set_item("entry_index", PyArray1::from_vec(py, entry_index))?;
+    trades.set_item("exit_index", PyArray1::from_vec(py, exit_index))?;
+    if let Some(labels) = merged.get_item("regimes") {
+        let labels: Vec<Option<String>> = labels.extract()?;
+        let labels: Vec<Option<&str>> = labels.iter().map(|l| l.as_deref()).collect();
+        let outcomes: Vec<String> = item(trades, "outcome")?.extract()?;
+        let entries: Vec<(usize, bool)> = trades_entry_index.iter().zip(&outcomes).map(|(&e, o)| (e, o == "win")).collect();
+        merged.set_item("regime_metrics", segment_metrics_to_py(py, &labels, &equity, Some(&entries))?)?;
+    }
+    merged.set_item("trades", trades)?;
+    merged.set_item("tax_lots", concat_columns(py, item(old, "tax_lots")?.downcast()?, item(new, "tax_lots")?.downcast()?)?)?;
+
+    let returns = pct_changes(&equity);
+    merged.set_item("return_stats", return_stats_to_py(py, &returns)?)?;
+    merged.set_item("returns", PyArray1::from_vec(py, returns))?;
+    merged.set_item("drawdowns", drawdowns_to_py(py, &equity, &dates)?)?;
+    merged.set_item("underwater", PyArray1::from_vec(py, underwater_curve(&equity)))?;
+
+    // Counters, ROI and buy-and-hold are already lifetime figures in the new result; the
+    // per-bar averages are recombined weighted by bars (holding period by closed trades).
+    let n_old = old_metric.n_periods as f64;
+    let n_new = new_metric.n_periods as f64;
+    let n = n_old + n_new;
+    let weighted = |a: f64, b: f64| if n > 0.0 { (a * n_old + b * n_new) / n } else { 0.0 };
+    // Turnover is traded notional over average equity per year, so recombine the notionals.
+    let notional = |m: &StockMetric, eq: &Vec<f64>| m.annual_turnover * mean(eq) * m.n_periods as f64 / TRADING_DAYS_PER_YEAR;
+    let avg_equity = mean(&equity);
+    let annual_turnover = if n > 0.0 && avg_equity > 0.0 {
+        (notional(old_metric, &old_equity) + notional(new_metric, &new_equity)) / avg_equity * (TRADING_DAYS_PER_YEAR / n)
+    } else { 0.0 };
+    let closed = old_closed + new_closed;
+    let avg_holding_bars = if closed > 0 {
+        (old_metric.avg_holding_bars * old_closed as f64 + new_metric.avg_holding_bars * new_closed as f64) / closed as f64
+    } else { 0.0 };
+
+    let (beta, asset_beta) = match &engine.benchmark {
+        Some(bench) => {
+            let sessions: Option<Vec<String>> = merged.get_item("sessions").map(|s| s.extract()).transpose()?;
+            let closes: Vec<f64> = item(merged, "closes")?.extract()?;
+            let (beta, rolling) = bench.betas(&dates, sessions.as_deref(), &equity);
+            let (asset_beta, _) = bench.betas(&dates, sessions.as_deref(), &closes);
+            merged.set_item("rolling_beta", PyArray1::from_vec(py, rolling))?;
+            (Some(beta), Some(asset_beta))
+        }
+        None => (None, None),
+    };
+
+    let metric = StockMetric {
+        max_drawdown_pct: max_drawdown(&equity) * 100.0,
+        sharpe: engine.sharpe(&dates, &equity),
+        n_periods: old_metric.n_periods + new_metric.n_periods,
+        time_in_market_pct: weighted(old_metric.time_in_market_pct, new_metric.time_in_market_pct),
+        avg_exposure_pct: weighted(old_metric.avg_exposure_pct, new_metric.avg_exposure_pct),
+        annual_turnover,
+        avg_holding_bars,
+        beta,
+        asset_beta,
+        ..new_metric.clone()
+    };
+    merged.set_item("metrics", metric.to_py(py)?)?;
+
+    Ok((merged, metric, (dates, equity)))
+}
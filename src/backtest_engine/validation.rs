@@ -0,0 +1,76 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use super::{Bar, StockMetric};
+
+pub(super) const NAN_POLICY_NAMES: [&str; 3] = ["mark", "raise", "off"];
+
+/// What a run does with a ticker whose prices or metrics are not usable numbers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum NanPolicy {
+    /// Leave the ticker out and list it with the reason in `invalid_tickers`
+    Mark,
+    /// Fail the run
+    Raise,
+    /// No checks: bad values flow into the metrics
+    Off,
+}
+
+impl NanPolicy {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "mark" => Ok(NanPolicy::Mark),
+            "raise" => Ok(NanPolicy::Raise),
+            "off" => Ok(NanPolicy::Off),
+            other => Err(PyValueError::new_err(format!(
+                "unknown nan_policy '{}', expected one of {}", other, NAN_POLICY_NAMES.join(", ")
+            ))),
+        }
+    }
+
+    /// Applies the policy to `ticker`'s first problem, if any: the reason to mark it invalid
+    /// with, or an error under "raise".
+    pub fn screen(self, ticker: &str, problem: impl FnOnce() -> Option<String>) -> PyResult<Option<String>> {
+        if self == NanPolicy::Off {
+            return Ok(None);
+        }
+        match problem() {
+            Some(reason) if self == NanPolicy::Raise => Err(PyValueError::new_err(format!("{}: {}", ticker, reason))),
+            reason => Ok(reason),
+        }
+    }
+}
+
+/// First bar with a NaN or infinite price, or a close that is not positive (which would put
+/// a zero or negative entry price under every return).
+pub(super) fn check_bars(bars: &[Bar]) -> Option<String> {
+    bars.iter().find_map(|b| {
+        let fields = [("open", b.open), ("high", b.high), ("low", b.low), ("close", b.close), ("adj_close", b.adj_close)];
+        if let Some((name, v)) = fields.iter().find(|(_, v)| !v.is_finite()) {
+            return Some(format!("{} is {} at {}", name, v, b.date));
+        }
+        (b.close <= 0.0).then(|| format!("close is {} at {}", b.close, b.date))
+    })
+}
+
+/// Metrics that came out NaN or infinite.
+pub(super) fn check_metric(m: &StockMetric) -> Option<String> {
+    let fields = [
+        ("final_balance", Some(m.final_balance)),
+        ("roi_pct", Some(m.roi_pct)),
+        ("buy_and_hold_pct", Some(m.buy_and_hold_pct)),
+        ("alpha_pct", Some(m.alpha_pct)),
+        ("max_drawdown_pct", Some(m.max_drawdown_pct)),
+        ("sharpe", Some(m.sharpe)),
+        ("time_in_market_pct", Some(m.time_in_market_pct)),
+        ("avg_exposure_pct", Some(m.avg_exposure_pct)),
+        ("annual_turnover", Some(m.annual_turnover)),
+        ("avg_holding_bars", Some(m.avg_holding_bars)),
+        ("beta", m.beta),
+        ("asset_beta", m.asset_beta),
+    ];
+    let bad: Vec<String> = fields.iter()
+        .filter_map(|(name, v)| v.filter(|v| !v.is_finite()).map(|v| format!("{} is {}", name, v)))
+        .collect();
+    if bad.is_empty() { None } else { Some(bad.join(", ")) }
+}
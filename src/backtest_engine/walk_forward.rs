@@ -0,0 +1,201 @@
+use ndarray::Array1;
+use numpy::PyArray1;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use std::ops::Range;
+
+use super::state::TickerState;
+use super::{combine_equity_curves, BacktestEngine, Bar, INITIAL_CAPITAL_PER_STOCK};
+use crate::series::{self, PriceSeries};
+
+/// One ticker's bars and pattern signals, loaded once for every fold.
+struct Ticker {
+    name: String,
+    bars: Vec<Bar>,
+    pattern_signals: Vec<(String, Array1<i32>)>,
+}
+
+/// Positions in the sorted union of all tickers' dates: the strategy is fit on `train` and
+/// traded on `test`.
+struct Fold {
+    train: Range<usize>,
+    test: Range<usize>,
+}
+
+/// Rolling windows of `train_bars` dates followed by up to `test_bars` dates, moved forward
+/// `step` dates at a time; the last test window may be shorter. `anchored` windows all train
+/// from the first date, so each one grows by `step` dates.
+fn folds(n_dates: usize, train_bars: usize, test_bars: usize, step: usize, anchored: bool) -> Vec<Fold> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    while start + train_bars < n_dates {
+        let test_start = start + train_bars;
+        let train_start = if anchored { 0 } else { start };
+        out.push(Fold { train: train_start..test_start, test: test_start..(test_start + test_bars).min(n_dates) });
+        start += step;
+    }
+    out
+}
+
+fn price_series(bars: &[Bar]) -> PriceSeries {
+    let rows: Vec<series::Bar> = bars.iter()
+        .map(|b| series::Bar { timestamp: b.date.clone(), open: b.open, high: b.high, low: b.low, close: b.close, volume: b.volume })
+        .collect();
+    PriceSeries::from_bars(&rows)
+}
+
+/// Fits `engine.strategy` on each fold's training dates and trades it on the following test
+/// dates. Before fitting fold k > 0 the strategy gets fold k - 1's parameters back through
+/// `set_params`, and after fitting its `get_params()` are stored with the fold. With `replay`
+/// (one parameter object per fold) the strategy is not fit; each fold's parameters are set
+/// from the list instead.
+pub(super) fn walk_forward(
+    engine: &BacktestEngine,
+    py: Python<'_>,
+    train_bars: usize,
+    test_bars: usize,
+    step: usize,
+    anchored: bool,
+    replay: Option<&PyList>,
+) -> PyResult<PyObject> {
+    if train_bars == 0 || test_bars == 0 || step == 0 {
+        return Err(PyValueError::new_err("train_bars, test_bars and step must be at least 1"));
+    }
+    let strategy = &engine.strategy;
+    let subscribed = BacktestEngine::subscribed_patterns(py, strategy);
+    let mut tickers = Vec::new();
+    for path in engine.data_files() {
+        let file_path = path.to_str().unwrap();
+        let name = path.file_stem().unwrap().to_str().unwrap().replace("_meso", "");
+        match engine.load_bars(file_path, &name) {
+            Ok(bars) => {
+                let pattern_signals = BacktestEngine::pattern_signals(&subscribed, &bars);
+                tickers.push(Ticker { name, bars, pattern_signals });
+            }
+            Err(e) => log::warn!("Skipping {} because of read error: {}", file_path, e),
+        }
+    }
+
+    let mut dates: Vec<&String> = tickers.iter().flat_map(|t| t.bars.iter().map(|b| &b.date)).collect();
+    dates.sort();
+    dates.dedup();
+    let folds = folds(dates.len(), train_bars, test_bars, step, anchored);
+    if folds.is_empty() {
+        return Err(PyValueError::new_err(format!(
+            "the data folder has {} dates, walk-forward needs more than train_bars = {}", dates.len(), train_bars
+        )));
+    }
+    if let Some(replay) = replay
+        && replay.len() != folds.len()
+    {
+        return Err(PyValueError::new_err(format!("replay has {} parameter sets for {} folds", replay.len(), folds.len())));
+    }
+    let has = |name: &str| strategy.as_ref(py).hasattr(name);
+    let (can_fit, can_get, can_set) = (has("fit")?, has("get_params")?, has("set_params")?);
+    if replay.is_some() && !can_set {
+        return Err(PyValueError::new_err("replaying parameters needs a strategy with set_params"));
+    }
+
+    let py_folds = PyList::empty(py);
+    let all_params = PyList::empty(py);
+    let mut previous: Option<PyObject> = None;
+    let mut portfolio_dates: Vec<String> = Vec::new();
+    let mut portfolio_equity: Vec<f64> = Vec::new();
+    let mut level = INITIAL_CAPITAL_PER_STOCK * tickers.len() as f64;
+
+    for (k, fold) in folds.iter().enumerate() {
+        let (train_first, train_last) = (dates[fold.train.start], dates[fold.train.end - 1]);
+        let (test_first, test_last) = (dates[fold.test.start], dates[fold.test.end - 1]);
+
+        // --- Fit ---
+        let params: PyObject = match replay {
+            Some(replay) => {
+                let params = replay.get_item(k)?.to_object(py);
+                strategy.call_method1(py, "set_params", (params.clone_ref(py),))?;
+                params
+            }
+            None => {
+                if let Some(p) = &previous && can_set {
+                    strategy.call_method1(py, "set_params", (p.clone_ref(py),))?;
+                }
+                if can_fit {
+                    let train = PyDict::new(py);
+                    for t in &tickers {
+                        let from = t.bars.partition_point(|b| b.date < *train_first);
+                        let to = t.bars.partition_point(|b| b.date <= *train_last);
+                        if from < to {
+                            train.set_item(&t.name, Py::new(py, price_series(&t.bars[from..to]))?)?;
+                        }
+                    }
+                    strategy.call_method1(py, "fit", (train,))?;
+                }
+                if can_get { strategy.call_method0(py, "get_params")? } else { py.None() }
+            }
+        };
+        all_params.append(params.clone_ref(py))?;
+        previous = Some(params.clone_ref(py));
+
+        // --- Test: every ticker starts flat with fresh capital ---
+        let metrics = PyList::empty(py);
+        let mut curves = Vec::new();
+        let mut strategy_errors = 0;
+        for t in &tickers {
+            let end = t.bars.partition_point(|b| b.date <= *test_last);
+            let start = t.bars.partition_point(|b| b.date < *test_first).max(engine.history_size);
+            if start >= end {
+                continue;
+            }
+            let bars = &t.bars[..end];
+            let st = TickerState::new(INITIAL_CAPITAL_PER_STOCK, INITIAL_CAPITAL_PER_STOCK / bars[start].close);
+            let strategy = engine.strategy_scope.instance(py, strategy, &t.name)?;
+            let run = engine.simulate_ticker(py, &t.name, bars, start, st, false, |i, history, position| {
+                engine.step_signal(py, &strategy, &t.name, bars, &t.pattern_signals, i, history, position)
+            })?;
+            strategy_errors += run.strategy_errors;
+            metrics.append(run.metric.to_py(py)?)?;
+            curves.push(run.equity_curve);
+        }
+
+        let (fold_dates, fold_equity) = combine_equity_curves(&curves);
+        let sharpe = engine.sharpe(&fold_dates, &fold_equity);
+        let base = INITIAL_CAPITAL_PER_STOCK * curves.len() as f64;
+        let roi_pct = match fold_equity.last() {
+            Some(last) => (last / base - 1.0) * 100.0,
+            None => 0.0,
+        };
+        // Chain the folds into one curve that compounds each fold's return.
+        let start_level = level;
+        for (date, equity) in fold_dates.into_iter().zip(&fold_equity) {
+            level = start_level * equity / base;
+            portfolio_dates.push(date);
+            portfolio_equity.push(level);
+        }
+
+        let item = PyDict::new(py);
+        item.set_item("fold", k)?;
+        item.set_item("train_start", train_first)?;
+        item.set_item("train_end", train_last)?;
+        item.set_item("train_bars", fold.train.len())?;
+        item.set_item("test_start", test_first)?;
+        item.set_item("test_end", test_last)?;
+        item.set_item("params", params)?;
+        item.set_item("metrics", metrics)?;
+        item.set_item("roi_pct", roi_pct)?;
+        item.set_item("sharpe", sharpe)?;
+        item.set_item("strategy_errors", strategy_errors)?;
+        py_folds.append(item)?;
+    }
+
+    let portfolio = PyDict::new(py);
+    portfolio.set_item("sharpe", engine.sharpe(&portfolio_dates, &portfolio_equity))?;
+    portfolio.set_item("dates", portfolio_dates)?;
+    portfolio.set_item("equity", PyArray1::from_vec(py, portfolio_equity))?;
+
+    let out = PyDict::new(py);
+    out.set_item("mode", if anchored { "anchored" } else { "rolling" })?;
+    out.set_item("folds", py_folds)?;
+    out.set_item("params", all_params)?;
+    out.set_item("portfolio", portfolio)?;
+    Ok(out.to_object(py))
+}
@@ -0,0 +1,176 @@
+// Timings of the hot paths on synthetic data: CSV parsing, indicator kernels, the per-bar
+// engine loop and the per-bar strategy call across FFI. `benchmark()` runs them from Python;
+// `benches/engine.rs` drives the same workloads through criterion.
+
+use chrono::NaiveDate;
+use ndarray::Array1;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::backtest_engine::BacktestEngine;
+use crate::indicators::{atr, ewm, rsi_method, sma_method, std_method};
+use crate::{series, synthetic};
+
+/// Strategies for the engine cases: `Batch` gives all signals in one `step_batch` call, so a
+/// run mostly measures the engine's own loop; `Step` is called once per bar.
+const STRATEGIES: &str = r#"
+import numpy as np
+
+class Batch:
+    def step_batch(self, windows, **kwargs):
+        return np.where(windows[:, -1] > windows[:, 0], 1, -1)
+
+class Step:
+    def step(self, history, position, *args, **kwargs):
+        return 1 if history[-1] > history[0] else -1
+"#;
+
+/// A temporary data folder of GBM tickers, removed when dropped.
+pub struct Workload {
+    pub folder: PathBuf,
+    pub n_tickers: usize,
+    pub n_bars: usize,
+}
+
+impl Workload {
+    pub fn new(n_tickers: usize, n_bars: usize, seed: u64) -> std::io::Result<Self> {
+        static WORKLOADS: AtomicUsize = AtomicUsize::new(0);
+        let id = WORKLOADS.fetch_add(1, Ordering::Relaxed);
+        let folder = std::env::temp_dir().join(format!("tradekit_bench_{}_{}", std::process::id(), id));
+        let start = NaiveDate::from_ymd_opt(2000, 1, 3).unwrap();
+        for k in 0..n_tickers {
+            let bars = synthetic::gbm(n_bars, 100.0, 0.08, 0.2, seed + k as u64);
+            synthetic::write_csv(&bars, &folder, &format!("BENCH{}", k), start)?;
+        }
+        Ok(Workload { folder, n_tickers, n_bars })
+    }
+
+    pub fn bars(&self) -> usize {
+        self.n_tickers * self.n_bars
+    }
+
+    fn files(&self) -> Vec<PathBuf> {
+        (0..self.n_tickers).map(|k| self.folder.join(format!("BENCH{}_meso.csv", k))).collect()
+    }
+}
+
+impl Drop for Workload {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.folder);
+    }
+}
+
+/// Parses every data file of the workload and returns the bars read.
+pub fn parse_csv(workload: &Workload) -> std::io::Result<usize> {
+    let mut bars = 0;
+    for path in workload.files() {
+        bars += series::read_csv(path.to_str().unwrap())?.len();
+    }
+    Ok(bars)
+}
+
+/// Runs the SMA, EMA, RSI, rolling std and ATR kernels over one OHLC series.
+pub fn indicators(high: &Array1<f64>, low: &Array1<f64>, close: &Array1<f64>, n: usize) -> f64 {
+    let outputs = [
+        sma_method::sma(close, n),
+        ewm::ewm(close, n),
+        rsi_method::rsi(close, n),
+        std_method::rolling_std(close, n),
+        atr::atr(high, low, close, n),
+    ];
+    outputs.iter().map(|o| o[o.len() - 1]).sum()
+}
+
+/// The `Batch` or `Step` strategy.
+pub fn strategy(py: Python<'_>, name: &str) -> PyResult<PyObject> {
+    let module = PyModule::from_code(py, STRATEGIES, "tradekit_bench.py", "tradekit_bench")?;
+    Ok(module.getattr(name)?.call0()?.into())
+}
+
+/// One `run` of `strategy` over the workload.
+pub fn run_engine(py: Python<'_>, workload: &Workload, strategy: PyObject, history_size: usize) -> PyResult<()> {
+    let engine = BacktestEngine::builder()
+        .strategy(strategy)
+        .history_size(history_size)
+        .data_folder(workload.folder.to_string_lossy())
+        .build()?;
+    Py::new(py, engine)?.call_method0(py, "run")?;
+    Ok(())
+}
+
+/// Fastest of `repeat` timings of `f`.
+fn best_of(repeat: usize, mut f: impl FnMut() -> PyResult<()>) -> PyResult<Duration> {
+    let mut best = Duration::MAX;
+    for _ in 0..repeat {
+        let started = Instant::now();
+        f()?;
+        best = best.min(started.elapsed());
+    }
+    Ok(best)
+}
+
+/// Times CSV parsing, indicator kernels, the per-bar loop (`run` with a `step_batch` strategy)
+/// and the FFI strategy path (`run` with a per-bar `step`) on `n_tickers` synthetic GBM
+/// tickers of `n_bars` bars each. Returns, per case, the best of `repeat` runs in `seconds`
+/// and `ns_per_bar` over all bars, plus the workload's parameters. The engine cases need numpy.
+#[pyfunction]
+pub fn benchmark(
+    py: Python<'_>,
+    n_bars: Option<usize>,
+    n_tickers: Option<usize>,
+    history_size: Option<usize>,
+    repeat: Option<usize>,
+    seed: Option<u64>,
+) -> PyResult<PyObject> {
+    let (n_bars, n_tickers, history_size) = (n_bars.unwrap_or(10_000), n_tickers.unwrap_or(4), history_size.unwrap_or(20));
+    let repeat = repeat.unwrap_or(3).max(1);
+    if n_tickers == 0 || history_size == 0 || n_bars <= history_size + 1 {
+        return Err(PyValueError::new_err(format!(
+            "need n_tickers >= 1 and n_bars > history_size + 1 >= 2, got n_tickers {}, n_bars {}, history_size {}",
+            n_tickers, n_bars, history_size
+        )));
+    }
+    let workload = Workload::new(n_tickers, n_bars, seed.unwrap_or(42))
+        .map_err(|e| PyIOError::new_err(format!("writing benchmark data: {}", e)))?;
+    let io_err = |e: std::io::Error| PyIOError::new_err(format!("reading benchmark data: {}", e));
+
+    let mut cases: Vec<(&str, Duration)> = Vec::new();
+    cases.push(("csv_parse", best_of(repeat, || parse_csv(&workload).map(|_| ()).map_err(io_err))?));
+
+    let series: Vec<[Array1<f64>; 3]> = workload.files().iter()
+        .map(|path| {
+            let bars = series::read_csv(path.to_str().unwrap()).map_err(io_err)?;
+            let column = |f: fn(&series::Bar) -> f64| Array1::from_iter(bars.iter().map(f));
+            Ok([column(|b| b.high), column(|b| b.low), column(|b| b.close)])
+        })
+        .collect::<PyResult<_>>()?;
+    cases.push(("indicators", best_of(repeat, || {
+        for [high, low, close] in &series {
+            std::hint::black_box(indicators(high, low, close, history_size));
+        }
+        Ok(())
+    })?));
+
+    for (case, name) in [("bar_loop", "Batch"), ("ffi_strategy", "Step")] {
+        let strategy = strategy(py, name)?;
+        cases.push((case, best_of(repeat, || run_engine(py, &workload, strategy.clone_ref(py), history_size))?));
+    }
+
+    let out = PyDict::new(py);
+    out.set_item("n_bars", n_bars)?;
+    out.set_item("n_tickers", n_tickers)?;
+    out.set_item("history_size", history_size)?;
+    out.set_item("repeat", repeat)?;
+    for (case, elapsed) in cases {
+        let timing = PyDict::new(py);
+        timing.set_item("seconds", elapsed.as_secs_f64())?;
+        timing.set_item("ns_per_bar", elapsed.as_nanos() as f64 / workload.bars() as f64)?;
+        out.set_item(case, timing)?;
+    }
+    Ok(out.to_object(py))
+}
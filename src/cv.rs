@@ -0,0 +1,60 @@
+// Purged, embargoed K-fold splits for time series, so parameters chosen on one stretch of
+// bars are scored on another without leaking through overlapping bars.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::ops::Range;
+
+/// One split: bars to fit or select on, and the contiguous block held out for scoring.
+pub struct Fold {
+    pub train: Vec<Range<usize>>,
+    pub test: Range<usize>,
+}
+
+/// Splits `n` bars into `k` contiguous test blocks in time order. Each fold trains on every
+/// other bar except the `purge` bars just before its test block (whose look-ahead would reach
+/// into it) and the `embargo` bars just after (which are serially correlated with it).
+pub fn purged_kfold(n: usize, k: usize, purge: usize, embargo: usize) -> Vec<Fold> {
+    (0..k)
+        .map(|f| {
+            let test = f * n / k..(f + 1) * n / k;
+            let train = [0..test.start.saturating_sub(purge), (test.end + embargo).min(n)..n]
+                .into_iter()
+                .filter(|r| !r.is_empty())
+                .collect();
+            Fold { train, test }
+        })
+        .collect()
+}
+
+pub fn check_folds(n: usize, k: usize) -> PyResult<()> {
+    if k < 2 || k > n {
+        return Err(PyValueError::new_err(format!("need 2 <= k <= n_bars, got k={} for {} bars", k, n)));
+    }
+    Ok(())
+}
+
+/// Purged K-fold splits of `n_bars` bars as a list of {"train": [(start, end), ...],
+/// "test": (start, end)} with half-open ranges.
+#[pyfunction]
+#[pyo3(name = "purged_kfold")]
+fn py_purged_kfold(py: Python<'_>, n_bars: usize, k: usize, purge: Option<usize>, embargo: Option<usize>) -> PyResult<Vec<PyObject>> {
+    check_folds(n_bars, k)?;
+    purged_kfold(n_bars, k, purge.unwrap_or(0), embargo.unwrap_or(0))
+        .into_iter()
+        .map(|fold| {
+            let d = PyDict::new(py);
+            d.set_item("train", fold.train.iter().map(|r| (r.start, r.end)).collect::<Vec<_>>())?;
+            d.set_item("test", (fold.test.start, fold.test.end))?;
+            Ok(d.into())
+        })
+        .collect()
+}
+
+pub fn register(py: Python<'_>, parent: &PyModule) -> PyResult<()> {
+    let m = PyModule::new(py, "cv")?;
+    m.add_function(wrap_pyfunction!(py_purged_kfold, m)?)?;
+    parent.add_submodule(m)?;
+    Ok(())
+}
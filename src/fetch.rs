@@ -0,0 +1,222 @@
+// Downloads daily OHLCV history into the `<TICKER>_meso.csv` files the engine reads, so a
+// backtest can start from a list of tickers. The HTTP client is behind the `fetch` feature.
+
+use chrono::{DateTime, Days, NaiveDate};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PROVIDER_NAMES: [&str; 2] = ["yahoo", "csv"];
+const NO_FETCH: &str = "fetch_data needs tradekit_rust built with the 'fetch' feature";
+
+/// One row of the engine's data files.
+struct Row {
+    date: String,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+}
+
+enum Provider<'a> {
+    /// Yahoo Finance chart API, daily bars dated in the exchange's zone
+    Yahoo,
+    /// Any CSV with date/open/high/low/close(/volume) columns; the URL may contain `{ticker}`,
+    /// `{start}` and `{end}`
+    Csv(&'a str),
+}
+
+impl<'a> Provider<'a> {
+    fn parse(name: Option<&str>, url: Option<&'a str>) -> PyResult<Self> {
+        match (name.unwrap_or("yahoo"), url) {
+            ("yahoo", _) => Ok(Provider::Yahoo),
+            ("csv", Some(url)) => Ok(Provider::Csv(url)),
+            ("csv", None) => Err(PyValueError::new_err("provider 'csv' needs a url template")),
+            (other, _) => Err(PyValueError::new_err(format!(
+                "unknown provider '{}', expected one of {}", other, PROVIDER_NAMES.join(", ")
+            ))),
+        }
+    }
+
+    fn rows(&self, ticker: &str, start: NaiveDate, end: NaiveDate) -> Result<Vec<Row>, String> {
+        let rows = match self {
+            Provider::Yahoo => {
+                let epoch = |d: NaiveDate| d.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp();
+                let url = format!(
+                    "https://query1.finance.yahoo.com/v8/finance/chart/{}?period1={}&period2={}&interval=1d",
+                    ticker, epoch(start), epoch(end + Days::new(1))
+                );
+                parse_yahoo(&http_get(&url)?)?
+            }
+            Provider::Csv(template) => {
+                let url = template
+                    .replace("{ticker}", ticker)
+                    .replace("{start}", &start.to_string())
+                    .replace("{end}", &end.to_string());
+                parse_csv(&http_get(&url)?)?
+            }
+        };
+        let (start, end) = (start.to_string(), end.to_string());
+        Ok(rows.into_iter().filter(|r| r.date.get(..10).is_some_and(|d| *d >= *start && *d <= *end)).collect())
+    }
+}
+
+#[cfg(feature = "fetch")]
+fn http_get(url: &str) -> Result<String, String> {
+    ureq::get(url)
+        .set("User-Agent", "Mozilla/5.0 (tradekit_rust)")
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "fetch"))]
+fn http_get(_url: &str) -> Result<String, String> {
+    Err(NO_FETCH.to_string())
+}
+
+/// Daily bars from a chart API response; bars without a close are left out.
+fn parse_yahoo(body: &str) -> Result<Vec<Row>, String> {
+    let json: serde_json::Value = serde_json::from_str(body).map_err(|e| e.to_string())?;
+    let chart = &json["chart"];
+    if let Some(message) = chart["error"]["description"].as_str() {
+        return Err(message.to_string());
+    }
+    let result = &chart["result"][0];
+    let offset = result["meta"]["gmtoffset"].as_i64().unwrap_or(0);
+    let quote = &result["indicators"]["quote"][0];
+    let column = |name: &str| -> Vec<Option<f64>> {
+        quote[name].as_array().map(|v| v.iter().map(|x| x.as_f64()).collect()).unwrap_or_default()
+    };
+    let (open, high, low, close, volume) = (column("open"), column("high"), column("low"), column("close"), column("volume"));
+    let timestamps = result["timestamp"].as_array().ok_or("response has no timestamps")?;
+
+    let mut rows = Vec::with_capacity(timestamps.len());
+    for (k, t) in timestamps.iter().enumerate() {
+        let (Some(t), Some(Some(c))) = (t.as_i64(), close.get(k)) else { continue };
+        let Some(date) = DateTime::from_timestamp(t + offset, 0) else { continue };
+        let field = |v: &Vec<Option<f64>>| v.get(k).copied().flatten().unwrap_or(*c);
+        rows.push(Row {
+            date: date.format("%Y-%m-%d").to_string(),
+            open: field(&open),
+            high: field(&high),
+            low: field(&low),
+            close: *c,
+            volume: volume.get(k).copied().flatten().unwrap_or(0.0),
+        });
+    }
+    Ok(rows)
+}
+
+/// Rows of a CSV with a header naming date (or timestamp), open, high, low, close and
+/// optionally volume columns, in any order and case. Rows without a numeric close are skipped.
+fn parse_csv(body: &str) -> Result<Vec<Row>, String> {
+    let mut lines = body.lines();
+    let header: Vec<String> = lines.next().ok_or("empty CSV")?
+        .split(',')
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let find = |names: &[&str]| header.iter().position(|h| names.contains(&h.as_str()));
+    let date_col = find(&["date", "timestamp", "datetime", "time"]).ok_or("CSV has no date column")?;
+    let close_col = find(&["close", "adj close", "adj_close"]).ok_or("CSV has no close column")?;
+    let (open_col, high_col, low_col, volume_col) = (find(&["open"]), find(&["high"]), find(&["low"]), find(&["volume"]));
+
+    let mut rows = Vec::new();
+    for line in lines {
+        let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+        let number = |col: Option<usize>| col.and_then(|c| parts.get(c)).and_then(|v| v.parse::<f64>().ok());
+        let Some(close) = number(Some(close_col)) else { continue };
+        let Some(date) = parts.get(date_col) else { continue };
+        rows.push(Row {
+            date: date.to_string(),
+            open: number(open_col).unwrap_or(close),
+            high: number(high_col).unwrap_or(close),
+            low: number(low_col).unwrap_or(close),
+            close,
+            volume: number(volume_col).unwrap_or(0.0),
+        });
+    }
+    Ok(rows)
+}
+
+/// Writes rows through a temporary file, so readers never see a half-written data file.
+fn write_rows(path: &Path, rows: &[Row]) -> Result<(), String> {
+    let mut text = String::from("date,open,high,low,close,volume\n");
+    for r in rows {
+        text.push_str(&format!("{},{},{},{},{},{}\n", r.date, r.open, r.high, r.low, r.close, r.volume));
+    }
+    let tmp = path.with_extension("csv.tmp");
+    fs::write(&tmp, text).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Downloads daily bars from `start` to `end` (inclusive, "YYYY-MM-DD") for each ticker and
+/// writes them to `<data_folder>/<TICKER>_meso.csv` (default folder "historical_data").
+/// `provider` is "yahoo" (default) or "csv" with a `url` template containing `{ticker}` and
+/// optionally `{start}` / `{end}`. Returns {"files": {ticker: path}, "errors": {ticker:
+/// message}}; a failed ticker doesn't stop the others. Tickers must not contain path
+/// separators. Needs the `fetch` feature; without it the call raises before writing anything.
+#[pyfunction]
+pub fn fetch_data(
+    py: Python<'_>,
+    tickers: Vec<String>,
+    start: &str,
+    end: &str,
+    provider: Option<&str>,
+    data_folder: Option<&str>,
+    url: Option<&str>,
+) -> PyResult<PyObject> {
+    if !cfg!(feature = "fetch") {
+        return Err(PyRuntimeError::new_err(NO_FETCH));
+    }
+    // Tickers become file names inside the data folder and must not point anywhere else.
+    if let Some(bad) = tickers.iter().find(|t| t.is_empty() || t.contains(['/', '\\'])) {
+        return Err(PyValueError::new_err(format!("invalid ticker '{}': tickers must not be empty or contain path separators", bad)));
+    }
+    let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|e| PyValueError::new_err(format!("invalid date '{}': {}", s, e)));
+    let (start, end) = (date(start)?, date(end)?);
+    if start > end {
+        return Err(PyValueError::new_err(format!("start {} is after end {}", start, end)));
+    }
+    let provider = Provider::parse(provider, url)?;
+    let folder = PathBuf::from(data_folder.unwrap_or("historical_data"));
+    fs::create_dir_all(&folder).map_err(|e| PyValueError::new_err(format!("creating {}: {}", folder.display(), e)))?;
+
+    let results: Vec<(String, Result<PathBuf, String>)> = py.allow_threads(|| {
+        tickers.iter()
+            .map(|ticker| {
+                let path = folder.join(format!("{}_meso.csv", ticker));
+                let written = provider.rows(ticker, start, end).and_then(|rows| {
+                    if rows.is_empty() {
+                        return Err(format!("no bars between {} and {}", start, end));
+                    }
+                    write_rows(&path, &rows)?;
+                    log::info!("Fetched {} bars for {} into {}", rows.len(), ticker, path.display());
+                    Ok(path)
+                });
+                (ticker.clone(), written)
+            })
+            .collect()
+    });
+
+    let files = PyDict::new(py);
+    let errors = PyDict::new(py);
+    for (ticker, result) in results {
+        match result {
+            Ok(path) => files.set_item(&ticker, path.to_string_lossy())?,
+            Err(e) => {
+                log::warn!("Fetching {} failed: {}", ticker, e);
+                errors.set_item(&ticker, e)?;
+            }
+        }
+    }
+    let out = PyDict::new(py);
+    out.set_item("files", files)?;
+    out.set_item("errors", errors)?;
+    Ok(out.to_object(py))
+}
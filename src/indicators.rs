@@ -1,9 +1,28 @@
 pub mod sma_method;
 pub mod ewm;
+pub mod atr;
+pub mod donchian;
+pub mod keltner;
+pub mod volume_method;
+pub mod linreg;
+pub mod normalize;
+pub mod kernels;
+pub mod std_method;
+pub mod rsi_method;
+pub mod padding;
+pub mod corr_method;
+pub mod ichimoku_method;
+pub mod psar_method;
+pub mod supertrend_method;
+#[cfg(test)]
+mod reference;
 
 use ndarray::{Array1};
-use numpy::PyReadonlyArray1;
+use numpy::{PyArray1, PyReadonlyArray1};
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use padding::Padding;
 use sma_method::sma;
 
 #[pyclass]
@@ -36,3 +55,335 @@ impl Indicator {
         }
     }
 }
+
+fn check_same_len(arrays: &[&Array1<f64>]) -> PyResult<()> {
+    let len = arrays[0].len();
+    if arrays.iter().any(|a| a.len() != len) {
+        return Err(PyValueError::new_err("input arrays must all have the same length"));
+    }
+    Ok(())
+}
+
+// Every wrapper below takes `padding="nan"|"compact"|"edge"` (default "nan"); see `Padding`.
+
+/// Simple moving average over `n` bars.
+#[pyfunction]
+#[pyo3(name = "sma")]
+pub fn sma_indicator<'py>(py: Python<'py>, data: PyReadonlyArray1<f64>, n: usize, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+    let padding = Padding::parse(padding)?;
+    let data = data.as_array().to_owned();
+    Ok(PyArray1::from_owned_array(py, padding.apply_with(sma(&data, n), n.saturating_sub(1))))
+}
+
+/// Exponential moving average over `n` bars, seeded with the SMA of the first `n` values.
+#[pyfunction]
+pub fn ema<'py>(py: Python<'py>, data: PyReadonlyArray1<f64>, n: usize, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+    let padding = Padding::parse(padding)?;
+    let data = data.as_array().to_owned();
+    Ok(PyArray1::from_owned_array(py, padding.apply_with(ewm::ewm(&data, n), n.saturating_sub(1))))
+}
+
+/// Donchian channel over `n` bars. Returns (upper, middle, lower).
+#[pyfunction]
+pub fn donchian_channel<'py>(
+    py: Python<'py>,
+    high: PyReadonlyArray1<f64>,
+    low: PyReadonlyArray1<f64>,
+    n: usize,
+    padding: Option<&str>,
+) -> PyResult<(&'py PyArray1<f64>, &'py PyArray1<f64>, &'py PyArray1<f64>)> {
+    let padding = Padding::parse(padding)?;
+    let high = high.as_array().to_owned();
+    let low = low.as_array().to_owned();
+    check_same_len(&[&high, &low])?;
+
+    let (upper, middle, lower) = donchian::donchian(&high, &low, n);
+    let warmup = n.saturating_sub(1);
+    let [upper, middle, lower] = padding.apply_all_with([upper, middle, lower], [warmup; 3]);
+    Ok((
+        PyArray1::from_owned_array(py, upper),
+        PyArray1::from_owned_array(py, middle),
+        PyArray1::from_owned_array(py, lower),
+    ))
+}
+
+/// Keltner channel: EMA(close, n) +/- multiplier * ATR(atr_n). Returns (upper, middle, lower).
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+pub fn keltner_channel<'py>(
+    py: Python<'py>,
+    high: PyReadonlyArray1<f64>,
+    low: PyReadonlyArray1<f64>,
+    close: PyReadonlyArray1<f64>,
+    n: usize,
+    atr_n: Option<usize>,
+    multiplier: Option<f64>,
+    padding: Option<&str>,
+) -> PyResult<(&'py PyArray1<f64>, &'py PyArray1<f64>, &'py PyArray1<f64>)> {
+    let padding = Padding::parse(padding)?;
+    let high = high.as_array().to_owned();
+    let low = low.as_array().to_owned();
+    let close = close.as_array().to_owned();
+    check_same_len(&[&high, &low, &close])?;
+
+    let atr_n = atr_n.unwrap_or(n);
+    let (upper, middle, lower) = keltner::keltner(&high, &low, &close, n, atr_n, multiplier.unwrap_or(2.0));
+    // The bands need both the EMA and the ATR; the middle line only the EMA.
+    let (band_warmup, middle_warmup) = (n.max(atr_n).saturating_sub(1), n.saturating_sub(1));
+    let [upper, middle, lower] =
+        padding.apply_all_with([upper, middle, lower], [band_warmup, middle_warmup, band_warmup]);
+    Ok((
+        PyArray1::from_owned_array(py, upper),
+        PyArray1::from_owned_array(py, middle),
+        PyArray1::from_owned_array(py, lower),
+    ))
+}
+
+/// On-Balance Volume from close and volume arrays. It has no warm-up, so every padding
+/// returns the full series; the option is accepted for a uniform API.
+#[pyfunction]
+pub fn obv<'py>(
+    py: Python<'py>,
+    close: PyReadonlyArray1<f64>,
+    volume: PyReadonlyArray1<f64>,
+    padding: Option<&str>,
+) -> PyResult<&'py PyArray1<f64>> {
+    let padding = Padding::parse(padding)?;
+    let close = close.as_array().to_owned();
+    let volume = volume.as_array().to_owned();
+    check_same_len(&[&close, &volume])?;
+
+    Ok(PyArray1::from_owned_array(py, padding.apply_with(volume_method::obv(&close, &volume), 0)))
+}
+
+/// Simple moving average of volume over `n` bars.
+#[pyfunction]
+pub fn volume_sma<'py>(py: Python<'py>, volume: PyReadonlyArray1<f64>, n: usize, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+    let padding = Padding::parse(padding)?;
+    let volume = volume.as_array().to_owned();
+    Ok(PyArray1::from_owned_array(py, padding.apply_with(volume_method::volume_sma(&volume, n), n.saturating_sub(1))))
+}
+
+/// Money Flow Index over `n` bars (default 14).
+#[pyfunction]
+pub fn money_flow_index<'py>(
+    py: Python<'py>,
+    high: PyReadonlyArray1<f64>,
+    low: PyReadonlyArray1<f64>,
+    close: PyReadonlyArray1<f64>,
+    volume: PyReadonlyArray1<f64>,
+    n: Option<usize>,
+    padding: Option<&str>,
+) -> PyResult<&'py PyArray1<f64>> {
+    let padding = Padding::parse(padding)?;
+    let high = high.as_array().to_owned();
+    let low = low.as_array().to_owned();
+    let close = close.as_array().to_owned();
+    let volume = volume.as_array().to_owned();
+    check_same_len(&[&high, &low, &close, &volume])?;
+
+    let n = n.unwrap_or(14);
+    let mfi = volume_method::mfi(&high, &low, &close, &volume, n);
+    // The first value needs `n` price changes, so `n + 1` bars.
+    Ok(PyArray1::from_owned_array(py, padding.apply_with(mfi, n)))
+}
+
+/// Rolling linear regression over `n` bars. Returns (slope, intercept, r_squared), with the
+/// intercept measured at the oldest bar of each window. r_squared is NaN for flat windows.
+#[pyfunction]
+pub fn linear_regression<'py>(
+    py: Python<'py>,
+    data: PyReadonlyArray1<f64>,
+    n: usize,
+    padding: Option<&str>,
+) -> PyResult<(&'py PyArray1<f64>, &'py PyArray1<f64>, &'py PyArray1<f64>)> {
+    let padding = Padding::parse(padding)?;
+    let data = data.as_array().to_owned();
+    let (slope, intercept, r2) = linreg::rolling_linreg(&data, n);
+    let [slope, intercept, r2] = padding.apply_all_with([slope, intercept, r2], [n.saturating_sub(1); 3]);
+    Ok((
+        PyArray1::from_owned_array(py, slope),
+        PyArray1::from_owned_array(py, intercept),
+        PyArray1::from_owned_array(py, r2),
+    ))
+}
+
+/// Rolling z-score over `n` bars.
+#[pyfunction]
+pub fn rolling_zscore<'py>(py: Python<'py>, data: PyReadonlyArray1<f64>, n: usize, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+    let padding = Padding::parse(padding)?;
+    let data = data.as_array().to_owned();
+    Ok(PyArray1::from_owned_array(py, padding.apply_with(normalize::rolling_zscore(&data, n), n.saturating_sub(1))))
+}
+
+/// Rolling min-max normalization into [0, 1] over `n` bars.
+#[pyfunction]
+pub fn rolling_minmax<'py>(py: Python<'py>, data: PyReadonlyArray1<f64>, n: usize, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+    let padding = Padding::parse(padding)?;
+    let data = data.as_array().to_owned();
+    Ok(PyArray1::from_owned_array(py, padding.apply_with(normalize::rolling_minmax(&data, n), n.saturating_sub(1))))
+}
+
+/// Rolling percent rank of each value within its trailing `n` bars.
+#[pyfunction]
+pub fn rolling_percent_rank<'py>(py: Python<'py>, data: PyReadonlyArray1<f64>, n: usize, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+    let padding = Padding::parse(padding)?;
+    let data = data.as_array().to_owned();
+    Ok(PyArray1::from_owned_array(py, padding.apply_with(normalize::rolling_percent_rank(&data, n), n.saturating_sub(1))))
+}
+
+/// Rolling sample standard deviation over `n` bars.
+#[pyfunction]
+pub fn rolling_std<'py>(py: Python<'py>, data: PyReadonlyArray1<f64>, n: usize, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+    let padding = Padding::parse(padding)?;
+    let data = data.as_array().to_owned();
+    Ok(PyArray1::from_owned_array(py, padding.apply_with(std_method::rolling_std(&data, n), n.saturating_sub(1))))
+}
+
+/// Relative Strength Index over `n` bars (default 14).
+#[pyfunction]
+pub fn rsi<'py>(py: Python<'py>, data: PyReadonlyArray1<f64>, n: Option<usize>, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+    let padding = Padding::parse(padding)?;
+    let data = data.as_array().to_owned();
+    let n = n.unwrap_or(14);
+    // Wilder's seed averages the first `n` changes, so the first value is at bar `n`.
+    Ok(PyArray1::from_owned_array(py, padding.apply_with(rsi_method::rsi(&data, n), n)))
+}
+
+/// Rolling Pearson correlation between `x` and `y` over `n` bars; flat windows score 0.
+#[pyfunction]
+pub fn rolling_correlation<'py>(
+    py: Python<'py>,
+    x: PyReadonlyArray1<f64>,
+    y: PyReadonlyArray1<f64>,
+    n: usize,
+    padding: Option<&str>,
+) -> PyResult<&'py PyArray1<f64>> {
+    let padding = Padding::parse(padding)?;
+    let x = x.as_array().to_owned();
+    let y = y.as_array().to_owned();
+    check_same_len(&[&x, &y])?;
+
+    Ok(PyArray1::from_owned_array(py, padding.apply_with(corr_method::rolling_corr(&x, &y, n), n.saturating_sub(1))))
+}
+
+/// Rolling sample covariance between `x` and `y` over `n` bars.
+#[pyfunction]
+pub fn rolling_covariance<'py>(
+    py: Python<'py>,
+    x: PyReadonlyArray1<f64>,
+    y: PyReadonlyArray1<f64>,
+    n: usize,
+    padding: Option<&str>,
+) -> PyResult<&'py PyArray1<f64>> {
+    let padding = Padding::parse(padding)?;
+    let x = x.as_array().to_owned();
+    let y = y.as_array().to_owned();
+    check_same_len(&[&x, &y])?;
+
+    Ok(PyArray1::from_owned_array(py, padding.apply_with(corr_method::rolling_cov(&x, &y, n), n.saturating_sub(1))))
+}
+
+/// Ichimoku Cloud over high, low and close arrays. Returns a dict of `tenkan`, `kijun`,
+/// `senkou_a`, `senkou_b` and `chikou`, all aligned with the input bars: the Senkou spans are
+/// shifted `displacement` bars forward (default `kijun_n`), so the cloud at bar `i` is the one
+/// projected at bar `i - displacement`, and `chikou[i]` is the close `displacement` bars later.
+/// Chikou looks ahead and its last `displacement` bars are NaN; compare it with past prices
+/// only. With `padding="compact"` every line is trimmed by the cloud's warm-up.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+pub fn ichimoku<'py>(
+    py: Python<'py>,
+    high: PyReadonlyArray1<f64>,
+    low: PyReadonlyArray1<f64>,
+    close: PyReadonlyArray1<f64>,
+    tenkan_n: Option<usize>,
+    kijun_n: Option<usize>,
+    senkou_b_n: Option<usize>,
+    displacement: Option<usize>,
+    padding: Option<&str>,
+) -> PyResult<&'py PyDict> {
+    let padding = Padding::parse(padding)?;
+    let high = high.as_array().to_owned();
+    let low = low.as_array().to_owned();
+    let close = close.as_array().to_owned();
+    check_same_len(&[&high, &low, &close])?;
+
+    let (tenkan_n, kijun_n, senkou_b_n) = (tenkan_n.unwrap_or(9), kijun_n.unwrap_or(26), senkou_b_n.unwrap_or(52));
+    let displacement = displacement.unwrap_or(kijun_n);
+    let lines = ichimoku_method::ichimoku(&high, &low, &close, tenkan_n, kijun_n, senkou_b_n, displacement);
+    // The Senkou spans warm up `displacement` bars after their lines; Chikou's NaNs are at the end.
+    let warmups = [
+        tenkan_n.saturating_sub(1),
+        kijun_n.saturating_sub(1),
+        tenkan_n.max(kijun_n).saturating_sub(1) + displacement,
+        senkou_b_n.saturating_sub(1) + displacement,
+        0,
+    ];
+    let [tenkan, kijun, senkou_a, senkou_b, chikou] = padding
+        .apply_all_with([lines.tenkan, lines.kijun, lines.senkou_a, lines.senkou_b, lines.chikou], warmups);
+    let out = PyDict::new(py);
+    out.set_item("tenkan", PyArray1::from_owned_array(py, tenkan))?;
+    out.set_item("kijun", PyArray1::from_owned_array(py, kijun))?;
+    out.set_item("senkou_a", PyArray1::from_owned_array(py, senkou_a))?;
+    out.set_item("senkou_b", PyArray1::from_owned_array(py, senkou_b))?;
+    out.set_item("chikou", PyArray1::from_owned_array(py, chikou))?;
+    Ok(out)
+}
+
+/// Parabolic SAR over high, low and close arrays, with acceleration `step` (default 0.02)
+/// capped at `max_step` (default 0.2). Returns (sar, direction), where direction is 1.0 in an
+/// uptrend and -1.0 in a downtrend and flips on the bar the price crosses the SAR.
+#[pyfunction]
+pub fn parabolic_sar<'py>(
+    py: Python<'py>,
+    high: PyReadonlyArray1<f64>,
+    low: PyReadonlyArray1<f64>,
+    close: PyReadonlyArray1<f64>,
+    step: Option<f64>,
+    max_step: Option<f64>,
+    padding: Option<&str>,
+) -> PyResult<(&'py PyArray1<f64>, &'py PyArray1<f64>)> {
+    let padding = Padding::parse(padding)?;
+    let (step, max_step) = (step.unwrap_or(0.02), max_step.unwrap_or(0.2));
+    if !(step > 0.0 && max_step >= step) {
+        return Err(PyValueError::new_err(format!("need 0 < step <= max_step, got step {} and max_step {}", step, max_step)));
+    }
+    let high = high.as_array().to_owned();
+    let low = low.as_array().to_owned();
+    let close = close.as_array().to_owned();
+    check_same_len(&[&high, &low, &close])?;
+
+    // The trend is seeded from the first two closes, so the first bar has no SAR.
+    let [sar, direction] = padding.apply_all_with(psar_method::psar(&high, &low, &close, step, max_step).into(), [1; 2]);
+    Ok((PyArray1::from_owned_array(py, sar), PyArray1::from_owned_array(py, direction)))
+}
+
+/// SuperTrend over ATR(`n`) (default 10) with bands `multiplier` ATRs (default 3.0) around
+/// the bar midpoint. Returns (level, direction), where direction is 1.0 in an uptrend and
+/// -1.0 in a downtrend and flips on the bar the close crosses the level.
+#[pyfunction]
+pub fn supertrend<'py>(
+    py: Python<'py>,
+    high: PyReadonlyArray1<f64>,
+    low: PyReadonlyArray1<f64>,
+    close: PyReadonlyArray1<f64>,
+    n: Option<usize>,
+    multiplier: Option<f64>,
+    padding: Option<&str>,
+) -> PyResult<(&'py PyArray1<f64>, &'py PyArray1<f64>)> {
+    let padding = Padding::parse(padding)?;
+    let multiplier = multiplier.unwrap_or(3.0);
+    if multiplier.is_nan() || multiplier <= 0.0 {
+        return Err(PyValueError::new_err(format!("multiplier must be > 0, got {}", multiplier)));
+    }
+    let high = high.as_array().to_owned();
+    let low = low.as_array().to_owned();
+    let close = close.as_array().to_owned();
+    check_same_len(&[&high, &low, &close])?;
+
+    let n = n.unwrap_or(10);
+    let [level, direction] = padding
+        .apply_all_with(supertrend_method::supertrend(&high, &low, &close, n, multiplier).into(), [n.saturating_sub(1); 2]);
+    Ok((PyArray1::from_owned_array(py, level), PyArray1::from_owned_array(py, direction)))
+}
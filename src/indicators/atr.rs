@@ -0,0 +1,38 @@
+use ndarray::Array1;
+
+pub fn true_range(high: &Array1<f64>, low: &Array1<f64>, close: &Array1<f64>) -> Array1<f64> {
+    let len = high.len();
+    let mut out = Vec::with_capacity(len);
+    for i in 0..len {
+        let hl = high[i] - low[i];
+        if i == 0 {
+            out.push(hl);
+            continue;
+        }
+        let prev_close = close[i - 1];
+        let hc = (high[i] - prev_close).abs();
+        let lc = (low[i] - prev_close).abs();
+        out.push(hl.max(hc).max(lc));
+    }
+    Array1::from(out)
+}
+
+/// Average True Range using Wilder's smoothing, NaN padded for the first `n - 1` bars.
+pub fn atr(high: &Array1<f64>, low: &Array1<f64>, close: &Array1<f64>, n: usize) -> Array1<f64> {
+    let len = high.len();
+    let mut out = vec![f64::NAN; len];
+    if n == 0 || n > len {
+        return Array1::from(out);
+    }
+
+    let tr = true_range(high, low, close);
+    let mut prev = tr.iter().take(n).sum::<f64>() / (n as f64);
+    out[n - 1] = prev;
+
+    for i in n..len {
+        prev = (prev * (n as f64 - 1.0) + tr[i]) / (n as f64);
+        out[i] = prev;
+    }
+
+    Array1::from(out)
+}
@@ -0,0 +1,62 @@
+use ndarray::Array1;
+
+use super::kernels::contiguous;
+
+/// Runs sums of x, y, x², y² and xy over each trailing `n`-bar window in one pass and writes
+/// `f(cov, var_x, var_y)` (sample moments) for every complete window, NaN-padding the rest.
+fn rolling_comoments(x: &Array1<f64>, y: &Array1<f64>, n: usize, f: impl Fn(f64, f64, f64) -> f64) -> Array1<f64> {
+    let len = x.len().min(y.len());
+    let mut out = vec![f64::NAN; len];
+    if n < 2 || n > len {
+        return Array1::from(out);
+    }
+
+    let (x, y) = (contiguous(x), contiguous(y));
+    let inv_n = 1.0 / n as f64;
+    let inv_dof = 1.0 / (n as f64 - 1.0);
+    let (mut sx, mut sy, mut sxx, mut syy, mut sxy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for i in 0..len {
+        let (a, b) = (x[i], y[i]);
+        sx += a;
+        sy += b;
+        sxx += a * a;
+        syy += b * b;
+        sxy += a * b;
+        if i >= n {
+            let (a, b) = (x[i - n], y[i - n]);
+            sx -= a;
+            sy -= b;
+            sxx -= a * a;
+            syy -= b * b;
+            sxy -= a * b;
+        }
+        if i + 1 >= n {
+            let cov = (sxy - sx * sy * inv_n) * inv_dof;
+            let var_x = ((sxx - sx * sx * inv_n) * inv_dof).max(0.0);
+            let var_y = ((syy - sy * sy * inv_n) * inv_dof).max(0.0);
+            out[i] = f(cov, var_x, var_y);
+        }
+    }
+
+    Array1::from(out)
+}
+
+/// Sample covariance of each trailing `n`-bar window of `x` and `y`.
+pub fn rolling_cov(x: &Array1<f64>, y: &Array1<f64>, n: usize) -> Array1<f64> {
+    rolling_comoments(x, y, n, |cov, _, _| cov)
+}
+
+/// Pearson correlation of each trailing `n`-bar window of `x` and `y`, clamped to [-1, 1].
+/// Windows where either series is flat score 0.
+pub fn rolling_corr(x: &Array1<f64>, y: &Array1<f64>, n: usize) -> Array1<f64> {
+    rolling_comoments(x, y, n, |cov, var_x, var_y| {
+        let denom = (var_x * var_y).sqrt();
+        if denom > f64::EPSILON { (cov / denom).clamp(-1.0, 1.0) } else { 0.0 }
+    })
+}
+
+/// Beta of `x` against `benchmark` over each trailing `n`-bar window: cov(x, b) / var(b).
+/// Windows where the benchmark is flat score 0.
+pub fn rolling_beta(x: &Array1<f64>, benchmark: &Array1<f64>, n: usize) -> Array1<f64> {
+    rolling_comoments(x, benchmark, n, |cov, _, var_b| if var_b > f64::EPSILON { cov / var_b } else { 0.0 })
+}
@@ -0,0 +1,45 @@
+use ndarray::Array1;
+use std::collections::VecDeque;
+
+/// Rolling maximum over a window of `n` using a monotonic deque, NaN padded.
+pub fn rolling_max(data: &Array1<f64>, n: usize) -> Array1<f64> {
+    rolling_extreme(data, n, |a, b| a >= b)
+}
+
+/// Rolling minimum over a window of `n` using a monotonic deque, NaN padded.
+pub fn rolling_min(data: &Array1<f64>, n: usize) -> Array1<f64> {
+    rolling_extreme(data, n, |a, b| a <= b)
+}
+
+fn rolling_extreme(data: &Array1<f64>, n: usize, dominates: fn(f64, f64) -> bool) -> Array1<f64> {
+    let len = data.len();
+    let mut out = vec![f64::NAN; len];
+    if n == 0 || n > len {
+        return Array1::from(out);
+    }
+
+    let mut window: VecDeque<usize> = VecDeque::with_capacity(n);
+    for i in 0..len {
+        while let Some(&back) = window.back() {
+            if dominates(data[i], data[back]) { window.pop_back(); } else { break; }
+        }
+        window.push_back(i);
+        if let Some(&front) = window.front() {
+            if front + n <= i { window.pop_front(); }
+        }
+        if i + 1 >= n {
+            out[i] = data[*window.front().unwrap()];
+        }
+    }
+
+    Array1::from(out)
+}
+
+/// Donchian channel: returns (upper, middle, lower) where upper is the highest high
+/// and lower the lowest low over the last `n` bars.
+pub fn donchian(high: &Array1<f64>, low: &Array1<f64>, n: usize) -> (Array1<f64>, Array1<f64>, Array1<f64>) {
+    let upper = rolling_max(high, n);
+    let lower = rolling_min(low, n);
+    let middle = (&upper + &lower) / 2.0;
+    (upper, middle, lower)
+}
@@ -1,5 +1,26 @@
 use ndarray::Array1;
 
-pub fn ewm(data: Array1<f64>) {
+use super::kernels::{contiguous, lane_sum};
 
-}
\ No newline at end of file
+/// Exponential moving average with `alpha = 2 / (n + 1)`, seeded with the SMA of the first `n` values.
+/// The recurrence is serial and already one multiply-add per bar, so only the seed is lane-summed.
+pub fn ewm(data: &Array1<f64>, n: usize) -> Array1<f64> {
+    let len = data.len();
+    let mut out = vec![f64::NAN; len];
+    if n == 0 || n > len {
+        return Array1::from(out);
+    }
+
+    let x = contiguous(data);
+    let alpha = 2.0 / (n as f64 + 1.0);
+    let decay = 1.0 - alpha;
+    let mut prev = lane_sum(&x[..n]) / (n as f64);
+    out[n - 1] = prev;
+
+    for (o, &v) in out[n..].iter_mut().zip(&x[n..]) {
+        prev = alpha * v + decay * prev;
+        *o = prev;
+    }
+
+    Array1::from(out)
+}
@@ -0,0 +1,86 @@
+use ndarray::Array1;
+
+use super::donchian::donchian;
+
+/// Ichimoku lines, each aligned with the input bars.
+pub struct Ichimoku {
+    pub tenkan: Array1<f64>,
+    pub kijun: Array1<f64>,
+    pub senkou_a: Array1<f64>,
+    pub senkou_b: Array1<f64>,
+    pub chikou: Array1<f64>,
+}
+
+/// Moves `values` `by` bars later: element `i` of the result is `values[i - by]`, NaN for the
+/// first `by` bars.
+fn shift_forward(values: &Array1<f64>, by: usize) -> Array1<f64> {
+    let len = values.len();
+    Array1::from_iter((0..len).map(|i| if i >= by { values[i - by] } else { f64::NAN }))
+}
+
+/// Moves `values` `by` bars earlier: element `i` of the result is `values[i + by]`, NaN for the
+/// last `by` bars.
+fn shift_back(values: &Array1<f64>, by: usize) -> Array1<f64> {
+    let len = values.len();
+    Array1::from_iter((0..len).map(|i| if i + by < len { values[i + by] } else { f64::NAN }))
+}
+
+/// Ichimoku Kinko Hyo. Tenkan and Kijun are the midpoints of the high-low range over
+/// `tenkan_n` and `kijun_n` bars. Senkou A (their mean) and Senkou B (the midpoint over
+/// `senkou_b_n` bars) are computed `displacement` bars back and plotted at the current bar, so
+/// the cloud at bar `i` is the one known at bar `i - displacement`. Chikou is the close
+/// `displacement` bars ahead plotted at the current bar.
+pub fn ichimoku(
+    high: &Array1<f64>,
+    low: &Array1<f64>,
+    close: &Array1<f64>,
+    tenkan_n: usize,
+    kijun_n: usize,
+    senkou_b_n: usize,
+    displacement: usize,
+) -> Ichimoku {
+    let (_, tenkan, _) = donchian(high, low, tenkan_n);
+    let (_, kijun, _) = donchian(high, low, kijun_n);
+    let (_, span_b, _) = donchian(high, low, senkou_b_n);
+    let span_a = (&tenkan + &kijun) / 2.0;
+    Ichimoku {
+        senkou_a: shift_forward(&span_a, displacement),
+        senkou_b: shift_forward(&span_b, displacement),
+        chikou: shift_back(close, displacement),
+        tenkan,
+        kijun,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn same(a: &Array1<f64>, b: &[f64]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x.is_nan() && y.is_nan()) || x == y)
+    }
+
+    #[test]
+    fn shifts_move_values_and_pad_with_nan() {
+        let v = Array1::from(vec![1.0, 2.0, 3.0, 4.0]);
+        assert!(same(&shift_forward(&v, 2), &[f64::NAN, f64::NAN, 1.0, 2.0]));
+        assert!(same(&shift_back(&v, 1), &[2.0, 3.0, 4.0, f64::NAN]));
+        assert!(same(&shift_forward(&v, 0), &[1.0, 2.0, 3.0, 4.0]));
+        assert!(shift_back(&v, 9).iter().all(|x| x.is_nan()));
+    }
+
+    #[test]
+    fn cloud_is_the_one_known_displacement_bars_ago() {
+        let close = Array1::from_iter((0..30).map(|i| 100.0 + i as f64));
+        let (high, low) = (&close + 1.0, &close - 1.0);
+        let lines = ichimoku(&high, &low, &close, 3, 5, 8, 5);
+        for i in 12..30 {
+            assert_eq!(lines.senkou_a[i], (lines.tenkan[i - 5] + lines.kijun[i - 5]) / 2.0);
+        }
+        // Senkou B needs 8 bars, then sits 5 bars later.
+        assert!(lines.senkou_b[11].is_nan());
+        assert_eq!(lines.senkou_b[12], 100.0 + 3.5);
+        assert_eq!(lines.chikou[0], close[5]);
+        assert!(lines.chikou[25].is_nan());
+    }
+}
@@ -0,0 +1,21 @@
+use ndarray::Array1;
+
+use super::atr::atr;
+use super::ewm::ewm;
+
+/// Keltner channel: returns (upper, middle, lower) where middle is the EMA of close over `n`
+/// bars and the bands sit `multiplier` ATRs (over `atr_n` bars) away from it.
+pub fn keltner(
+    high: &Array1<f64>,
+    low: &Array1<f64>,
+    close: &Array1<f64>,
+    n: usize,
+    atr_n: usize,
+    multiplier: f64,
+) -> (Array1<f64>, Array1<f64>, Array1<f64>) {
+    let middle = ewm(close, n);
+    let range = atr(high, low, close, atr_n) * multiplier;
+    let upper = &middle + &range;
+    let lower = &middle - &range;
+    (upper, middle, lower)
+}
@@ -0,0 +1,162 @@
+//! Lane-chunked building blocks for the rolling indicator loops. Element-wise work is laid out
+//! in fixed `LANES`-wide chunks with independent accumulators so the compiler emits packed SIMD
+//! on stable Rust, and results are written straight into the caller's output buffer. What stays
+//! serial in a rolling window is a single add per bar.
+
+use ndarray::Array1;
+use std::borrow::Cow;
+
+pub const LANES: usize = 4;
+
+/// The values of `data` as one slice, copying only if the array is not contiguous.
+pub fn contiguous(data: &Array1<f64>) -> Cow<'_, [f64]> {
+    match data.as_slice() {
+        Some(s) => Cow::Borrowed(s),
+        None => Cow::Owned(data.iter().cloned().collect()),
+    }
+}
+
+/// Sum using `LANES` independent accumulators.
+pub fn lane_sum(x: &[f64]) -> f64 {
+    let mut acc = [0.0; LANES];
+    let chunks = x.chunks_exact(LANES);
+    let rest = chunks.remainder();
+    for c in chunks {
+        for k in 0..LANES {
+            acc[k] += c[k];
+        }
+    }
+    acc.iter().sum::<f64>() + rest.iter().sum::<f64>()
+}
+
+/// Writes `f(x[j + lag], x[j])` to `out[j]` for every `j < x.len() - lag`.
+#[inline(always)]
+fn lagged_into(out: &mut [f64], x: &[f64], lag: usize, f: impl Fn(f64, f64) -> f64) {
+    let (old, new) = (&x[..x.len() - lag], &x[lag..]);
+    let out = &mut out[..old.len()];
+    let mut out_chunks = out.chunks_exact_mut(LANES);
+    let mut old_chunks = old.chunks_exact(LANES);
+    let mut new_chunks = new.chunks_exact(LANES);
+    for ((o, a), b) in (&mut out_chunks).zip(&mut old_chunks).zip(&mut new_chunks) {
+        for k in 0..LANES {
+            o[k] = f(b[k], a[k]);
+        }
+    }
+    let rest = out_chunks.into_remainder().iter_mut().zip(old_chunks.remainder()).zip(new_chunks.remainder());
+    for ((o, a), b) in rest {
+        *o = f(*b, *a);
+    }
+}
+
+/// Running sum over `out[start..]` in place, continuing from `seed`.
+#[inline(always)]
+fn scan_from(out: &mut [f64], start: usize, seed: f64) {
+    let mut sum = seed;
+    for v in &mut out[start..] {
+        sum += *v;
+        *v = sum;
+    }
+}
+
+/// Writes the sum of every `n`-wide window ending at bar `i` to `out[i]` for `i >= n - 1`;
+/// `out[..n - 1]` is left untouched. Requires `0 < n <= x.len() == out.len()`.
+pub fn rolling_sums_into(out: &mut [f64], x: &[f64], n: usize) {
+    let seed = lane_sum(&x[..n]);
+    // What enters minus what leaves each window, then one running add per bar.
+    lag_deltas_into(&mut out[n..], x, n);
+    out[n - 1] = seed;
+    scan_from(out, n, seed);
+}
+
+/// Writes `x[j + lag] - x[j]` to `out[j]` for every `j < x.len() - lag`.
+pub fn lag_deltas_into(out: &mut [f64], x: &[f64], lag: usize) {
+    lagged_into(out, x, lag, |new, old| new - old);
+}
+
+/// Writes `x[i] - x[i - 1]` to `out[i]` for `i >= 1`.
+pub fn diffs_into(out: &mut [f64], x: &[f64]) {
+    lag_deltas_into(&mut out[1..], x, 1);
+}
+
+/// Multiplies every element by `factor` in place.
+pub fn scale(x: &mut [f64], factor: f64) {
+    let mut chunks = x.chunks_exact_mut(LANES);
+    for c in &mut chunks {
+        for v in c {
+            *v *= factor;
+        }
+    }
+    for v in chunks.into_remainder() {
+        *v *= factor;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::reference::{scalar_ema, scalar_rsi, scalar_sma, scalar_std};
+    use crate::indicators::{ewm::ewm, rsi_method::rsi, sma_method::sma, std_method::rolling_std};
+    use ndarray::s;
+
+    type Kernel = fn(&Array1<f64>, usize) -> Array1<f64>;
+    const KERNELS: [(&str, Kernel, Kernel); 4] = [
+        ("sma", sma, scalar_sma),
+        ("ema", ewm, scalar_ema),
+        ("rolling_std", rolling_std, scalar_std),
+        ("rsi", rsi, scalar_rsi),
+    ];
+
+    /// Deterministic random walk, as in the benches.
+    fn prices(len: usize) -> Array1<f64> {
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut price = 100.0;
+        Array1::from_iter((0..len).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            price *= 1.0 + ((state >> 11) as f64 / (1u64 << 53) as f64 - 0.5) * 0.02;
+            price
+        }))
+    }
+
+    fn assert_agree(name: &str, data: &Array1<f64>, n: usize, chunked: Kernel, scalar: Kernel) {
+        let (a, b) = (chunked(data, n), scalar(data, n));
+        assert_eq!(a.len(), b.len(), "{} len {} n {}", name, data.len(), n);
+        for (i, (x, y)) in a.iter().zip(&b).enumerate() {
+            let close = (x.is_nan() && y.is_nan()) || (x - y).abs() <= 1e-9 * y.abs().max(1.0);
+            assert!(close, "{} len {} n {} bar {}: {} vs {}", name, data.len(), n, i, x, y);
+        }
+    }
+
+    #[test]
+    fn chunked_kernels_match_scalar_loops_around_the_lane_width() {
+        for (name, chunked, scalar) in KERNELS {
+            for len in 2..=3 * LANES + 1 {
+                let data = prices(len);
+                // rsi needs n < len; the others take n == len as well.
+                let longest = if name == "rsi" { len - 1 } else { len };
+                for n in 2..=longest {
+                    assert_agree(name, &data, n, chunked, scalar);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn chunked_kernels_match_scalar_loops_on_long_and_strided_input() {
+        for (name, chunked, scalar) in KERNELS {
+            for len in [LANES * 50, LANES * 50 + 1, LANES * 50 + LANES - 1] {
+                let data = prices(len);
+                for n in [LANES - 1, LANES, LANES + 1, 20] {
+                    assert_agree(name, &data, n, chunked, scalar);
+                }
+            }
+            // Every other element of a longer array is not contiguous, so the kernels copy it first.
+            let strided = prices(2 * (LANES * 25 + 3)).slice_move(s![..;2]);
+            assert!(strided.as_slice().is_none());
+            for n in [LANES, LANES + 1, 14] {
+                assert_agree(name, &strided, n, chunked, scalar);
+            }
+        }
+    }
+}
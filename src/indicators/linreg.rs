@@ -0,0 +1,102 @@
+use ndarray::Array1;
+
+/// Rolling least-squares fit of `y = intercept + slope * x` over the last `n` values, with
+/// x = 0 at the oldest bar of each window. Returns (slope, intercept, r_squared), NaN padded.
+/// Window sums are updated in O(1) per bar instead of refitting each window. r_squared is NaN
+/// for a window without variance, where the fit explains nothing.
+pub fn rolling_linreg(data: &Array1<f64>, n: usize) -> (Array1<f64>, Array1<f64>, Array1<f64>) {
+    let len = data.len();
+    let mut slope = vec![f64::NAN; len];
+    let mut intercept = vec![f64::NAN; len];
+    let mut r2 = vec![f64::NAN; len];
+    if n < 2 || n > len {
+        return (Array1::from(slope), Array1::from(intercept), Array1::from(r2));
+    }
+
+    let nf = n as f64;
+    let sum_x = nf * (nf - 1.0) / 2.0;
+    let sum_xx = (nf - 1.0) * nf * (2.0 * nf - 1.0) / 6.0;
+    let x_den = nf * sum_xx - sum_x * sum_x;
+
+    let mut sum_y = 0.0;
+    let mut sum_yy = 0.0;
+    let mut sum_xy = 0.0;
+    for j in 0..n {
+        let y = data[j];
+        sum_y += y;
+        sum_yy += y * y;
+        sum_xy += (j as f64) * y;
+    }
+
+    for i in (n - 1)..len {
+        if i >= n {
+            // Slide the window: every remaining x shifts down by one and the new value lands at n - 1.
+            let y_old = data[i - n];
+            let y_new = data[i];
+            sum_xy = sum_xy - (sum_y - y_old) + (nf - 1.0) * y_new;
+            sum_y += y_new - y_old;
+            sum_yy += y_new * y_new - y_old * y_old;
+        }
+
+        let cov = nf * sum_xy - sum_x * sum_y;
+        let b = cov / x_den;
+        slope[i] = b;
+        intercept[i] = (sum_y - b * sum_x) / nf;
+
+        // n * sum(y^2) - sum(y)^2 cancels to rounding noise on flat windows at any price level,
+        // so variance below a relative tolerance counts as none.
+        let y_den = nf * sum_yy - sum_y * sum_y;
+        r2[i] = if y_den <= nf * sum_yy * 1e-12 { f64::NAN } else { (cov * cov / (x_den * y_den)).min(1.0) };
+    }
+
+    (Array1::from(slope), Array1::from(intercept), Array1::from(r2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_a_line_exactly() {
+        let data = Array1::from_iter((0..10).map(|i| 5.0 + 2.0 * i as f64));
+        let (slope, intercept, r2) = rolling_linreg(&data, 4);
+        assert!(slope[2].is_nan() && intercept[2].is_nan() && r2[2].is_nan());
+        for i in 3..10 {
+            assert!((slope[i] - 2.0).abs() < 1e-9);
+            // x = 0 is the oldest bar of the window, i - 3.
+            assert!((intercept[i] - (5.0 + 2.0 * (i - 3) as f64)).abs() < 1e-9);
+            assert!((r2[i] - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn sliding_sums_match_a_refit() {
+        let data = Array1::from_iter((0..50).map(|i| 100.0 + (i as f64 * 0.7).sin() * 3.0 + i as f64 * 0.1));
+        let n = 7;
+        let (slope, intercept, r2) = rolling_linreg(&data, n);
+        for i in n - 1..data.len() {
+            let window = data.slice(ndarray::s![i + 1 - n..=i]).to_owned();
+            let (s, c, r) = rolling_linreg(&window, n);
+            assert!((slope[i] - s[n - 1]).abs() < 1e-8);
+            assert!((intercept[i] - c[n - 1]).abs() < 1e-8);
+            assert!((r2[i] - r[n - 1]).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn flat_windows_have_no_r_squared_at_any_price_level() {
+        for level in [1.0, 1e3, 1e5] {
+            let mut data = vec![level * 0.9, level * 1.1, level * 0.95];
+            data.extend(std::iter::repeat_n(level, 20));
+            let (slope, _, r2) = rolling_linreg(&Array1::from(data), 5);
+            assert!(slope[22].abs() < 1e-6 * level);
+            assert!(r2[22].is_nan(), "r2 {} at level {}", r2[22], level);
+        }
+    }
+
+    #[test]
+    fn short_input_is_all_nan() {
+        let (slope, _, _) = rolling_linreg(&Array1::from(vec![1.0, 2.0]), 3);
+        assert!(slope.iter().all(|v| v.is_nan()));
+    }
+}
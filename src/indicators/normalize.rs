@@ -0,0 +1,65 @@
+use ndarray::Array1;
+
+use super::donchian::{rolling_max, rolling_min};
+
+/// Rolling z-score of each value against the mean and sample std of its trailing `n`-bar window.
+/// Windows with zero variance score 0.
+pub fn rolling_zscore(data: &Array1<f64>, n: usize) -> Array1<f64> {
+    let len = data.len();
+    let mut out = vec![f64::NAN; len];
+    if n < 2 || n > len {
+        return Array1::from(out);
+    }
+
+    let nf = n as f64;
+    let mut sum = 0.0;
+    let mut sum_sq = 0.0;
+    for i in 0..len {
+        sum += data[i];
+        sum_sq += data[i] * data[i];
+        if i >= n {
+            sum -= data[i - n];
+            sum_sq -= data[i - n] * data[i - n];
+        }
+        if i + 1 >= n {
+            let mean = sum / nf;
+            let var = ((sum_sq - nf * mean * mean) / (nf - 1.0)).max(0.0);
+            let std = var.sqrt();
+            out[i] = if std > f64::EPSILON { (data[i] - mean) / std } else { 0.0 };
+        }
+    }
+
+    Array1::from(out)
+}
+
+/// Rolling min-max scaling into [0, 1] over the trailing `n`-bar window.
+/// Windows with zero range map to 0.5.
+pub fn rolling_minmax(data: &Array1<f64>, n: usize) -> Array1<f64> {
+    let hi = rolling_max(data, n);
+    let lo = rolling_min(data, n);
+    let mut out = Array1::from(vec![f64::NAN; data.len()]);
+    for i in 0..data.len() {
+        if hi[i].is_nan() { continue; }
+        let range = hi[i] - lo[i];
+        out[i] = if range > f64::EPSILON { (data[i] - lo[i]) / range } else { 0.5 };
+    }
+    out
+}
+
+/// Rolling percent rank: the share of the trailing `n` values (current included) that are
+/// less than or equal to the current value, in (0, 1].
+pub fn rolling_percent_rank(data: &Array1<f64>, n: usize) -> Array1<f64> {
+    let len = data.len();
+    let mut out = vec![f64::NAN; len];
+    if n == 0 || n > len {
+        return Array1::from(out);
+    }
+
+    for i in (n - 1)..len {
+        let current = data[i];
+        let below = (i + 1 - n..=i).filter(|&j| data[j] <= current).count();
+        out[i] = below as f64 / n as f64;
+    }
+
+    Array1::from(out)
+}
@@ -0,0 +1,92 @@
+use ndarray::{s, Array1};
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+
+/// How an indicator fills the warm-up bars before its first complete window.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Padding {
+    /// Keep the input length with NaN for warm-up bars.
+    Nan,
+    /// Drop the warm-up bars; element `k` then lines up with input bar `k + warmup`.
+    Compact,
+    /// Keep the input length, repeating the first computed value over the warm-up bars.
+    Edge,
+}
+
+impl Padding {
+    pub fn parse(name: Option<&str>) -> PyResult<Self> {
+        match name.unwrap_or("nan") {
+            "nan" => Ok(Padding::Nan),
+            "compact" => Ok(Padding::Compact),
+            "edge" => Ok(Padding::Edge),
+            other => Err(PyValueError::new_err(format!(
+                "unknown padding '{}'; expected 'nan', 'compact' or 'edge'", other
+            ))),
+        }
+    }
+
+    /// Applies the policy to indicator output whose first `warmup` bars are padding. The
+    /// warm-up comes from the indicator's window rather than the NaNs it returns, since NaN input
+    /// or a degenerate window can leave NaN after it.
+    pub fn apply_with(self, mut values: Array1<f64>, warmup: usize) -> Array1<f64> {
+        let warmup = warmup.min(values.len());
+        match self {
+            Padding::Nan => values,
+            Padding::Compact => values.slice(s![warmup..]).to_owned(),
+            Padding::Edge => {
+                if let Some(&edge) = values.iter().skip(warmup).find(|v| !v.is_nan()) {
+                    values.slice_mut(s![..warmup]).fill(edge);
+                }
+                values
+            }
+        }
+    }
+
+    /// Applies the policy to several outputs of one indicator with their own warm-ups. Compact
+    /// trims them all by the longest so the arrays stay aligned with each other; edge fills each
+    /// from its own first value.
+    pub fn apply_all_with<const N: usize>(self, values: [Array1<f64>; N], warmups: [usize; N]) -> [Array1<f64>; N] {
+        let longest = warmups.iter().copied().max().unwrap_or(0);
+        let mut warmups = warmups.into_iter();
+        values.map(|v| {
+            let own = warmups.next().unwrap_or(0);
+            self.apply_with(v, if self == Padding::Compact { longest } else { own })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_trims_the_warm_up_even_after_nan_input() {
+        // An SMA(2) over input starting with NaN: the NaN runs past the one-bar warm-up.
+        let values = Array1::from(vec![f64::NAN, f64::NAN, 1.5, 2.5]);
+        let compact = Padding::Compact.apply_with(values.clone(), 1);
+        assert_eq!(compact.len(), 3);
+        assert!(compact[0].is_nan() && compact[1] == 1.5);
+
+        let edge = Padding::Edge.apply_with(values, 1);
+        assert_eq!(edge[0], 1.5);
+        assert!(edge[1].is_nan());
+    }
+
+    #[test]
+    fn grouped_outputs_compact_together_and_edge_on_their_own() {
+        let short = Array1::from(vec![f64::NAN, 1.0, 2.0, 3.0]);
+        let long = Array1::from(vec![f64::NAN, f64::NAN, 5.0, 6.0]);
+        let [a, b] = Padding::Compact.apply_all_with([short.clone(), long.clone()], [1, 2]);
+        assert_eq!((a.to_vec(), b.to_vec()), (vec![2.0, 3.0], vec![5.0, 6.0]));
+
+        let [a, b] = Padding::Edge.apply_all_with([short, long], [1, 2]);
+        assert_eq!((a[0], b[0], b[1]), (1.0, 5.0, 5.0));
+    }
+
+    #[test]
+    fn warm_up_longer_than_the_input_leaves_nothing() {
+        let values = Array1::from(vec![f64::NAN; 3]);
+        assert!(Padding::Compact.apply_with(values.clone(), 9).is_empty());
+        assert_eq!(Padding::Edge.apply_with(values, 9).len(), 3);
+    }
+}
@@ -0,0 +1,106 @@
+use ndarray::Array1;
+
+/// Parabolic SAR (Wilder). Returns (sar, direction): the stop-and-reverse level under an
+/// uptrend or over a downtrend, and 1.0 / -1.0 for the trend it trails. The acceleration
+/// factor starts at `step`, grows by `step` with every new extreme point up to `max_step`,
+/// and resets when the price crosses the SAR, which then flips to the last extreme. The first
+/// bar is NaN; the second seeds the trend from the direction of the close.
+pub fn psar(
+    high: &Array1<f64>,
+    low: &Array1<f64>,
+    close: &Array1<f64>,
+    step: f64,
+    max_step: f64,
+) -> (Array1<f64>, Array1<f64>) {
+    let len = high.len();
+    let mut sar_out = vec![f64::NAN; len];
+    let mut direction = vec![f64::NAN; len];
+    if len < 2 {
+        return (Array1::from(sar_out), Array1::from(direction));
+    }
+
+    let mut up = close[1] >= close[0];
+    let mut sar = if up { low[0] } else { high[0] };
+    let mut extreme = if up { high[1] } else { low[1] };
+    let mut af = step;
+    sar_out[1] = sar;
+    direction[1] = if up { 1.0 } else { -1.0 };
+
+    for i in 2..len {
+        let mut next = sar + af * (extreme - sar);
+        if up {
+            // The SAR never rises into the last two bars' range.
+            next = next.min(low[i - 1]).min(low[i - 2]);
+            if low[i] < next {
+                up = false;
+                next = extreme;
+                extreme = low[i];
+                af = step;
+            } else if high[i] > extreme {
+                extreme = high[i];
+                af = (af + step).min(max_step);
+            }
+        } else {
+            next = next.max(high[i - 1]).max(high[i - 2]);
+            if high[i] > next {
+                up = true;
+                next = extreme;
+                extreme = high[i];
+                af = step;
+            } else if low[i] < extreme {
+                extreme = low[i];
+                af = (af + step).min(max_step);
+            }
+        }
+        sar = next;
+        sar_out[i] = sar;
+        direction[i] = if up { 1.0 } else { -1.0 };
+    }
+
+    (Array1::from(sar_out), Array1::from(direction))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bars one point wide around `closes`.
+    fn bars(closes: &[f64]) -> (Array1<f64>, Array1<f64>, Array1<f64>) {
+        let close = Array1::from(closes.to_vec());
+        (&close + 1.0, &close - 1.0, close)
+    }
+
+    #[test]
+    fn uptrend_trails_below_the_lows_with_growing_acceleration() {
+        let closes: Vec<f64> = (0..12).map(|i| i as f64).collect();
+        let (high, low, close) = bars(&closes);
+        let (sar, direction) = psar(&high, &low, &close, 0.02, 0.2);
+        assert!(sar[0].is_nan() && direction[0].is_nan());
+        assert_eq!(sar[1], -1.0);
+        for i in 1..12 {
+            assert_eq!(direction[i], 1.0);
+            assert!(sar[i] < low[i]);
+        }
+        // Clamped to the low two bars back, then 0.04 of the way to the extreme of 3.
+        assert_eq!(sar[2], -1.0);
+        assert!((sar[3] - (-1.0 + 0.04 * 4.0)).abs() < 1e-12);
+        assert!(sar.windows(2).into_iter().skip(1).all(|w| w[1] >= w[0]));
+    }
+
+    #[test]
+    fn reversal_flips_to_the_last_extreme() {
+        let mut closes: Vec<f64> = (0..10).map(|i| 100.0 + i as f64).collect();
+        closes.push(90.0);
+        let (high, low, close) = bars(&closes);
+        let (sar, direction) = psar(&high, &low, &close, 0.02, 0.2);
+        assert_eq!(direction[9], 1.0);
+        assert_eq!(direction[10], -1.0);
+        assert_eq!(sar[10], high[9]);
+    }
+
+    #[test]
+    fn short_input_is_nan() {
+        let (high, low, close) = bars(&[1.0]);
+        assert!(psar(&high, &low, &close, 0.02, 0.2).0[0].is_nan());
+    }
+}
@@ -0,0 +1,65 @@
+//! Straightforward scalar loops that the lane-chunked kernels must agree with. Only compiled
+//! for tests; `benches/indicators.rs` includes this file to time the kernels against them.
+
+use ndarray::Array1;
+
+pub fn scalar_sma(data: &Array1<f64>, n: usize) -> Array1<f64> {
+    let mut out = vec![f64::NAN; data.len()];
+    let mut sum = 0.0;
+    for i in 0..data.len() {
+        sum += data[i];
+        if i >= n { sum -= data[i - n]; }
+        if i + 1 >= n { out[i] = sum / n as f64; }
+    }
+    Array1::from(out)
+}
+
+pub fn scalar_ema(data: &Array1<f64>, n: usize) -> Array1<f64> {
+    let mut out = vec![f64::NAN; data.len()];
+    let alpha = 2.0 / (n as f64 + 1.0);
+    let mut prev = data.iter().take(n).sum::<f64>() / n as f64;
+    out[n - 1] = prev;
+    for i in n..data.len() {
+        prev = alpha * data[i] + (1.0 - alpha) * prev;
+        out[i] = prev;
+    }
+    Array1::from(out)
+}
+
+pub fn scalar_std(data: &Array1<f64>, n: usize) -> Array1<f64> {
+    let mut out = vec![f64::NAN; data.len()];
+    let nf = n as f64;
+    let (mut sum, mut sum_sq) = (0.0, 0.0);
+    for i in 0..data.len() {
+        sum += data[i];
+        sum_sq += data[i] * data[i];
+        if i >= n {
+            sum -= data[i - n];
+            sum_sq -= data[i - n] * data[i - n];
+        }
+        if i + 1 >= n { out[i] = ((sum_sq - sum * sum / nf) / (nf - 1.0)).max(0.0).sqrt(); }
+    }
+    Array1::from(out)
+}
+
+pub fn scalar_rsi(data: &Array1<f64>, n: usize) -> Array1<f64> {
+    let mut out = vec![f64::NAN; data.len()];
+    let nf = n as f64;
+    let (mut gain, mut loss) = (0.0, 0.0);
+    for i in 1..data.len() {
+        let d = data[i] - data[i - 1];
+        let (g, l) = (d.max(0.0), (-d).max(0.0));
+        if i <= n {
+            gain += g / nf;
+            loss += l / nf;
+        } else {
+            gain = (gain * (nf - 1.0) + g) / nf;
+            loss = (loss * (nf - 1.0) + l) / nf;
+        }
+        // Same flat-window convention as `rsi`: no losses scores 100, no change at all 50.
+        if i >= n {
+            out[i] = if loss > 0.0 { 100.0 - 100.0 / (1.0 + gain / loss) } else if gain > 0.0 { 100.0 } else { 50.0 };
+        }
+    }
+    Array1::from(out)
+}
@@ -0,0 +1,44 @@
+use ndarray::Array1;
+
+use super::kernels::{contiguous, diffs_into};
+
+/// Relative Strength Index with Wilder's smoothing over `n` changes, NaN-padded for the first `n` bars.
+/// A window without losses scores 100, one without any change 50. Only the bar-to-bar changes
+/// are computed in lane chunks; the smoothing that follows is a serial scan.
+pub fn rsi(data: &Array1<f64>, n: usize) -> Array1<f64> {
+    let len = data.len();
+    let mut out = vec![f64::NAN; len];
+    if n == 0 || n >= len {
+        return Array1::from(out);
+    }
+
+    // Bar-to-bar changes are computed in chunks into the output, then smoothed in place.
+    diffs_into(&mut out, &contiguous(data));
+    let k = 1.0 / n as f64;
+    let (mut avg_gain, mut avg_loss) = (0.0, 0.0);
+    for &d in &out[1..=n] {
+        avg_gain += d.max(0.0);
+        avg_loss += (-d).max(0.0);
+    }
+    avg_gain *= k;
+    avg_loss *= k;
+    out[1..n].fill(f64::NAN);
+    out[n] = strength(avg_gain, avg_loss);
+
+    for v in &mut out[n + 1..] {
+        let d = *v;
+        avg_gain += (d.max(0.0) - avg_gain) * k;
+        avg_loss += ((-d).max(0.0) - avg_loss) * k;
+        *v = strength(avg_gain, avg_loss);
+    }
+
+    Array1::from(out)
+}
+
+fn strength(avg_gain: f64, avg_loss: f64) -> f64 {
+    if avg_loss <= 0.0 {
+        if avg_gain <= 0.0 { 50.0 } else { 100.0 }
+    } else {
+        100.0 - 100.0 / (1.0 + avg_gain / avg_loss)
+    }
+}
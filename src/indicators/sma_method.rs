@@ -1,45 +1,24 @@
-use ndarray::Array1;
+use ndarray::{s, Array1};
+
+use super::kernels::{contiguous, rolling_sums_into, scale};
 
 pub fn _sma(data: &Array1<f64>, n: usize) -> Array1<f64> {
     assert!(n > 0, "window size must be > 0");
     let len = data.len();
     assert!(n <= len, "window size must be <= data length");
 
-    let slice: Vec<f64> = match data.as_slice_memory_order() {
-        Some(s) => s.to_vec(),
-        None => data.iter().cloned().collect(),
-    };
-
-    let out_len = len - n + 1;
-    let mut out = Vec::with_capacity(out_len);
-
-    let mut sum: f64 = 0.0;
-    for i in 0..n {
-        sum += slice[i];
-    }
-    out.push(sum / (n as f64));
-
-    for i in n..len {
-        sum += slice[i];
-        sum -= slice[i - n];
-        out.push(sum / (n as f64));
-    }
-
-    Array1::from(out)
+    sma(data, n).slice(s![n - 1..]).to_owned()
 }
 
 
 pub fn sma(data: &Array1<f64>, n: usize) -> Array1<f64> {
     let len = data.len();
+    let mut out = vec![f64::NAN; len];
     if n == 0 || n > len {
-        return Array1::from(vec![f64::NAN; len]);
+        return Array1::from(out);
     }
 
-    let compact = _sma(data, n);
-    let mut padded = vec![f64::NAN; len];
-    let start = n - 1;
-    for (i, &v) in compact.as_slice().expect("compact contiguous").iter().enumerate() {
-        padded[start + i] = v;
-    }
-    Array1::from(padded)
+    rolling_sums_into(&mut out, &contiguous(data), n);
+    scale(&mut out[n - 1..], 1.0 / n as f64);
+    Array1::from(out)
 }
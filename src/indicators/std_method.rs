@@ -0,0 +1,33 @@
+use ndarray::Array1;
+
+use super::kernels::{contiguous, lag_deltas_into, lane_sum};
+
+/// Sample standard deviation of each trailing `n`-bar window, NaN-padded for the first `n - 1` bars.
+/// Only the lag-`n` deltas of the window sum are computed in lane chunks; the running sums
+/// are a serial scan.
+pub fn rolling_std(data: &Array1<f64>, n: usize) -> Array1<f64> {
+    let len = data.len();
+    let mut out = vec![f64::NAN; len];
+    if n < 2 || n > len {
+        return Array1::from(out);
+    }
+
+    let x = contiguous(data);
+    let inv_n = 1.0 / n as f64;
+    let inv_dof = 1.0 / (n as f64 - 1.0);
+    let std = |s: f64, q: f64| ((q - s * s * inv_n) * inv_dof).max(0.0).sqrt();
+
+    // Window-sum deltas are computed in chunks into the output; the sum of squares runs alongside.
+    lag_deltas_into(&mut out[n..], &x, n);
+    let mut sum = lane_sum(&x[..n]);
+    let mut sum_sq = x[..n].iter().map(|v| v * v).sum::<f64>();
+    out[n - 1] = std(sum, sum_sq);
+    for i in n..len {
+        let (new, old) = (x[i], x[i - n]);
+        sum += out[i];
+        sum_sq += (new - old) * (new + old);
+        out[i] = std(sum, sum_sq);
+    }
+
+    Array1::from(out)
+}
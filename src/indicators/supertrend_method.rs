@@ -0,0 +1,90 @@
+use ndarray::Array1;
+
+use super::atr::atr;
+
+/// SuperTrend over ATR(`n`). Returns (level, direction): bands sit `multiplier` ATRs above and
+/// below the bar midpoint, the lower band only ratchets up and the upper band only down while
+/// the close stays inside them, and the trend flips to 1.0 (up) on a close above the upper
+/// band or -1.0 (down) on a close below the lower one. The level is the lower band in an
+/// uptrend and the upper band in a downtrend. NaN padded for the first `n - 1` bars.
+pub fn supertrend(
+    high: &Array1<f64>,
+    low: &Array1<f64>,
+    close: &Array1<f64>,
+    n: usize,
+    multiplier: f64,
+) -> (Array1<f64>, Array1<f64>) {
+    let len = high.len();
+    let mut level = vec![f64::NAN; len];
+    let mut direction = vec![f64::NAN; len];
+    let range = atr(high, low, close, n);
+    if n == 0 || n > len {
+        return (Array1::from(level), Array1::from(direction));
+    }
+
+    let first = n - 1;
+    let mid = (high[first] + low[first]) / 2.0;
+    let mut upper = mid + multiplier * range[first];
+    let mut lower = mid - multiplier * range[first];
+    let mut up = close[first] >= mid;
+    level[first] = if up { lower } else { upper };
+    direction[first] = if up { 1.0 } else { -1.0 };
+
+    for i in n..len {
+        let mid = (high[i] + low[i]) / 2.0;
+        let basic_upper = mid + multiplier * range[i];
+        let basic_lower = mid - multiplier * range[i];
+        upper = if basic_upper < upper || close[i - 1] > upper { basic_upper } else { upper };
+        lower = if basic_lower > lower || close[i - 1] < lower { basic_lower } else { lower };
+        if up && close[i] < lower {
+            up = false;
+        } else if !up && close[i] > upper {
+            up = true;
+        }
+        level[i] = if up { lower } else { upper };
+        direction[i] = if up { 1.0 } else { -1.0 };
+    }
+
+    (Array1::from(level), Array1::from(direction))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bars(closes: &[f64]) -> (Array1<f64>, Array1<f64>, Array1<f64>) {
+        let close = Array1::from(closes.to_vec());
+        (&close + 1.0, &close - 1.0, close)
+    }
+
+    #[test]
+    fn uptrend_level_ratchets_up_below_the_close() {
+        let closes: Vec<f64> = (0..30).map(|i| 100.0 + i as f64).collect();
+        let (high, low, close) = bars(&closes);
+        let (level, direction) = supertrend(&high, &low, &close, 5, 2.0);
+        assert!(level[3].is_nan() && direction[3].is_nan());
+        for i in 4..30 {
+            assert_eq!(direction[i], 1.0);
+            assert!(level[i] < close[i]);
+        }
+        assert!(level.slice(ndarray::s![4..]).windows(2).into_iter().all(|w| w[1] >= w[0]));
+    }
+
+    #[test]
+    fn close_below_the_lower_band_flips_down() {
+        let mut closes: Vec<f64> = (0..20).map(|i| 100.0 + i as f64).collect();
+        closes.push(80.0);
+        let (high, low, close) = bars(&closes);
+        let (level, direction) = supertrend(&high, &low, &close, 5, 2.0);
+        assert_eq!(direction[19], 1.0);
+        assert_eq!(direction[20], -1.0);
+        assert!(level[20] > close[20]);
+    }
+
+    #[test]
+    fn window_longer_than_input_is_nan() {
+        let (high, low, close) = bars(&[1.0, 2.0]);
+        let (level, direction) = supertrend(&high, &low, &close, 3, 3.0);
+        assert!(level.iter().chain(direction.iter()).all(|v| v.is_nan()));
+    }
+}
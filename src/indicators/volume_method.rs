@@ -0,0 +1,61 @@
+use ndarray::Array1;
+
+use super::sma_method::sma;
+
+/// On-Balance Volume: cumulative volume signed by the direction of the close-to-close move.
+pub fn obv(close: &Array1<f64>, volume: &Array1<f64>) -> Array1<f64> {
+    let len = close.len();
+    let mut out = Vec::with_capacity(len);
+    let mut total = 0.0;
+    for i in 0..len {
+        if i > 0 {
+            if close[i] > close[i - 1] { total += volume[i]; }
+            else if close[i] < close[i - 1] { total -= volume[i]; }
+        }
+        out.push(total);
+    }
+    Array1::from(out)
+}
+
+pub fn volume_sma(volume: &Array1<f64>, n: usize) -> Array1<f64> {
+    sma(volume, n)
+}
+
+/// Money Flow Index over `n` bars, NaN padded for the first `n` bars (it needs `n` price changes).
+pub fn mfi(high: &Array1<f64>, low: &Array1<f64>, close: &Array1<f64>, volume: &Array1<f64>, n: usize) -> Array1<f64> {
+    let len = close.len();
+    let mut out = vec![f64::NAN; len];
+    if n == 0 || n >= len {
+        return Array1::from(out);
+    }
+
+    let typical: Vec<f64> = (0..len).map(|i| (high[i] + low[i] + close[i]) / 3.0).collect();
+
+    // Signed raw money flow per bar; index 0 has no previous bar and contributes nothing.
+    let mut positive = vec![0.0; len];
+    let mut negative = vec![0.0; len];
+    for i in 1..len {
+        let flow = typical[i] * volume[i];
+        if typical[i] > typical[i - 1] { positive[i] = flow; }
+        else if typical[i] < typical[i - 1] { negative[i] = flow; }
+    }
+
+    let mut pos_sum: f64 = positive[1..=n].iter().sum();
+    let mut neg_sum: f64 = negative[1..=n].iter().sum();
+    out[n] = money_flow_ratio(pos_sum, neg_sum);
+
+    for i in (n + 1)..len {
+        pos_sum += positive[i] - positive[i - n];
+        neg_sum += negative[i] - negative[i - n];
+        out[i] = money_flow_ratio(pos_sum, neg_sum);
+    }
+
+    Array1::from(out)
+}
+
+fn money_flow_ratio(pos_sum: f64, neg_sum: f64) -> f64 {
+    if neg_sum <= 0.0 {
+        return if pos_sum > 0.0 { 100.0 } else { 50.0 };
+    }
+    100.0 - 100.0 / (1.0 + pos_sum / neg_sum)
+}
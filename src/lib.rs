@@ -1,17 +1,73 @@
+mod analysis;
 mod backtest_engine;
-mod indicators;
+pub mod bench;
+mod cv;
+mod fetch;
+pub mod indicators;
+mod logging;
+mod patterns;
+mod portfolio;
+mod rng;
+mod series;
+mod stats;
+mod synthetic;
+mod timestamps;
 
-use backtest_engine::BacktestEngine;
-use indicators::Indicator;
+pub use backtest_engine::{BacktestEngine, BacktestEngineBuilder};
+use backtest_engine::{DebugEngine, HistoryBuffer, LazyDetails};
+use indicators::{
+    donchian_channel, ema, ichimoku, keltner_channel, linear_regression, money_flow_index, obv, parabolic_sar,
+    rolling_correlation, rolling_covariance, rolling_minmax, rolling_percent_rank, rolling_std, rolling_zscore, rsi,
+    sma_indicator, supertrend, volume_sma, Indicator,
+};
 use pyo3::prelude::*;
 
 use crate::indicators::INDICATORS;
 
 #[pymodule]
 fn tradekit_rust(py: Python, m: &PyModule) -> PyResult<()> {
+    logging::init();
+
     m.add_class::<BacktestEngine>()?;
+    m.add_class::<LazyDetails>()?;
+    m.add_class::<HistoryBuffer>()?;
+    m.add_class::<DebugEngine>()?;
+    m.add_class::<series::Bar>()?;
+    m.add_class::<series::PriceSeries>()?;
     m.add_class::<Indicator>()?;
     m.add_class::<INDICATORS>()?;
+    m.add_function(wrap_pyfunction!(sma_indicator, m)?)?;
+    m.add_function(wrap_pyfunction!(ema, m)?)?;
+    m.add_function(wrap_pyfunction!(donchian_channel, m)?)?;
+    m.add_function(wrap_pyfunction!(keltner_channel, m)?)?;
+    m.add_function(wrap_pyfunction!(obv, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_sma, m)?)?;
+    m.add_function(wrap_pyfunction!(money_flow_index, m)?)?;
+    m.add_function(wrap_pyfunction!(linear_regression, m)?)?;
+    m.add_function(wrap_pyfunction!(rolling_zscore, m)?)?;
+    m.add_function(wrap_pyfunction!(rolling_minmax, m)?)?;
+    m.add_function(wrap_pyfunction!(rolling_percent_rank, m)?)?;
+    m.add_function(wrap_pyfunction!(rolling_std, m)?)?;
+    m.add_function(wrap_pyfunction!(rsi, m)?)?;
+    m.add_function(wrap_pyfunction!(rolling_correlation, m)?)?;
+    m.add_function(wrap_pyfunction!(rolling_covariance, m)?)?;
+    m.add_function(wrap_pyfunction!(ichimoku, m)?)?;
+    m.add_function(wrap_pyfunction!(parabolic_sar, m)?)?;
+    m.add_function(wrap_pyfunction!(supertrend, m)?)?;
+
+    m.add_function(wrap_pyfunction!(fetch::fetch_data, m)?)?;
+
+    m.add_function(wrap_pyfunction!(logging::set_log_level, m)?)?;
+    m.add_function(wrap_pyfunction!(logging::log_to_python, m)?)?;
+
+    m.add_function(wrap_pyfunction!(bench::benchmark, m)?)?;
+
+    patterns::register(py, m)?;
+    stats::register(py, m)?;
+    cv::register(py, m)?;
+    synthetic::register(py, m)?;
+    analysis::register(py, m)?;
+    portfolio::register(py, m)?;
 
     Ok(())
 } 
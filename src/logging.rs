@@ -0,0 +1,73 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Crate-wide logger. Records go to stderr by default, or to Python's `logging` module under
+// the "tradekit_rust" logger once `log_to_python(True)` is called.
+
+static FORWARD_TO_PYTHON: AtomicBool = AtomicBool::new(false);
+static LOGGER: EngineLogger = EngineLogger;
+
+struct EngineLogger;
+
+impl Log for EngineLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) { return; }
+
+        if FORWARD_TO_PYTHON.load(Ordering::Relaxed) {
+            let forwarded = Python::with_gil(|py| -> PyResult<()> {
+                let logger = py.import("logging")?.call_method1("getLogger", ("tradekit_rust",))?;
+                logger.call_method1("log", (python_level(record.level()), record.args().to_string()))?;
+                Ok(())
+            });
+            if forwarded.is_ok() { return; }
+        }
+        eprintln!("[{}] {}", record.level(), record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+/// Numeric levels used by Python's `logging`.
+fn python_level(level: Level) -> i32 {
+    match level {
+        Level::Error => 40,
+        Level::Warn => 30,
+        Level::Info => 20,
+        Level::Debug | Level::Trace => 10,
+    }
+}
+
+/// Installs the logger. Warnings and errors are shown by default.
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Warn);
+    }
+}
+
+/// Sets the minimum level logged by the Rust side: "off", "error", "warn", "info", "debug" or "trace".
+#[pyfunction]
+pub fn set_log_level(level: &str) -> PyResult<()> {
+    let filter = match level.to_ascii_lowercase().as_str() {
+        "off" => LevelFilter::Off,
+        "error" => LevelFilter::Error,
+        "warn" | "warning" => LevelFilter::Warn,
+        "info" => LevelFilter::Info,
+        "debug" => LevelFilter::Debug,
+        "trace" => LevelFilter::Trace,
+        other => return Err(PyValueError::new_err(format!("unknown log level '{}'", other))),
+    };
+    log::set_max_level(filter);
+    Ok(())
+}
+
+/// Routes Rust log records to Python's `logging.getLogger("tradekit_rust")` instead of stderr.
+#[pyfunction]
+pub fn log_to_python(enabled: bool) {
+    FORWARD_TO_PYTHON.store(enabled, Ordering::Relaxed);
+}
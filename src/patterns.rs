@@ -0,0 +1,186 @@
+use ndarray::Array1;
+use numpy::{PyArray1, PyReadonlyArray1};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+// Candlestick pattern detectors. Each returns an array aligned with the input where
+// 1 marks a bullish occurrence, -1 a bearish one and 0 no pattern on that bar.
+
+pub const PATTERN_NAMES: [&str; 4] = ["doji", "engulfing", "hammer", "morning_star"];
+
+const DEFAULT_DOJI_BODY_RATIO: f64 = 0.1;
+
+fn body(open: f64, close: f64) -> f64 {
+    (close - open).abs()
+}
+
+/// Doji: the real body is at most `body_ratio` of the bar's range. Always reported as 1.
+pub fn doji(open: &Array1<f64>, high: &Array1<f64>, low: &Array1<f64>, close: &Array1<f64>, body_ratio: f64) -> Array1<i32> {
+    let len = close.len();
+    let mut out = Array1::zeros(len);
+    for i in 0..len {
+        let range = high[i] - low[i];
+        if range > 0.0 && body(open[i], close[i]) <= body_ratio * range {
+            out[i] = 1;
+        }
+    }
+    out
+}
+
+/// Engulfing: the current body fully covers the previous, opposite-coloured body.
+pub fn engulfing(open: &Array1<f64>, close: &Array1<f64>) -> Array1<i32> {
+    let len = close.len();
+    let mut out = Array1::zeros(len);
+    for i in 1..len {
+        let prev_bearish = close[i - 1] < open[i - 1];
+        let prev_bullish = close[i - 1] > open[i - 1];
+        let bullish = close[i] > open[i];
+        let bearish = close[i] < open[i];
+
+        if prev_bearish && bullish && open[i] <= close[i - 1] && close[i] >= open[i - 1] {
+            out[i] = 1;
+        } else if prev_bullish && bearish && open[i] >= close[i - 1] && close[i] <= open[i - 1] {
+            out[i] = -1;
+        }
+    }
+    out
+}
+
+/// Hammer: small body near the top of the range with a lower shadow at least twice the body.
+/// The inverted shape (shooting star) is reported as -1.
+pub fn hammer(open: &Array1<f64>, high: &Array1<f64>, low: &Array1<f64>, close: &Array1<f64>) -> Array1<i32> {
+    let len = close.len();
+    let mut out = Array1::zeros(len);
+    for i in 0..len {
+        let b = body(open[i], close[i]);
+        if b <= 0.0 { continue; }
+        let upper_shadow = high[i] - open[i].max(close[i]);
+        let lower_shadow = open[i].min(close[i]) - low[i];
+
+        if lower_shadow >= 2.0 * b && upper_shadow <= b {
+            out[i] = 1;
+        } else if upper_shadow >= 2.0 * b && lower_shadow <= b {
+            out[i] = -1;
+        }
+    }
+    out
+}
+
+/// Morning star (1) / evening star (-1): a long candle, a small-bodied candle, then a candle
+/// of the opposite colour closing beyond the midpoint of the first body. Marked on the third bar.
+pub fn morning_star(open: &Array1<f64>, high: &Array1<f64>, low: &Array1<f64>, close: &Array1<f64>) -> Array1<i32> {
+    let len = close.len();
+    let mut out = Array1::zeros(len);
+    for i in 2..len {
+        let (a, b, c) = (i - 2, i - 1, i);
+        let first_body = body(open[a], close[a]);
+        let first_range = high[a] - low[a];
+        if first_range <= 0.0 || first_body < 0.5 * first_range { continue; }
+        if body(open[b], close[b]) > 0.3 * first_body { continue; }
+
+        let first_mid = (open[a] + close[a]) / 2.0;
+        if close[a] < open[a] && close[c] > open[c] && close[c] > first_mid {
+            out[i] = 1;
+        } else if close[a] > open[a] && close[c] < open[c] && close[c] < first_mid {
+            out[i] = -1;
+        }
+    }
+    out
+}
+
+/// Runs the detector registered under `name`, or None if the name is unknown.
+pub fn detect(name: &str, open: &Array1<f64>, high: &Array1<f64>, low: &Array1<f64>, close: &Array1<f64>) -> Option<Array1<i32>> {
+    match name {
+        "doji" => Some(doji(open, high, low, close, DEFAULT_DOJI_BODY_RATIO)),
+        "engulfing" => Some(engulfing(open, close)),
+        "hammer" => Some(hammer(open, high, low, close)),
+        "morning_star" => Some(morning_star(open, high, low, close)),
+        _ => None,
+    }
+}
+
+// ----------------- Python bindings -----------------
+
+type Ohlc = (Array1<f64>, Array1<f64>, Array1<f64>, Array1<f64>);
+
+fn to_ohlc(
+    open: PyReadonlyArray1<f64>,
+    high: PyReadonlyArray1<f64>,
+    low: PyReadonlyArray1<f64>,
+    close: PyReadonlyArray1<f64>,
+) -> PyResult<Ohlc> {
+    let open = open.as_array().to_owned();
+    let high = high.as_array().to_owned();
+    let low = low.as_array().to_owned();
+    let close = close.as_array().to_owned();
+    let len = close.len();
+    if open.len() != len || high.len() != len || low.len() != len {
+        return Err(PyValueError::new_err("open, high, low and close must have the same length"));
+    }
+    Ok((open, high, low, close))
+}
+
+#[pyfunction]
+#[pyo3(name = "doji")]
+fn py_doji<'py>(
+    py: Python<'py>,
+    open: PyReadonlyArray1<f64>,
+    high: PyReadonlyArray1<f64>,
+    low: PyReadonlyArray1<f64>,
+    close: PyReadonlyArray1<f64>,
+    body_ratio: Option<f64>,
+) -> PyResult<&'py PyArray1<i32>> {
+    let (o, h, l, c) = to_ohlc(open, high, low, close)?;
+    Ok(PyArray1::from_owned_array(py, doji(&o, &h, &l, &c, body_ratio.unwrap_or(DEFAULT_DOJI_BODY_RATIO))))
+}
+
+#[pyfunction]
+#[pyo3(name = "engulfing")]
+fn py_engulfing<'py>(
+    py: Python<'py>,
+    open: PyReadonlyArray1<f64>,
+    high: PyReadonlyArray1<f64>,
+    low: PyReadonlyArray1<f64>,
+    close: PyReadonlyArray1<f64>,
+) -> PyResult<&'py PyArray1<i32>> {
+    let (o, _h, _l, c) = to_ohlc(open, high, low, close)?;
+    Ok(PyArray1::from_owned_array(py, engulfing(&o, &c)))
+}
+
+#[pyfunction]
+#[pyo3(name = "hammer")]
+fn py_hammer<'py>(
+    py: Python<'py>,
+    open: PyReadonlyArray1<f64>,
+    high: PyReadonlyArray1<f64>,
+    low: PyReadonlyArray1<f64>,
+    close: PyReadonlyArray1<f64>,
+) -> PyResult<&'py PyArray1<i32>> {
+    let (o, h, l, c) = to_ohlc(open, high, low, close)?;
+    Ok(PyArray1::from_owned_array(py, hammer(&o, &h, &l, &c)))
+}
+
+#[pyfunction]
+#[pyo3(name = "morning_star")]
+fn py_morning_star<'py>(
+    py: Python<'py>,
+    open: PyReadonlyArray1<f64>,
+    high: PyReadonlyArray1<f64>,
+    low: PyReadonlyArray1<f64>,
+    close: PyReadonlyArray1<f64>,
+) -> PyResult<&'py PyArray1<i32>> {
+    let (o, h, l, c) = to_ohlc(open, high, low, close)?;
+    Ok(PyArray1::from_owned_array(py, morning_star(&o, &h, &l, &c)))
+}
+
+/// Builds the `tradekit_rust.patterns` submodule.
+pub fn register(py: Python<'_>, parent: &PyModule) -> PyResult<()> {
+    let m = PyModule::new(py, "patterns")?;
+    m.add_function(wrap_pyfunction!(py_doji, m)?)?;
+    m.add_function(wrap_pyfunction!(py_engulfing, m)?)?;
+    m.add_function(wrap_pyfunction!(py_hammer, m)?)?;
+    m.add_function(wrap_pyfunction!(py_morning_star, m)?)?;
+    m.add("PATTERN_NAMES", PATTERN_NAMES.to_vec())?;
+    parent.add_submodule(m)?;
+    Ok(())
+}
@@ -0,0 +1,196 @@
+// Return covariance across the tickers of a `run` result and simple allocation schemes built
+// on it (inverse volatility, equal risk contribution), for portfolio construction on top of
+// per-ticker results.
+
+use ndarray::Array2;
+use numpy::{PyArray2, PyReadonlyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
+
+use crate::stats::{cov_sample, pct_changes};
+
+const SOURCE_NAMES: [&str; 2] = ["strategy", "asset"];
+const ERC_MAX_ITER: usize = 1000;
+const ERC_TOLERANCE: f64 = 1e-10;
+
+/// Per-bar returns of every ticker over the dates all of them share, in `keys()` order.
+/// "strategy" returns come from each ticker's `balance_history`, "asset" returns from its
+/// `closes`.
+fn aligned_returns(results: &PyAny, source: &str) -> PyResult<(Vec<String>, Vec<Vec<f64>>)> {
+    let column = match source {
+        "strategy" => "balance_history",
+        "asset" => "closes",
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "unknown returns source '{}', expected one of {}", other, SOURCE_NAMES.join(", ")
+            )))
+        }
+    };
+    let details = results.get_item("details").map_err(|_| PyValueError::new_err("results have no 'details'"))?;
+    let mut tickers = Vec::new();
+    let mut series: Vec<HashMap<String, f64>> = Vec::new();
+    for ticker in details.call_method0("keys")?.iter()? {
+        let ticker: String = ticker?.extract()?;
+        let detail = details.get_item(&ticker)?;
+        let dates: Vec<String> = detail.get_item("dates")?.extract()?;
+        let values: Vec<f64> = detail.get_item(column)?.extract()?;
+        series.push(dates.into_iter().zip(values).collect());
+        tickers.push(ticker);
+    }
+    if tickers.len() < 2 {
+        return Err(PyValueError::new_err(format!("results have {} tickers, at least 2 are needed", tickers.len())));
+    }
+
+    let mut common: Vec<&String> = series[0].keys()
+        .filter(|d| series[1..].iter().all(|s| s.contains_key(*d)))
+        .collect();
+    common.sort();
+    if common.len() < 3 {
+        return Err(PyValueError::new_err(format!("the tickers share {} dates, at least 3 are needed", common.len())));
+    }
+    let returns = series.iter()
+        .map(|s| pct_changes(&common.iter().map(|d| s[*d]).collect()))
+        .collect();
+    Ok((tickers, returns))
+}
+
+fn covariance(returns: &[Vec<f64>]) -> Array2<f64> {
+    Array2::from_shape_fn((returns.len(), returns.len()), |(a, b)| cov_sample(&returns[a], &returns[b]))
+}
+
+fn correlation(cov: &Array2<f64>) -> Array2<f64> {
+    Array2::from_shape_fn(cov.dim(), |(a, b)| {
+        let scale = (cov[[a, a]] * cov[[b, b]]).sqrt();
+        if a == b { 1.0 } else if scale > 0.0 { cov[[a, b]] / scale } else { 0.0 }
+    })
+}
+
+fn matrix_to_py(py: Python<'_>, tickers: Vec<String>, matrix: Array2<f64>, observations: usize) -> PyResult<PyObject> {
+    let out = PyDict::new(py);
+    out.set_item("tickers", tickers)?;
+    out.set_item("matrix", PyArray2::from_owned_array(py, matrix))?;
+    out.set_item("observations", observations)?;
+    Ok(out.to_object(py))
+}
+
+/// (tickers, covariance) from a `covariance_matrix` result, or computed from a `run` result.
+fn covariance_input(source: &PyAny, returns: &str) -> PyResult<(Vec<String>, Array2<f64>)> {
+    if let Ok(matrix) = source.get_item("matrix") {
+        let tickers: Vec<String> = source.get_item("tickers")?.extract()?;
+        let matrix: PyReadonlyArray2<f64> = matrix.extract()?;
+        let matrix = matrix.as_array().to_owned();
+        if matrix.dim() != (tickers.len(), tickers.len()) {
+            return Err(PyValueError::new_err(format!(
+                "a {:?} covariance matrix does not match {} tickers", matrix.dim(), tickers.len()
+            )));
+        }
+        return Ok((tickers, matrix));
+    }
+    let (tickers, r) = aligned_returns(source, returns)?;
+    Ok((tickers, covariance(&r)))
+}
+
+/// Ticker -> weight dict.
+fn weights_to_py(py: Python<'_>, tickers: &[String], weights: &[f64]) -> PyResult<PyObject> {
+    let out = PyDict::new(py);
+    for (ticker, w) in tickers.iter().zip(weights) {
+        out.set_item(ticker, *w)?;
+    }
+    Ok(out.to_object(py))
+}
+
+/// Weights proportional to 1 / volatility, summing to 1.
+fn inverse_volatility(cov: &Array2<f64>) -> Vec<f64> {
+    let inverse: Vec<f64> = (0..cov.nrows())
+        .map(|k| if cov[[k, k]] > 0.0 { 1.0 / cov[[k, k]].sqrt() } else { 0.0 })
+        .collect();
+    let total: f64 = inverse.iter().sum();
+    inverse.iter().map(|w| if total > 0.0 { w / total } else { 0.0 }).collect()
+}
+
+/// Long-only weights whose risk contributions w_k (Σw)_k are all equal, by cyclical coordinate
+/// descent on ½ w'Σw - Σ ln(w_k) / n, then scaled to sum to 1. Tickers with no variance are
+/// left out at 0.
+fn equal_risk_contribution(cov: &Array2<f64>, max_iter: usize, tolerance: f64) -> Vec<f64> {
+    let n = cov.nrows();
+    let active: Vec<usize> = (0..n).filter(|&k| cov[[k, k]] > 0.0).collect();
+    let budget = 1.0 / active.len().max(1) as f64;
+    let mut w = inverse_volatility(cov);
+    for _ in 0..max_iter {
+        let mut largest_step: f64 = 0.0;
+        for &k in &active {
+            let others: f64 = active.iter().filter(|&&j| j != k).map(|&j| cov[[k, j]] * w[j]).sum();
+            let next = (-others + (others * others + 4.0 * cov[[k, k]] * budget).sqrt()) / (2.0 * cov[[k, k]]);
+            largest_step = largest_step.max((next - w[k]).abs());
+            w[k] = next;
+        }
+        if largest_step < tolerance {
+            break;
+        }
+    }
+    let total: f64 = w.iter().sum();
+    w.iter().map(|v| if total > 0.0 { v / total } else { 0.0 }).collect()
+}
+
+/// Sample covariance of per-bar returns between every pair of tickers in a `run` result,
+/// over the dates all tickers share. `returns` is "strategy" (default; each ticker's equity
+/// curve) or "asset" (its closes). Returns {"tickers", "matrix", "observations"}; the matrix
+/// is per bar, so multiply by the bars per year to annualize.
+#[pyfunction]
+#[pyo3(name = "covariance_matrix")]
+fn py_covariance_matrix(py: Python<'_>, results: &PyAny, returns: Option<&str>) -> PyResult<PyObject> {
+    let (tickers, r) = aligned_returns(results, returns.unwrap_or("strategy"))?;
+    let observations = r[0].len();
+    matrix_to_py(py, tickers, covariance(&r), observations)
+}
+
+/// Correlation of per-bar returns between every pair of tickers, computed like
+/// `covariance_matrix`. Tickers with flat returns correlate 0 with the others.
+#[pyfunction]
+#[pyo3(name = "correlation_matrix")]
+fn py_correlation_matrix(py: Python<'_>, results: &PyAny, returns: Option<&str>) -> PyResult<PyObject> {
+    let (tickers, r) = aligned_returns(results, returns.unwrap_or("strategy"))?;
+    let observations = r[0].len();
+    matrix_to_py(py, tickers, correlation(&covariance(&r)), observations)
+}
+
+/// Ticker -> weight proportional to the inverse of its return volatility, summing to 1.
+/// `source` is a `run` result (with `returns` as in `covariance_matrix`) or a
+/// `covariance_matrix` result, which may be edited first (e.g. shrunk). Tickers whose returns
+/// never move get 0.
+#[pyfunction]
+#[pyo3(name = "inverse_volatility_weights")]
+fn py_inverse_volatility_weights(py: Python<'_>, source: &PyAny, returns: Option<&str>) -> PyResult<PyObject> {
+    let (tickers, cov) = covariance_input(source, returns.unwrap_or("strategy"))?;
+    weights_to_py(py, &tickers, &inverse_volatility(&cov))
+}
+
+/// Ticker -> long-only weight, summing to 1, at which every ticker contributes the same share
+/// of portfolio variance (risk parity). Takes the same `source` and `returns` as
+/// `inverse_volatility_weights`; iterates until no weight moves by more than `tolerance`
+/// (default 1e-10) or for `max_iter` (default 1000) sweeps.
+#[pyfunction]
+#[pyo3(name = "equal_risk_contribution_weights")]
+fn py_equal_risk_contribution_weights(
+    py: Python<'_>,
+    source: &PyAny,
+    returns: Option<&str>,
+    max_iter: Option<usize>,
+    tolerance: Option<f64>,
+) -> PyResult<PyObject> {
+    let (tickers, cov) = covariance_input(source, returns.unwrap_or("strategy"))?;
+    let weights = equal_risk_contribution(&cov, max_iter.unwrap_or(ERC_MAX_ITER), tolerance.unwrap_or(ERC_TOLERANCE));
+    weights_to_py(py, &tickers, &weights)
+}
+
+pub fn register(py: Python<'_>, parent: &PyModule) -> PyResult<()> {
+    let m = PyModule::new(py, "portfolio")?;
+    m.add_function(wrap_pyfunction!(py_covariance_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(py_correlation_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(py_inverse_volatility_weights, m)?)?;
+    m.add_function(wrap_pyfunction!(py_equal_risk_contribution_weights, m)?)?;
+    parent.add_submodule(m)?;
+    Ok(())
+}
@@ -0,0 +1,347 @@
+// OHLCV bars and bar series shared by the data loader, the engine and Python code: `Bar` is
+// one row of a data file and `PriceSeries` a column-wise run of them with slicing,
+// resampling and indicator methods.
+
+use chrono::{Datelike, NaiveDate};
+use ndarray::Array1;
+use numpy::{PyArray1, PyReadonlyArray1};
+use pyo3::exceptions::{PyIOError, PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PySlice;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::os::raw::c_long;
+
+use crate::indicators::padding::Padding;
+use crate::indicators::{atr, ewm, rsi_method, sma_method, std_method, volume_method};
+
+const RESAMPLE_NAMES: [&str; 3] = ["day", "week", "month"];
+
+/// One OHLCV bar; `timestamp` is a date or an intraday timestamp as in the data files.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct Bar {
+    #[pyo3(get)]
+    pub timestamp: String,
+    #[pyo3(get)]
+    pub open: f64,
+    #[pyo3(get)]
+    pub high: f64,
+    #[pyo3(get)]
+    pub low: f64,
+    #[pyo3(get)]
+    pub close: f64,
+    #[pyo3(get)]
+    pub volume: f64,
+}
+
+#[pymethods]
+impl Bar {
+    #[new]
+    fn new(timestamp: String, open: f64, high: f64, low: f64, close: f64, volume: Option<f64>) -> Self {
+        Bar { timestamp, open, high, low, close, volume: volume.unwrap_or(0.0) }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Bar({}, open={}, high={}, low={}, close={}, volume={})",
+            self.timestamp, self.open, self.high, self.low, self.close, self.volume
+        )
+    }
+}
+
+/// Header names of an adjusted-close column, compared in lowercase.
+const ADJ_CLOSE_NAMES: [&str; 4] = ["adj_close", "adj close", "adjclose", "adjusted_close"];
+
+/// Reads `date,open,high,low,close[,volume]` rows after a header. Rows with an unparseable
+/// close are skipped; unparseable open/high/low fall back to the close and volume to 0.
+pub fn read_csv(path: &str) -> Result<Vec<Bar>, std::io::Error> {
+    Ok(read_csv_adjusted(path)?.0)
+}
+
+/// `read_csv`, plus each row's adjusted close when the header names an adjusted-close column
+/// (an unparseable value falls back to the close). A header `volume` column is read wherever
+/// it is, so files laid out as `date,open,high,low,close,adj_close,volume` load as well.
+pub fn read_csv_adjusted(path: &str) -> Result<(Vec<Bar>, Option<Vec<f64>>), std::io::Error> {
+    let mut lines = BufReader::new(File::open(path)?).lines();
+    let header: Vec<String> = match lines.next() {
+        Some(line) => line?.split(',').map(|c| c.trim().to_lowercase()).collect(),
+        None => Vec::new(),
+    };
+    let adj_col = header.iter().position(|c| ADJ_CLOSE_NAMES.contains(&c.as_str()));
+    let volume_col = header.iter().position(|c| c == "volume").unwrap_or(5);
+
+    let mut rows = Vec::new();
+    let mut adjusted = Vec::new();
+    for line in lines {
+        let Ok(line) = line else { continue };
+        let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+        if parts.len() < 5 {
+            continue;
+        }
+        let Ok(close) = parts[4].parse::<f64>() else { continue };
+        let field = |i: usize| parts.get(i).and_then(|v| v.parse::<f64>().ok()).unwrap_or(close);
+        if let Some(col) = adj_col {
+            adjusted.push(field(col));
+        }
+        rows.push(Bar {
+            timestamp: parts[0].to_string(),
+            open: field(1),
+            high: field(2),
+            low: field(3),
+            close,
+            volume: parts.get(volume_col).and_then(|v| v.parse().ok()).unwrap_or(0.0),
+        });
+    }
+    Ok((rows, adj_col.map(|_| adjusted)))
+}
+
+/// How `resample` groups bars.
+enum Resample {
+    /// Consecutive runs of `n` bars
+    Every(usize),
+    /// Bars sharing a calendar day, ISO week or month
+    Calendar(&'static str),
+}
+
+impl Resample {
+    fn parse(rule: &PyAny) -> PyResult<Self> {
+        if let Ok(n) = rule.extract::<usize>() {
+            if n == 0 {
+                return Err(PyValueError::new_err("resample needs at least 1 bar per group"));
+            }
+            return Ok(Resample::Every(n));
+        }
+        let name: &str = rule.extract()?;
+        RESAMPLE_NAMES.into_iter().find(|r| *r == name).map(Resample::Calendar).ok_or_else(|| {
+            PyValueError::new_err(format!("unknown resample rule '{}', expected a bar count or one of {}", name, RESAMPLE_NAMES.join(", ")))
+        })
+    }
+
+    /// Group key of bar `k`; bars whose timestamp has no date fall in their own group.
+    fn key(&self, k: usize, timestamp: &str) -> (i64, i64) {
+        let date = timestamp.get(..10).and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        match (self, date) {
+            (Resample::Every(n), _) => ((k / n) as i64, 0),
+            (Resample::Calendar("day"), Some(d)) => (d.num_days_from_ce() as i64, 0),
+            (Resample::Calendar("week"), Some(d)) => (d.iso_week().year() as i64, d.iso_week().week() as i64),
+            (Resample::Calendar(_), Some(d)) => (d.year() as i64, d.month() as i64),
+            (Resample::Calendar(_), None) => (i64::MIN, k as i64),
+        }
+    }
+}
+
+/// A run of OHLCV bars stored column-wise. Indexing gives a `Bar`, slicing another series.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct PriceSeries {
+    timestamps: Vec<String>,
+    open: Array1<f64>,
+    high: Array1<f64>,
+    low: Array1<f64>,
+    close: Array1<f64>,
+    volume: Array1<f64>,
+}
+
+impl PriceSeries {
+    pub fn from_bars(bars: &[Bar]) -> Self {
+        let column = |f: fn(&Bar) -> f64| Array1::from_iter(bars.iter().map(f));
+        PriceSeries {
+            timestamps: bars.iter().map(|b| b.timestamp.clone()).collect(),
+            open: column(|b| b.open),
+            high: column(|b| b.high),
+            low: column(|b| b.low),
+            close: column(|b| b.close),
+            volume: column(|b| b.volume),
+        }
+    }
+
+    fn bar(&self, k: usize) -> Bar {
+        Bar {
+            timestamp: self.timestamps[k].clone(),
+            open: self.open[k],
+            high: self.high[k],
+            low: self.low[k],
+            close: self.close[k],
+            volume: self.volume[k],
+        }
+    }
+
+    fn select(&self, indices: impl Iterator<Item = usize>) -> Self {
+        let bars: Vec<Bar> = indices.map(|k| self.bar(k)).collect();
+        PriceSeries::from_bars(&bars)
+    }
+}
+
+fn to_py<'py>(py: Python<'py>, values: Array1<f64>, warmup: usize, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+    Ok(PyArray1::from_owned_array(py, Padding::parse(padding)?.apply_with(values, warmup)))
+}
+
+#[pymethods]
+impl PriceSeries {
+    /// Columns of equal length; `volume` defaults to zeros.
+    #[new]
+    fn new(
+        timestamps: Vec<String>,
+        open: PyReadonlyArray1<f64>,
+        high: PyReadonlyArray1<f64>,
+        low: PyReadonlyArray1<f64>,
+        close: PyReadonlyArray1<f64>,
+        volume: Option<PyReadonlyArray1<f64>>,
+    ) -> PyResult<Self> {
+        let n = timestamps.len();
+        let volume = volume.map_or_else(|| Array1::zeros(n), |v| v.as_array().to_owned());
+        let series = PriceSeries {
+            timestamps,
+            open: open.as_array().to_owned(),
+            high: high.as_array().to_owned(),
+            low: low.as_array().to_owned(),
+            close: close.as_array().to_owned(),
+            volume,
+        };
+        if [&series.open, &series.high, &series.low, &series.close, &series.volume].iter().any(|c| c.len() != n) {
+            return Err(PyValueError::new_err("timestamps and price columns must have the same length"));
+        }
+        Ok(series)
+    }
+
+    /// Reads a data file the way the engine does.
+    #[staticmethod]
+    fn from_csv(path: &str) -> PyResult<Self> {
+        let bars = read_csv(path).map_err(|e| PyIOError::new_err(format!("reading {}: {}", path, e)))?;
+        Ok(PriceSeries::from_bars(&bars))
+    }
+
+    /// Writes the series as a data file (`date,open,high,low,close,volume`).
+    fn to_csv(&self, path: &str) -> PyResult<()> {
+        let mut text = String::from("date,open,high,low,close,volume\n");
+        for k in 0..self.timestamps.len() {
+            text.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                self.timestamps[k], self.open[k], self.high[k], self.low[k], self.close[k], self.volume[k]
+            ));
+        }
+        fs::write(path, text).map_err(|e| PyIOError::new_err(format!("writing {}: {}", path, e)))
+    }
+
+    #[getter]
+    fn timestamps(&self) -> Vec<String> {
+        self.timestamps.clone()
+    }
+
+    #[getter]
+    fn open<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+        PyArray1::from_owned_array(py, self.open.clone())
+    }
+
+    #[getter]
+    fn high<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+        PyArray1::from_owned_array(py, self.high.clone())
+    }
+
+    #[getter]
+    fn low<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+        PyArray1::from_owned_array(py, self.low.clone())
+    }
+
+    #[getter]
+    fn close<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+        PyArray1::from_owned_array(py, self.close.clone())
+    }
+
+    #[getter]
+    fn volume<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+        PyArray1::from_owned_array(py, self.volume.clone())
+    }
+
+    fn __len__(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    /// `series[k]` (negative counts from the end) is a `Bar`; `series[a:b:step]` a `PriceSeries`.
+    fn __getitem__(&self, py: Python<'_>, index: &PyAny) -> PyResult<PyObject> {
+        let n = self.timestamps.len();
+        if let Ok(slice) = index.downcast::<PySlice>() {
+            let s = slice.indices(n as c_long)?;
+            let picked = (0..s.slicelength).map(|k| (s.start + k * s.step) as usize);
+            return Ok(Py::new(py, self.select(picked))?.into_py(py));
+        }
+        let k: isize = index.extract()?;
+        let k = if k < 0 { k + n as isize } else { k };
+        if k < 0 || k as usize >= n {
+            return Err(PyIndexError::new_err(format!("bar index {} out of range for {} bars", k, n)));
+        }
+        Ok(Py::new(py, self.bar(k as usize))?.into_py(py))
+    }
+
+    fn __repr__(&self) -> String {
+        match (self.timestamps.first(), self.timestamps.last()) {
+            (Some(first), Some(last)) => format!("PriceSeries({} bars, {} .. {})", self.timestamps.len(), first, last),
+            _ => "PriceSeries(0 bars)".to_string(),
+        }
+    }
+
+    /// Aggregates consecutive bars: `rule` is a bar count or "day", "week" (ISO) or "month" of
+    /// the timestamp's date. Each group opens at its first open, closes at its last close,
+    /// spans its highest high and lowest low, sums the volume and is stamped with its last
+    /// bar's timestamp.
+    fn resample(&self, rule: &PyAny) -> PyResult<Self> {
+        let rule = Resample::parse(rule)?;
+        let mut bars: Vec<Bar> = Vec::new();
+        let mut current_key = None;
+        for k in 0..self.timestamps.len() {
+            let key = rule.key(k, &self.timestamps[k]);
+            let bar = self.bar(k);
+            match bars.last_mut() {
+                Some(group) if current_key == Some(key) => {
+                    group.timestamp = bar.timestamp;
+                    group.high = group.high.max(bar.high);
+                    group.low = group.low.min(bar.low);
+                    group.close = bar.close;
+                    group.volume += bar.volume;
+                }
+                _ => bars.push(bar),
+            }
+            current_key = Some(key);
+        }
+        Ok(PriceSeries::from_bars(&bars))
+    }
+
+    /// Simple moving average of the close over `n` bars.
+    fn sma<'py>(&self, py: Python<'py>, n: usize, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+        to_py(py, sma_method::sma(&self.close, n), n.saturating_sub(1), padding)
+    }
+
+    /// Exponential moving average of the close over `n` bars.
+    fn ema<'py>(&self, py: Python<'py>, n: usize, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+        to_py(py, ewm::ewm(&self.close, n), n.saturating_sub(1), padding)
+    }
+
+    /// RSI of the close over `n` bars (default 14).
+    fn rsi<'py>(&self, py: Python<'py>, n: Option<usize>, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+        let n = n.unwrap_or(14);
+        to_py(py, rsi_method::rsi(&self.close, n), n, padding)
+    }
+
+    /// Rolling standard deviation of the close over `n` bars.
+    fn rolling_std<'py>(&self, py: Python<'py>, n: usize, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+        to_py(py, std_method::rolling_std(&self.close, n), n.saturating_sub(1), padding)
+    }
+
+    /// Average true range over `n` bars (default 14).
+    fn atr<'py>(&self, py: Python<'py>, n: Option<usize>, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+        let n = n.unwrap_or(14);
+        to_py(py, atr::atr(&self.high, &self.low, &self.close, n), n.saturating_sub(1), padding)
+    }
+
+    /// On-balance volume.
+    fn obv<'py>(&self, py: Python<'py>, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+        to_py(py, volume_method::obv(&self.close, &self.volume), 0, padding)
+    }
+
+    /// Money flow index over `n` bars (default 14).
+    fn money_flow_index<'py>(&self, py: Python<'py>, n: Option<usize>, padding: Option<&str>) -> PyResult<&'py PyArray1<f64>> {
+        let n = n.unwrap_or(14);
+        to_py(py, volume_method::mfi(&self.high, &self.low, &self.close, &self.volume, n), n, padding)
+    }
+}
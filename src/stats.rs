@@ -0,0 +1,489 @@
+use numpy::{PyArray1, PyReadonlyArray1};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::rng::SplitMix64;
+
+pub const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+const EULER_MASCHERONI: f64 = 0.577_215_664_901_532_9;
+
+pub fn mean(x: &Vec<f64>) -> f64 {
+    if x.is_empty() { return 0.0; }
+    x.iter().sum::<f64>() / (x.len() as f64)
+}
+
+pub fn var_sample(x: &Vec<f64>) -> f64 {
+    let n = x.len();
+    if n < 2 { return 0.0; }
+    let m = mean(x);
+    x.iter().map(|v| (v - m).powi(2)).sum::<f64>() / ((n - 1) as f64)
+}
+
+pub fn std_sample(x: &Vec<f64>) -> f64 {
+    var_sample(x).sqrt()
+}
+
+/// Sample covariance of two equally long series.
+pub fn cov_sample(x: &Vec<f64>, y: &Vec<f64>) -> f64 {
+    let n = x.len().min(y.len());
+    if n < 2 { return 0.0; }
+    let (mx, my) = (mean(x), mean(y));
+    x.iter().zip(y).map(|(a, b)| (a - mx) * (b - my)).sum::<f64>() / ((n - 1) as f64)
+}
+
+/// Beta of `returns` against `benchmark` returns; 0 when the benchmark is flat.
+pub fn beta(returns: &Vec<f64>, benchmark: &Vec<f64>) -> f64 {
+    let var_b = var_sample(benchmark);
+    if var_b > f64::EPSILON { cov_sample(returns, benchmark) / var_b } else { 0.0 }
+}
+
+/// Sample skewness (population moments).
+pub fn skewness(x: &Vec<f64>) -> f64 {
+    let n = x.len() as f64;
+    if n < 3.0 { return 0.0; }
+    let m = mean(x);
+    let m2 = x.iter().map(|v| (v - m).powi(2)).sum::<f64>() / n;
+    let m3 = x.iter().map(|v| (v - m).powi(3)).sum::<f64>() / n;
+    if m2 <= 0.0 { 0.0 } else { m3 / m2.powf(1.5) }
+}
+
+/// Kurtosis (non-excess, normal = 3).
+pub fn kurtosis(x: &Vec<f64>) -> f64 {
+    let n = x.len() as f64;
+    if n < 4.0 { return 3.0; }
+    let m = mean(x);
+    let m2 = x.iter().map(|v| (v - m).powi(2)).sum::<f64>() / n;
+    let m4 = x.iter().map(|v| (v - m).powi(4)).sum::<f64>() / n;
+    if m2 <= 0.0 { 3.0 } else { m4 / (m2 * m2) }
+}
+
+/// Equal-width histogram over [min, max] of the finite values. Returns (counts, edges) with
+/// `bins + 1` edges; the last bin is closed on the right.
+pub fn histogram(x: &Vec<f64>, bins: usize) -> (Vec<u64>, Vec<f64>) {
+    let finite: Vec<f64> = x.iter().cloned().filter(|v| v.is_finite()).collect();
+    if finite.is_empty() || bins == 0 { return (vec![0; bins], Vec::new()); }
+
+    let lo = finite.iter().cloned().fold(f64::INFINITY, f64::min);
+    let mut hi = finite.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if hi <= lo { hi = lo + 1e-12; }
+    let width = (hi - lo) / bins as f64;
+
+    let mut counts = vec![0u64; bins];
+    for v in finite {
+        let k = (((v - lo) / width) as usize).min(bins - 1);
+        counts[k] += 1;
+    }
+    let edges = (0..=bins).map(|k| lo + width * k as f64).collect();
+    (counts, edges)
+}
+
+/// Annualized Sharpe of per-bar returns: mean / std * sqrt(252).
+pub fn sharpe_of_returns(returns: &Vec<f64>) -> f64 {
+    let sd = std_sample(returns);
+    if sd > 0.0 { mean(returns) / sd * TRADING_DAYS_PER_YEAR.sqrt() } else { 0.0 }
+}
+
+/// Bar-to-bar fractional changes of `series`; a change from zero counts as 0.
+pub fn pct_changes(series: &Vec<f64>) -> Vec<f64> {
+    if series.len() < 2 { return Vec::new(); }
+    let mut res = Vec::with_capacity(series.len() - 1);
+    for i in 1..series.len() {
+        let prev = series[i-1];
+        if prev.abs() < f64::EPSILON { res.push(0.0); }
+        else { res.push((series[i] / prev) - 1.0); }
+    }
+    res
+}
+
+/// Compound annual growth of an equity curve with `periods_per_year` bars per year.
+pub fn annualized_return(series: &[f64], periods_per_year: f64) -> f64 {
+    match (series.first(), series.last()) {
+        // No growth rate exists from a zero or negative start.
+        (Some(first), Some(_)) if *first <= 0.0 => f64::NAN,
+        (Some(first), Some(last)) => (last / first).powf(periods_per_year / series.len() as f64) - 1.0,
+        _ => 0.0,
+    }
+}
+
+/// Sample standard deviation of per-bar returns scaled to a year of `periods_per_year` bars.
+pub fn annualized_volatility(returns: &Vec<f64>, periods_per_year: f64) -> f64 {
+    std_sample(returns) * periods_per_year.sqrt()
+}
+
+/// Annualized Sharpe ratio of an equity curve sampled once per bar: annualized return in
+/// excess of `risk_free_rate_annual` over annualized volatility.
+pub fn sharpe_ratio(series: &Vec<f64>, risk_free_rate_annual: f64) -> f64 {
+    let annualized_vol = annualized_volatility(&pct_changes(series), TRADING_DAYS_PER_YEAR);
+    if annualized_vol > 0.0 {
+        (annualized_return(series, TRADING_DAYS_PER_YEAR) - risk_free_rate_annual) / annualized_vol
+    } else { 0.0 }
+}
+
+/// Largest peak-to-trough decline of `series` as a fraction of the peak.
+pub fn max_drawdown(series: &Vec<f64>) -> f64 {
+    if series.is_empty() { return 0.0; }
+    let mut peak = series[0];
+    let mut max_dd = 0.0;
+    for &v in series {
+        if v > peak { peak = v; }
+        let dd = if peak > 0.0 { (peak - v) / peak } else { 0.0 };
+        if dd > max_dd { max_dd = dd; }
+    }
+    max_dd
+}
+
+/// Fractional distance below the running peak at every bar (0 at new highs, negative otherwise).
+pub fn underwater_curve(series: &Vec<f64>) -> Vec<f64> {
+    let mut peak = f64::NEG_INFINITY;
+    series.iter().map(|&v| {
+        if v > peak { peak = v; }
+        if peak > 0.0 { v / peak - 1.0 } else { 0.0 }
+    }).collect()
+}
+
+/// One peak-to-recovery episode. `recovery` is None if the series never regained the peak.
+#[derive(Debug, Clone)]
+pub struct Drawdown {
+    pub start: usize,
+    pub trough: usize,
+    pub recovery: Option<usize>,
+    pub depth: f64,
+}
+
+impl Drawdown {
+    /// Bars from peak to recovery, or to the last bar if still underwater.
+    pub fn duration(&self, len: usize) -> usize {
+        self.recovery.unwrap_or(len.saturating_sub(1)) - self.start
+    }
+}
+
+/// All drawdown episodes of `series`, deepest first, truncated to `top_k`.
+pub fn drawdowns(series: &Vec<f64>, top_k: usize) -> Vec<Drawdown> {
+    let mut episodes: Vec<Drawdown> = Vec::new();
+    let mut current: Option<Drawdown> = None;
+    let mut peak_idx = 0;
+
+    for (i, &v) in series.iter().enumerate() {
+        let peak = series[peak_idx];
+        if v >= peak {
+            if let Some(mut dd) = current.take() {
+                dd.recovery = Some(i);
+                episodes.push(dd);
+            }
+            peak_idx = i;
+            continue;
+        }
+        let depth = if peak > 0.0 { (peak - v) / peak } else { 0.0 };
+        match current.as_mut() {
+            Some(dd) if depth > dd.depth => { dd.depth = depth; dd.trough = i; }
+            Some(_) => {}
+            None => current = Some(Drawdown { start: peak_idx, trough: i, recovery: None, depth }),
+        }
+    }
+    episodes.extend(current);
+
+    episodes.sort_by(|a, b| b.depth.partial_cmp(&a.depth).unwrap_or(std::cmp::Ordering::Equal));
+    episodes.truncate(top_k);
+    episodes
+}
+
+// ----------------- Distributions -----------------
+
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / std::f64::consts::SQRT_2))
+}
+
+/// Abramowitz-Stegun 7.1.26 (max error ~1.5e-7).
+fn erf(x: f64) -> f64 {
+    let sign = if x < 0.0 { -1.0 } else { 1.0 };
+    let x = x.abs();
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let y = 1.0 - (((((1.061_405_429 * t - 1.453_152_027) * t) + 1.421_413_741) * t - 0.284_496_736) * t + 0.254_829_592) * t * (-x * x).exp();
+    sign * y
+}
+
+/// Inverse standard normal CDF (Acklam's rational approximation).
+pub fn normal_ppf(p: f64) -> f64 {
+    if p <= 0.0 { return f64::NEG_INFINITY; }
+    if p >= 1.0 { return f64::INFINITY; }
+    const A: [f64; 6] = [-3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2, 1.383577518672690e2, -3.066479806614716e1, 2.506628277459239];
+    const B: [f64; 5] = [-5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2, 6.680131188771972e1, -1.328068155288572e1];
+    const C: [f64; 6] = [-7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838, -2.549732539343734, 4.374664141464968, 2.938163982698783];
+    const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996, 3.754408661907416];
+    let p_low = 0.02425;
+    if p < p_low {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5]) / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    } else if p <= 1.0 - p_low {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -normal_ppf(1.0 - p)
+    }
+}
+
+fn ln_gamma(x: f64) -> f64 {
+    // Lanczos approximation (g = 7).
+    const COEF: [f64; 9] = [0.999_999_999_999_809_9, 676.520_368_121_885_1, -1_259.139_216_722_402_8, 771.323_428_777_653_1, -176.615_029_162_140_6, 12.507_343_278_686_905, -0.138_571_095_265_720_12, 9.984_369_578_019_572e-6, 1.505_632_735_149_311_6e-7];
+    if x < 0.5 {
+        return std::f64::consts::PI.ln() - (std::f64::consts::PI * x).sin().ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut a = COEF[0];
+    let t = x + 7.5;
+    for (i, c) in COEF.iter().enumerate().skip(1) {
+        a += c / (x + i as f64);
+    }
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + a.ln()
+}
+
+/// Regularized incomplete beta I_x(a, b) via Lentz's continued fraction.
+fn incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 { return 0.0; }
+    if x >= 1.0 { return 1.0; }
+    if x > (a + 1.0) / (a + b + 2.0) {
+        return 1.0 - incomplete_beta(1.0 - x, b, a);
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp() / a;
+
+    let tiny = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < tiny { d = tiny; }
+    d = 1.0 / d;
+    let mut f = d;
+    for m in 1..300 {
+        let m = m as f64;
+        let num = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 + num * d; if d.abs() < tiny { d = tiny; } d = 1.0 / d;
+        c = 1.0 + num / c; if c.abs() < tiny { c = tiny; }
+        f *= d * c;
+        let num = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 + num * d; if d.abs() < tiny { d = tiny; } d = 1.0 / d;
+        c = 1.0 + num / c; if c.abs() < tiny { c = tiny; }
+        let delta = d * c;
+        f *= delta;
+        if (delta - 1.0).abs() < 1e-12 { break; }
+    }
+    front * f
+}
+
+/// Two-sided p-value of Student's t with `df` degrees of freedom.
+pub fn t_two_sided_p(t: f64, df: f64) -> f64 {
+    if !t.is_finite() { return 0.0; }
+    incomplete_beta(df / (df + t * t), df / 2.0, 0.5)
+}
+
+// ----------------- Significance tests -----------------
+
+/// One-sample t-test of mean return against zero. Returns (t, two-sided p-value).
+pub fn t_test(returns: &Vec<f64>) -> (f64, f64) {
+    let n = returns.len();
+    if n < 2 { return (0.0, 1.0); }
+    let se = std_sample(returns) / (n as f64).sqrt();
+    if se <= 0.0 { return (0.0, 1.0); }
+    let t = mean(returns) / se;
+    (t, t_two_sided_p(t, (n - 1) as f64))
+}
+
+/// Draws a moving-block bootstrap resample of indices in [0, n) with blocks of `block_size`.
+fn block_resample(rng: &mut SplitMix64, n: usize, block_size: usize) -> Vec<usize> {
+    let block_size = block_size.clamp(1, n);
+    let mut idx = Vec::with_capacity(n);
+    while idx.len() < n {
+        let start = rng.below(n - block_size + 1);
+        for k in start..(start + block_size).min(start + n - idx.len()) {
+            idx.push(k);
+        }
+    }
+    idx
+}
+
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() { return f64::NAN; }
+    let pos = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lo = pos.floor() as usize;
+    let hi = pos.ceil() as usize;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
+}
+
+/// Percentile bootstrap confidence interval of the annualized Sharpe of `returns`.
+/// Returns (sharpe, lower, upper).
+pub fn bootstrap_sharpe_ci(returns: &Vec<f64>, n_boot: usize, confidence: f64, block_size: usize, seed: u64) -> (f64, f64, f64) {
+    let point = sharpe_of_returns(returns);
+    if returns.len() < 2 || n_boot == 0 { return (point, point, point); }
+
+    let mut rng = SplitMix64::new(seed);
+    let mut samples: Vec<f64> = (0..n_boot)
+        .map(|_| {
+            let resampled: Vec<f64> = block_resample(&mut rng, returns.len(), block_size).into_iter().map(|i| returns[i]).collect();
+            sharpe_of_returns(&resampled)
+        })
+        .collect();
+    samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let alpha = (1.0 - confidence) / 2.0;
+    (point, quantile(&samples, alpha), quantile(&samples, 1.0 - alpha))
+}
+
+/// Deflated Sharpe ratio (Bailey & Lopez de Prado): probability that the observed Sharpe is
+/// above the maximum expected from `trial_sharpes.len()` unskilled trials, correcting for the
+/// skew and kurtosis of `returns`. Sharpes are annualized and converted to per-bar internally.
+pub fn deflated_sharpe(returns: &Vec<f64>, observed_sharpe: f64, trial_sharpes: &Vec<f64>) -> f64 {
+    let n_obs = returns.len() as f64;
+    let n_trials = trial_sharpes.len() as f64;
+    if n_obs < 2.0 { return 0.0; }
+    let per_bar = TRADING_DAYS_PER_YEAR.sqrt();
+
+    let sr = observed_sharpe / per_bar;
+    let trials: Vec<f64> = trial_sharpes.iter().map(|s| s / per_bar).collect();
+    let sr0 = if n_trials > 1.0 {
+        let sd = std_sample(&trials);
+        sd * ((1.0 - EULER_MASCHERONI) * normal_ppf(1.0 - 1.0 / n_trials)
+            + EULER_MASCHERONI * normal_ppf(1.0 - 1.0 / (n_trials * std::f64::consts::E)))
+    } else { 0.0 };
+
+    let skew = skewness(returns);
+    let kurt = kurtosis(returns);
+    let denom = (1.0 - skew * sr + (kurt - 1.0) / 4.0 * sr * sr).max(f64::EPSILON).sqrt();
+    normal_cdf((sr - sr0) * (n_obs - 1.0).sqrt() / denom)
+}
+
+/// White's reality check: p-value of the null that no strategy in `candidates` (per-bar
+/// returns of equal length, e.g. one per optimizer trial) has positive expected return, using
+/// a moving-block bootstrap shared across candidates.
+pub fn reality_check(candidates: &Vec<Vec<f64>>, n_boot: usize, block_size: usize, seed: u64) -> f64 {
+    let n = candidates.first().map_or(0, |c| c.len());
+    if n < 2 || n_boot == 0 { return 1.0; }
+
+    let sqrt_n = (n as f64).sqrt();
+    let means: Vec<f64> = candidates.iter().map(mean).collect();
+    let observed = means.iter().fold(f64::NEG_INFINITY, |m, &v| m.max(sqrt_n * v));
+
+    let mut rng = SplitMix64::new(seed);
+    let mut exceed = 0;
+    for _ in 0..n_boot {
+        let idx = block_resample(&mut rng, n, block_size);
+        let stat = candidates.iter().zip(means.iter())
+            .map(|(c, m)| sqrt_n * (idx.iter().map(|&i| c[i]).sum::<f64>() / n as f64 - m))
+            .fold(f64::NEG_INFINITY, f64::max);
+        if stat >= observed { exceed += 1; }
+    }
+    exceed as f64 / n_boot as f64
+}
+
+// ----------------- Python bindings -----------------
+
+fn to_vec(a: &PyReadonlyArray1<f64>) -> Vec<f64> {
+    a.as_array().to_vec()
+}
+
+/// Bar-to-bar fractional changes of a price or equity series, one shorter than it.
+#[pyfunction]
+#[pyo3(name = "pct_changes")]
+fn py_pct_changes(py: Python<'_>, series: PyReadonlyArray1<f64>) -> Py<PyArray1<f64>> {
+    PyArray1::from_vec(py, pct_changes(&to_vec(&series))).into()
+}
+
+/// Sample standard deviation (n - 1 denominator); 0 for fewer than two values.
+#[pyfunction]
+#[pyo3(name = "std_sample")]
+fn py_std_sample(x: PyReadonlyArray1<f64>) -> f64 {
+    std_sample(&to_vec(&x))
+}
+
+/// Largest peak-to-trough decline of an equity series as a fraction of the peak (0.25 = 25%).
+#[pyfunction]
+#[pyo3(name = "max_drawdown")]
+fn py_max_drawdown(series: PyReadonlyArray1<f64>) -> f64 {
+    max_drawdown(&to_vec(&series))
+}
+
+/// Compound annual growth of an equity series with `periods_per_year` bars a year (default 252).
+#[pyfunction]
+#[pyo3(name = "annualized_return")]
+fn py_annualized_return(series: PyReadonlyArray1<f64>, periods_per_year: Option<f64>) -> f64 {
+    annualized_return(&to_vec(&series), periods_per_year.unwrap_or(TRADING_DAYS_PER_YEAR))
+}
+
+/// Sample standard deviation of per-bar returns scaled to a year of `periods_per_year` bars
+/// (default 252).
+#[pyfunction]
+#[pyo3(name = "annualized_volatility")]
+fn py_annualized_volatility(returns: PyReadonlyArray1<f64>, periods_per_year: Option<f64>) -> f64 {
+    annualized_volatility(&to_vec(&returns), periods_per_year.unwrap_or(TRADING_DAYS_PER_YEAR))
+}
+
+/// Annualized Sharpe ratio of an equity series with daily bars, as the engine reports it:
+/// annualized return minus `risk_free_rate_annual` (default 0) over annualized volatility.
+#[pyfunction]
+#[pyo3(name = "sharpe_ratio")]
+fn py_sharpe_ratio(series: PyReadonlyArray1<f64>, risk_free_rate_annual: Option<f64>) -> f64 {
+    sharpe_ratio(&to_vec(&series), risk_free_rate_annual.unwrap_or(0.0))
+}
+
+/// One-sample t-test of mean per-bar return against zero. Returns (t_stat, p_value).
+#[pyfunction]
+#[pyo3(name = "t_test")]
+fn py_t_test(returns: PyReadonlyArray1<f64>) -> (f64, f64) {
+    t_test(&to_vec(&returns))
+}
+
+/// Bootstrap CI for the annualized Sharpe of per-bar returns. Returns (sharpe, lower, upper).
+#[pyfunction]
+#[pyo3(name = "bootstrap_sharpe_ci")]
+fn py_bootstrap_sharpe_ci(
+    returns: PyReadonlyArray1<f64>,
+    n_boot: Option<usize>,
+    confidence: Option<f64>,
+    block_size: Option<usize>,
+    seed: Option<u64>,
+) -> PyResult<(f64, f64, f64)> {
+    let confidence = confidence.unwrap_or(0.95);
+    if !(0.0..1.0).contains(&confidence) {
+        return Err(PyValueError::new_err("confidence must be in [0, 1)"));
+    }
+    Ok(bootstrap_sharpe_ci(&to_vec(&returns), n_boot.unwrap_or(1000), confidence, block_size.unwrap_or(1), seed.unwrap_or(42)))
+}
+
+/// Deflated Sharpe ratio of the selected strategy given the Sharpes of every trial tried.
+#[pyfunction]
+#[pyo3(name = "deflated_sharpe")]
+fn py_deflated_sharpe(returns: PyReadonlyArray1<f64>, observed_sharpe: f64, trial_sharpes: Vec<f64>) -> f64 {
+    deflated_sharpe(&to_vec(&returns), observed_sharpe, &trial_sharpes)
+}
+
+/// White's reality check p-value over a list of per-bar return arrays (one per trial).
+#[pyfunction]
+#[pyo3(name = "reality_check")]
+fn py_reality_check(
+    candidates: Vec<PyReadonlyArray1<f64>>,
+    n_boot: Option<usize>,
+    block_size: Option<usize>,
+    seed: Option<u64>,
+) -> PyResult<f64> {
+    let candidates: Vec<Vec<f64>> = candidates.iter().map(to_vec).collect();
+    let n = candidates.first().map_or(0, |c| c.len());
+    if candidates.iter().any(|c| c.len() != n) {
+        return Err(PyValueError::new_err("all candidate return arrays must have the same length"));
+    }
+    Ok(reality_check(&candidates, n_boot.unwrap_or(1000), block_size.unwrap_or(1), seed.unwrap_or(42)))
+}
+
+/// Builds the `tradekit_rust.stats` submodule.
+pub fn register(py: Python<'_>, parent: &PyModule) -> PyResult<()> {
+    let m = PyModule::new(py, "stats")?;
+    m.add_function(wrap_pyfunction!(py_pct_changes, m)?)?;
+    m.add_function(wrap_pyfunction!(py_std_sample, m)?)?;
+    m.add_function(wrap_pyfunction!(py_max_drawdown, m)?)?;
+    m.add_function(wrap_pyfunction!(py_annualized_return, m)?)?;
+    m.add_function(wrap_pyfunction!(py_annualized_volatility, m)?)?;
+    m.add_function(wrap_pyfunction!(py_sharpe_ratio, m)?)?;
+    m.add_function(wrap_pyfunction!(py_t_test, m)?)?;
+    m.add_function(wrap_pyfunction!(py_bootstrap_sharpe_ci, m)?)?;
+    m.add_function(wrap_pyfunction!(py_deflated_sharpe, m)?)?;
+    m.add_function(wrap_pyfunction!(py_reality_check, m)?)?;
+    parent.add_submodule(m)?;
+    Ok(())
+}
@@ -0,0 +1,251 @@
+// Seeded synthetic price series (GBM, mean-reverting OU and regime-switching) for testing
+// strategies and engine features without real data. Series come back as OHLCV arrays or are
+// written as `<TICKER>_meso.csv` data files.
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use numpy::{PyArray1, PyReadonlyArray1};
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::rng::SplitMix64;
+use crate::stats::TRADING_DAYS_PER_YEAR;
+
+/// Intrabar spread of the high/low around open and close, as a share of the bar's volatility.
+const INTRABAR_RANGE: f64 = 0.5;
+const MEAN_VOLUME: f64 = 1_000_000.0;
+
+pub struct Ohlcv {
+    pub open: Vec<f64>,
+    pub high: Vec<f64>,
+    pub low: Vec<f64>,
+    pub close: Vec<f64>,
+    pub volume: Vec<f64>,
+}
+
+/// Bars around a close path: each bar opens at the previous close, and the high and low reach
+/// past the open and close by a random part of the bar's volatility `bar_sigma[t]`.
+fn bars_from_closes(close: Vec<f64>, bar_sigma: &[f64], rng: &mut SplitMix64) -> Ohlcv {
+    let n = close.len();
+    let mut bars = Ohlcv {
+        open: Vec::with_capacity(n),
+        high: Vec::with_capacity(n),
+        low: Vec::with_capacity(n),
+        close: Vec::new(),
+        volume: Vec::with_capacity(n),
+    };
+    for t in 0..n {
+        let open = if t == 0 { close[0] } else { close[t - 1] };
+        let reach = |rng: &mut SplitMix64| (rng.normal().abs() * bar_sigma[t] * INTRABAR_RANGE).exp();
+        bars.open.push(open);
+        bars.high.push(open.max(close[t]) * reach(rng));
+        bars.low.push(open.min(close[t]) / reach(rng));
+        bars.volume.push((MEAN_VOLUME * (0.5 * rng.normal()).exp()).round());
+    }
+    bars.close = close;
+    bars
+}
+
+/// Geometric Brownian motion with annualized drift `mu` and volatility `sigma`.
+pub fn gbm(n: usize, start_price: f64, mu: f64, sigma: f64, seed: u64) -> Ohlcv {
+    regime_switching(n, start_price, &[(mu, sigma)], 0.0, seed).0
+}
+
+/// Ornstein-Uhlenbeck process on the log price, pulled towards `mean` at annual rate `theta`
+/// with annualized volatility `sigma`.
+pub fn ornstein_uhlenbeck(n: usize, start_price: f64, mean: f64, theta: f64, sigma: f64, seed: u64) -> Ohlcv {
+    let mut rng = SplitMix64::new(seed);
+    let dt = 1.0 / TRADING_DAYS_PER_YEAR;
+    let target = mean.ln();
+    let mut x = start_price.ln();
+    let mut close = Vec::with_capacity(n);
+    for t in 0..n {
+        if t > 0 {
+            x += theta * (target - x) * dt + sigma * dt.sqrt() * rng.normal();
+        }
+        close.push(x.exp());
+    }
+    bars_from_closes(close, &vec![sigma * dt.sqrt(); n], &mut rng)
+}
+
+/// GBM whose (drift, volatility) pair switches between `regimes` as a Markov chain: each bar
+/// leaves the current regime with probability `switch_prob` for a uniformly chosen other one.
+/// Also returns the regime index of every bar.
+pub fn regime_switching(n: usize, start_price: f64, regimes: &[(f64, f64)], switch_prob: f64, seed: u64) -> (Ohlcv, Vec<usize>) {
+    let mut rng = SplitMix64::new(seed);
+    let dt = 1.0 / TRADING_DAYS_PER_YEAR;
+    let mut regime = 0;
+    let mut price = start_price;
+    let mut close = Vec::with_capacity(n);
+    let mut labels = Vec::with_capacity(n);
+    let mut bar_sigma = Vec::with_capacity(n);
+    for t in 0..n {
+        if t > 0 {
+            if regimes.len() > 1 && rng.next_f64() < switch_prob {
+                regime = (regime + 1 + rng.below(regimes.len() - 1)) % regimes.len();
+            }
+            let (mu, sigma) = regimes[regime];
+            price *= ((mu - 0.5 * sigma * sigma) * dt + sigma * dt.sqrt() * rng.normal()).exp();
+        }
+        close.push(price);
+        labels.push(regime);
+        bar_sigma.push(regimes[regime].1 * dt.sqrt());
+    }
+    (bars_from_closes(close, &bar_sigma, &mut rng), labels)
+}
+
+/// `n` consecutive weekdays from `start` (moved forward to a weekday).
+pub fn weekdays(start: NaiveDate, n: usize) -> Vec<NaiveDate> {
+    let mut dates = Vec::with_capacity(n);
+    let mut d = start;
+    while dates.len() < n {
+        if !matches!(d.weekday(), Weekday::Sat | Weekday::Sun) {
+            dates.push(d);
+        }
+        d = d + Days::new(1);
+    }
+    dates
+}
+
+fn check(n_bars: usize, start_price: f64) -> PyResult<()> {
+    if n_bars == 0 {
+        return Err(PyValueError::new_err("n_bars must be at least 1"));
+    }
+    if start_price.is_nan() || start_price <= 0.0 {
+        return Err(PyValueError::new_err(format!("start_price must be positive, got {}", start_price)));
+    }
+    Ok(())
+}
+
+fn ohlcv_to_py(py: Python<'_>, bars: Ohlcv) -> PyResult<&PyDict> {
+    let out = PyDict::new(py);
+    out.set_item("open", PyArray1::from_vec(py, bars.open))?;
+    out.set_item("high", PyArray1::from_vec(py, bars.high))?;
+    out.set_item("low", PyArray1::from_vec(py, bars.low))?;
+    out.set_item("close", PyArray1::from_vec(py, bars.close))?;
+    out.set_item("volume", PyArray1::from_vec(py, bars.volume))?;
+    Ok(out)
+}
+
+/// GBM bars as a dict of "open", "high", "low", "close" and "volume" arrays. `mu` (default
+/// 0.08) and `sigma` (default 0.2) are annualized.
+#[pyfunction]
+#[pyo3(name = "gbm")]
+fn py_gbm(
+    py: Python<'_>,
+    n_bars: usize,
+    start_price: Option<f64>,
+    mu: Option<f64>,
+    sigma: Option<f64>,
+    seed: Option<u64>,
+) -> PyResult<PyObject> {
+    let start_price = start_price.unwrap_or(100.0);
+    check(n_bars, start_price)?;
+    let bars = gbm(n_bars, start_price, mu.unwrap_or(0.08), sigma.unwrap_or(0.2), seed.unwrap_or(42));
+    Ok(ohlcv_to_py(py, bars)?.to_object(py))
+}
+
+/// Mean-reverting bars whose log price is pulled towards `mean` (default `start_price`) at
+/// annual rate `theta` (default 20, a half-life of about 9 bars); `sigma` (default 0.2) is
+/// annualized.
+#[pyfunction]
+#[pyo3(name = "ornstein_uhlenbeck")]
+fn py_ornstein_uhlenbeck(
+    py: Python<'_>,
+    n_bars: usize,
+    start_price: Option<f64>,
+    mean: Option<f64>,
+    theta: Option<f64>,
+    sigma: Option<f64>,
+    seed: Option<u64>,
+) -> PyResult<PyObject> {
+    let start_price = start_price.unwrap_or(100.0);
+    check(n_bars, start_price)?;
+    let mean = mean.unwrap_or(start_price);
+    if mean.is_nan() || mean <= 0.0 {
+        return Err(PyValueError::new_err(format!("mean must be positive, got {}", mean)));
+    }
+    let bars = ornstein_uhlenbeck(n_bars, start_price, mean, theta.unwrap_or(20.0), sigma.unwrap_or(0.2), seed.unwrap_or(42));
+    Ok(ohlcv_to_py(py, bars)?.to_object(py))
+}
+
+/// Regime-switching GBM bars plus a "regimes" array with each bar's regime index. `regimes`
+/// lists annualized (mu, sigma) pairs, by default a calm bull (0.15, 0.15) and a volatile
+/// bear (-0.2, 0.35); each bar switches regime with probability `switch_prob` (default 0.02).
+#[pyfunction]
+#[pyo3(name = "regime_switching")]
+fn py_regime_switching(
+    py: Python<'_>,
+    n_bars: usize,
+    start_price: Option<f64>,
+    regimes: Option<Vec<(f64, f64)>>,
+    switch_prob: Option<f64>,
+    seed: Option<u64>,
+) -> PyResult<PyObject> {
+    let start_price = start_price.unwrap_or(100.0);
+    check(n_bars, start_price)?;
+    let regimes = regimes.unwrap_or_else(|| vec![(0.15, 0.15), (-0.2, 0.35)]);
+    if regimes.is_empty() {
+        return Err(PyValueError::new_err("regimes must list at least one (mu, sigma) pair"));
+    }
+    let switch_prob = switch_prob.unwrap_or(0.02);
+    if !(0.0..=1.0).contains(&switch_prob) {
+        return Err(PyValueError::new_err(format!("switch_prob must be in [0, 1], got {}", switch_prob)));
+    }
+    let (bars, labels) = regime_switching(n_bars, start_price, &regimes, switch_prob, seed.unwrap_or(42));
+    let out = ohlcv_to_py(py, bars)?;
+    out.set_item("regimes", PyArray1::from_vec(py, labels))?;
+    Ok(out.to_object(py))
+}
+
+/// Writes `bars` to `<data_folder>/<ticker>_meso.csv`, dated on consecutive weekdays from
+/// `start`, creating the folder if needed.
+pub fn write_csv(bars: &Ohlcv, data_folder: &Path, ticker: &str, start: NaiveDate) -> std::io::Result<PathBuf> {
+    let mut text = String::from("date,open,high,low,close,volume\n");
+    for (t, date) in weekdays(start, bars.close.len()).into_iter().enumerate() {
+        text.push_str(&format!(
+            "{},{},{},{},{},{}\n", date, bars.open[t], bars.high[t], bars.low[t], bars.close[t], bars.volume[t]
+        ));
+    }
+    fs::create_dir_all(data_folder)?;
+    let path = data_folder.join(format!("{}_meso.csv", ticker));
+    fs::write(&path, text)?;
+    Ok(path)
+}
+
+/// Writes generated bars (a dict from one of the generators) to
+/// `<data_folder>/<ticker>_meso.csv`, dated on consecutive weekdays from `start_date`
+/// (default "2020-01-01"). Returns the path.
+#[pyfunction]
+#[pyo3(name = "write")]
+fn py_write(bars: &PyDict, data_folder: &str, ticker: &str, start_date: Option<&str>) -> PyResult<String> {
+    let column = |key: &str| -> PyResult<Vec<f64>> {
+        let values: PyReadonlyArray1<f64> = bars.get_item(key)
+            .ok_or_else(|| PyValueError::new_err(format!("bars have no '{}'", key)))?
+            .extract()?;
+        Ok(values.as_array().to_vec())
+    };
+    let (open, high, low, close, volume) = (column("open")?, column("high")?, column("low")?, column("close")?, column("volume")?);
+    if [&open, &high, &low, &volume].iter().any(|c| c.len() != close.len()) {
+        return Err(PyValueError::new_err("bar columns must have the same length"));
+    }
+    let start = start_date.unwrap_or("2020-01-01");
+    let start = NaiveDate::parse_from_str(start, "%Y-%m-%d")
+        .map_err(|e| PyValueError::new_err(format!("invalid start_date '{}': {}", start, e)))?;
+
+    let path = write_csv(&Ohlcv { open, high, low, close, volume }, Path::new(data_folder), ticker, start)
+        .map_err(|e| PyIOError::new_err(format!("writing {} to {}: {}", ticker, data_folder, e)))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+pub fn register(py: Python<'_>, parent: &PyModule) -> PyResult<()> {
+    let m = PyModule::new(py, "synthetic")?;
+    m.add_function(wrap_pyfunction!(py_gbm, m)?)?;
+    m.add_function(wrap_pyfunction!(py_ornstein_uhlenbeck, m)?)?;
+    m.add_function(wrap_pyfunction!(py_regime_switching, m)?)?;
+    m.add_function(wrap_pyfunction!(py_write, m)?)?;
+    parent.add_submodule(m)?;
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+
+/// Local intraday formats accepted for timestamps without an offset.
+const NAIVE_FORMATS: [&str; 4] = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"];
+
+/// Parses an IANA timezone name such as "Asia/Kolkata" or "UTC".
+pub fn parse_tz(name: &str) -> PyResult<Tz> {
+    name.parse::<Tz>()
+        .map_err(|_| PyValueError::new_err(format!("unknown timezone '{}'", name)))
+}
+
+/// Parses an intraday timestamp. Timestamps carrying an offset keep it, naive ones are read as
+/// local time in `tz`. Date-only values return `None` since they already name a session.
+/// Ambiguous local times (DST fall-back) resolve to the earlier instant.
+pub fn parse_instant(s: &str, tz: Tz) -> Option<DateTime<Utc>> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Some(t.with_timezone(&Utc));
+    }
+    if let Ok(t) = DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%:z") {
+        return Some(t.with_timezone(&Utc));
+    }
+    let naive = NAIVE_FORMATS.iter().find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())?;
+    tz.from_local_datetime(&naive).earliest().map(|t| t.with_timezone(&Utc))
+}
+
+/// Canonical UTC form; sorts chronologically across exchanges.
+pub fn format_utc(t: &DateTime<Utc>) -> String {
+    t.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// Calendar date of the trading session in the exchange timezone.
+pub fn session_date(t: &DateTime<Utc>, tz: Tz) -> String {
+    t.with_timezone(&tz).format("%Y-%m-%d").to_string()
+}